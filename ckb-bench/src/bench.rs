@@ -6,7 +6,7 @@ use ckb_testkit::ckb_types::{
     prelude::*,
 };
 use ckb_testkit::util::since_from_absolute_epoch_number_with_fraction;
-use ckb_testkit::{Node, User};
+use ckb_testkit::{AsyncRpcClient, Node, RpcError, User};
 use crossbeam_channel::{Receiver, Sender};
 use lru::LruCache;
 use std::collections::HashMap;
@@ -292,15 +292,14 @@ impl TransactionProducer {
 }
 
 pub struct TransactionConsumer {
-    nodes: Vec<Node>,
+    rpc_clients: Vec<AsyncRpcClient>,
 }
 
 
 impl TransactionConsumer {
     pub fn new(nodes: Vec<Node>) -> Self {
-        Self {
-            nodes
-        }
+        let rpc_clients = nodes.iter().map(Node::async_rpc_client).collect();
+        Self { rpc_clients }
     }
 
     pub async fn run(
@@ -335,13 +334,13 @@ impl TransactionConsumer {
                 async_sleep(t_tx_interval).await;
             }
 
-            i = (i + 1) % self.nodes.len();
-            let node = self.nodes[i].clone();
+            i = (i + 1) % self.rpc_clients.len();
+            let rpc_client = self.rpc_clients[i].clone();
             let permit = semaphore.clone().acquire_owned().await;
             let tx_hash = tx.hash();
             let begin_time = Instant::now();
             let task = async move {
-                let result = maybe_retry_send_transaction_async(&node, &tx).await;
+                let result = maybe_retry_send_transaction_async(&rpc_client, &tx).await;
                 drop(permit);
                 (result, tx_hash, Instant::now() - begin_time)
             };
@@ -370,7 +369,7 @@ impl TransactionConsumer {
                     tx_hash,
                     err
                 );
-                        if !err.contains("TransactionFailedToResolve") {
+                        if !matches!(err, RpcError::TransactionFailedToResolve(_)) {
                             ckb_testkit::error!(
                         "failed to send tx {:#x}, error: {}",
                         tx_hash,
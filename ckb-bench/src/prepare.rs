@@ -7,7 +7,7 @@ use ckb_testkit::ckb_types::{
     packed::{Byte32, CellInput, CellOutput, OutPoint, WitnessArgs},
     prelude::*,
 };
-use ckb_testkit::{Node, User};
+use ckb_testkit::{Node, RpcError, User};
 use std::cmp::min;
 use std::collections::{HashMap, VecDeque};
 use std::thread::sleep;
@@ -185,21 +185,18 @@ pub fn dispatch(
         } else if last_sent_time.elapsed() > Duration::from_secs(60) {
             if last_txs_len == txs.len() {
                 txs.iter().for_each(|tx| {
-                    let result = nodes[0]
-                        .rpc_client()
-                        .send_transaction_result(tx.data().into());
+                    let result = nodes[0].rpc_client().try_send_transaction(tx.data().into());
                     match result {
                         Ok(_) => {
                             ckb_testkit::info!("resend tx {:#x} success", tx.hash());
                         }
+                        Err(RpcError::PoolRejectedDuplicatedTransaction(_)) => {}
                         Err(err) => {
-                            if !err.to_string().contains("Duplicated") {
-                                ckb_testkit::error!(
-                                    "failed to send tx {:#x}, error: {}",
-                                    tx.hash(),
-                                    err
-                                );
-                            }
+                            ckb_testkit::error!(
+                                "failed to send tx {:#x}, error: {}",
+                                tx.hash(),
+                                err
+                            );
                         }
                     }
                 });
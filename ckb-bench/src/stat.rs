@@ -1,8 +1,12 @@
-use ckb_testkit::ckb_types::core::BlockNumber;
+use ckb_testkit::ckb_types::core::{BlockNumber, BlockView};
 use ckb_testkit::Node;
 use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::time::Duration;
 
+// The number of blocks fetched in a batch request
+const BATCH_SIZE: BlockNumber = 100;
+
 /// On-chain report
 #[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct Report {
@@ -53,11 +57,12 @@ pub fn stat(
     let mut total_transactions_size = 0;
     let mut n_inout = 0;
     let mut best_report = Report::default();
+    let mut blocks = BlockWindow::new(node, to_number);
     loop {
-        let block_i = node.get_block_by_number(i);
+        let block_i = blocks.get(i);
         let mut block_j_timestamp = 0;
         while j <= to_number {
-            let block_j = node.get_block_by_number(j);
+            let block_j = blocks.get(j);
             block_j_timestamp = block_j.timestamp();
             if block_j.timestamp().saturating_sub(block_i.timestamp())
                 >= stat_time.as_millis() as u64
@@ -78,7 +83,7 @@ pub fn stat(
             j = to_number;
         }
 
-        let header_j = node.get_header_by_number(j);
+        let header_j = blocks.get(j).header();
         let tps = (total_transactions as f64 * 1000.0
             / (block_j_timestamp.saturating_sub(block_i.timestamp())) as f64)
             as u64;
@@ -107,6 +112,7 @@ pub fn stat(
         }
 
         total_transactions -= block_i.transactions().len();
+        blocks.evict(i);
         i += 1;
     }
 
@@ -117,3 +123,34 @@ pub fn stat(
     best_report.delay_time_ms = delay_time.map(|t| t.as_millis() as u64);
     best_report
 }
+
+/// The blocks between the sliding `i` and `j` of `stat`, fetched in batches ahead of `j`
+struct BlockWindow<'a> {
+    node: &'a Node,
+    to_number: BlockNumber,
+    blocks: BTreeMap<BlockNumber, BlockView>,
+}
+
+impl<'a> BlockWindow<'a> {
+    fn new(node: &'a Node, to_number: BlockNumber) -> Self {
+        Self {
+            node,
+            to_number,
+            blocks: BTreeMap::new(),
+        }
+    }
+
+    fn get(&mut self, number: BlockNumber) -> BlockView {
+        if !self.blocks.contains_key(&number) {
+            let end = (number + BATCH_SIZE - 1).min(self.to_number).max(number);
+            for block in self.node.get_blocks_by_number(number..=end) {
+                self.blocks.insert(block.number(), block);
+            }
+        }
+        self.blocks[&number].clone()
+    }
+
+    fn evict(&mut self, number: BlockNumber) {
+        self.blocks.remove(&number);
+    }
+}
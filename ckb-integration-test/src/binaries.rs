@@ -0,0 +1,284 @@
+use crate::{CKB2019, CKB2021};
+use ckb_testkit::toml_edit;
+use lazy_static::lazy_static;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+/// The role of the binary returned by `ckb2019()`
+pub const ROLE_CKB2019: &str = "ckb2019";
+/// The role of the binary returned by `ckb2021()`
+pub const ROLE_CKB2021: &str = "ckb2021";
+
+thread_local! {
+    // The binaries bound for the case running on this thread. It is scoped to the case thread,
+    // so that a timed-out case still running can't see the bindings of the later cases.
+    static BOUND_ROLES: RefCell<Bindings> = RefCell::new(Vec::new());
+}
+
+lazy_static! {
+    // path => output of `ckb --version`
+    static ref VERSIONS: Mutex<HashMap<PathBuf, String>> = Default::default();
+}
+
+/// A named ckb binary, e.g. "v0.101" or "rc".
+#[derive(Debug, Clone)]
+pub struct CkbBinary {
+    pub name: String,
+    pub path: PathBuf,
+    pub version: String,
+}
+
+impl CkbBinary {
+    pub fn new<P: Into<PathBuf>>(name: &str, path: P) -> Self {
+        let path = crate::absolutize(path.into());
+        if !path.is_file() {
+            panic!(
+                "binary \"{}\" points to non-executable {}",
+                name,
+                path.display()
+            );
+        }
+        let version = binary_version(&path).trim().to_string();
+        Self {
+            name: name.to_string(),
+            path,
+            version,
+        }
+    }
+}
+
+/// A binary bound to each role that a case needs, in the form of `(role, binary)`.
+pub type Bindings = Vec<(String, CkbBinary)>;
+
+/// Return the output of `<path> --version`, e.g. "ckb 0.100.0 (...)\n".
+pub fn binary_version(path: &Path) -> String {
+    let mut versions = VERSIONS.lock().unwrap();
+    if let Some(version) = versions.get(path) {
+        return version.clone();
+    }
+    let output = Command::new(path)
+        .arg("--version")
+        .output()
+        .unwrap_or_else(|err| panic!("failed to execute {}, error: {}", path.display(), err));
+    let version = String::from_utf8_lossy(&output.stdout).to_string();
+    versions.insert(path.to_path_buf(), version.clone());
+    version
+}
+
+/// Bind the binaries to the roles for the case running on the current thread. Call it at the
+/// start of the case thread, and of the threads spawned by the case, see `bound_roles`.
+pub fn bind_roles(bindings: &[(String, CkbBinary)]) {
+    BOUND_ROLES.with(|bound_roles| *bound_roles.borrow_mut() = bindings.to_vec());
+}
+
+/// The bindings of the current thread, to be bound to the threads spawned by the case.
+pub fn bound_roles() -> Bindings {
+    BOUND_ROLES.with(|bound_roles| bound_roles.borrow().clone())
+}
+
+/// Return the path of the binary bound to `role`, e.g. "old" and "new".
+pub fn role_binary(role: &str) -> PathBuf {
+    try_role_binary(role).unwrap_or_else(|| panic!("binary role \"{}\" is not bound", role))
+}
+
+fn try_role_binary(role: &str) -> Option<PathBuf> {
+    BOUND_ROLES.with(|bound_roles| {
+        bound_roles
+            .borrow()
+            .iter()
+            .find(|(r, _)| r == role)
+            .map(|(_, binary)| binary.path.clone())
+    })
+}
+
+/// The binary bound to role "ckb2019". Outside of cases, e.g. when generating testdata, it is
+/// the binary given by `--ckb2019`.
+pub fn ckb2019() -> PathBuf {
+    try_role_binary(ROLE_CKB2019).unwrap_or_else(|| CKB2019.read().unwrap().clone())
+}
+
+/// The binary bound to role "ckb2021". Outside of cases, e.g. when generating testdata, it is
+/// the binary given by `--ckb2021`.
+pub fn ckb2021() -> PathBuf {
+    try_role_binary(ROLE_CKB2021).unwrap_or_else(|| CKB2021.read().unwrap().clone())
+}
+
+/// The named binaries, and the candidate binaries of roles to run cases against.
+///
+/// A role without candidates is bound to the binary of the same name, e.g. role "ckb2021" is
+/// bound to the binary given by `--ckb2021`.
+#[derive(Debug, Clone, Default)]
+pub struct BinaryMatrix {
+    binaries: Vec<CkbBinary>,
+    candidates: Vec<(String, Vec<String>)>,
+}
+
+impl BinaryMatrix {
+    /// Add the binary, replace the one of the same name.
+    pub fn add_binary(&mut self, binary: CkbBinary) {
+        self.binaries.retain(|b| b.name != binary.name);
+        self.binaries.push(binary);
+    }
+
+    /// Run cases needing `role` against each of the binaries.
+    pub fn set_candidates(&mut self, role: &str, binary_names: Vec<String>) {
+        self.candidates.retain(|(r, _)| r != role);
+        self.candidates.push((role.to_string(), binary_names));
+    }
+
+    /// Load binaries and candidates from a TOML file, e.g.
+    ///
+    /// ```toml
+    /// [binaries]
+    /// "v0.100" = "/path/to/ckb-v0.100"
+    /// "v0.101" = "/path/to/ckb-v0.101"
+    ///
+    /// [matrix]
+    /// ckb2021 = ["v0.100", "v0.101"]
+    /// ```
+    pub fn load_config(&mut self, path: &Path) {
+        let content = fs::read_to_string(path)
+            .unwrap_or_else(|err| panic!("failed to read {}, error: {}", path.display(), err));
+        let document = content
+            .parse::<toml_edit::Document>()
+            .unwrap_or_else(|err| panic!("failed to parse {}, error: {}", path.display(), err));
+        if let Some(binaries) = document
+            .get("binaries")
+            .and_then(|item| item.as_table_like())
+        {
+            for (name, binary_path) in binaries.iter() {
+                let binary_path = binary_path.as_str().unwrap_or_else(|| {
+                    panic!("binaries.\"{}\" of {} is not a path", name, path.display())
+                });
+                self.add_binary(CkbBinary::new(name, binary_path));
+            }
+        }
+        if let Some(matrix) = document.get("matrix").and_then(|item| item.as_table_like()) {
+            for (role, binary_names) in matrix.iter() {
+                let binary_names = binary_names
+                    .as_array()
+                    .unwrap_or_else(|| {
+                        panic!("matrix.\"{}\" of {} is not an array", role, path.display())
+                    })
+                    .iter()
+                    .filter_map(|name| name.as_str().map(ToString::to_string))
+                    .collect();
+                self.set_candidates(role, binary_names);
+            }
+        }
+    }
+
+    pub fn binaries(&self) -> &[CkbBinary] {
+        &self.binaries
+    }
+
+    pub fn binary(&self, name: &str) -> Option<&CkbBinary> {
+        self.binaries.iter().find(|binary| binary.name == name)
+    }
+
+    /// Whether any role has candidates, i.e. cases may run more than once.
+    pub fn is_matrix(&self) -> bool {
+        !self.candidates.is_empty()
+    }
+
+    /// Return every combination of binaries bound to `roles`. Return an error if a role has no
+    /// binary.
+    pub fn combinations(&self, roles: &[&str]) -> Result<Vec<Bindings>, String> {
+        let mut combinations: Vec<Bindings> = vec![Vec::new()];
+        for role in roles {
+            let binary_names = self
+                .candidates
+                .iter()
+                .find(|(r, _)| r == role)
+                .map(|(_, binary_names)| binary_names.clone())
+                .unwrap_or_else(|| vec![role.to_string()]);
+            let mut binaries = Vec::new();
+            for name in binary_names.iter() {
+                let binary = self.binary(name).ok_or_else(|| {
+                    format!("binary \"{}\" for role \"{}\" is not provided", name, role)
+                })?;
+                binaries.push(binary.clone());
+            }
+            combinations = combinations
+                .into_iter()
+                .flat_map(|bindings| {
+                    binaries.iter().map(move |binary| {
+                        let mut bindings = bindings.clone();
+                        bindings.push((role.to_string(), binary.clone()));
+                        bindings
+                    })
+                })
+                .collect();
+        }
+        Ok(combinations)
+    }
+
+    /// The arguments passing the binaries and the given combination to a worker process.
+    pub fn worker_args(&self, bindings: &[(String, CkbBinary)]) -> Vec<String> {
+        let mut args = Vec::new();
+        for binary in self.binaries.iter() {
+            args.push("--binary".to_string());
+            args.push(format!("{}={}", binary.name, binary.path.display()));
+        }
+        if self.is_matrix() {
+            for (role, binary) in bindings {
+                args.push("--matrix".to_string());
+                args.push(format!("{}={}", role, binary.name));
+            }
+        }
+        args
+    }
+}
+
+/// Label the combination, e.g. "ckb2019=v0.43,ckb2021=rc".
+pub fn label(bindings: &[(String, CkbBinary)]) -> String {
+    bindings
+        .iter()
+        .map(|(role, binary)| format!("{}={}", role, binary.name))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bind_roles, bound_roles, role_binary, CkbBinary};
+    use std::path::PathBuf;
+    use std::thread;
+
+    fn binary(name: &str) -> CkbBinary {
+        CkbBinary {
+            name: name.to_string(),
+            path: PathBuf::from(format!("/path/to/ckb-{}", name)),
+            version: format!("ckb {}", name),
+        }
+    }
+
+    #[test]
+    fn test_bindings_are_scoped_to_thread() {
+        bind_roles(&[("old".to_string(), binary("v0.100"))]);
+
+        // e.g. a timed-out case still running while the next case binds its roles
+        let next_case = thread::spawn(|| {
+            assert!(bound_roles().is_empty());
+            bind_roles(&[("old".to_string(), binary("v0.101"))]);
+            role_binary("old")
+        });
+        assert_eq!(
+            next_case.join().unwrap(),
+            PathBuf::from("/path/to/ckb-v0.101")
+        );
+        assert_eq!(role_binary("old"), PathBuf::from("/path/to/ckb-v0.100"));
+
+        // The threads spawned by the case inherit the bindings explicitly
+        let bindings = bound_roles();
+        let row = thread::spawn(move || {
+            bind_roles(&bindings);
+            role_binary("old")
+        });
+        assert_eq!(row.join().unwrap(), PathBuf::from("/path/to/ckb-v0.100"));
+    }
+}
@@ -1,34 +1,37 @@
+use crate::binaries::{ckb2019, ckb2021, ROLE_CKB2019, ROLE_CKB2021};
 use crate::case::{Case, CaseOptions};
-use crate::{CKB2019, CKB2021};
 use ckb_testkit::NodeOptions;
 use ckb_testkit::Nodes;
 
 pub struct BasicNetworking;
 
 impl Case for BasicNetworking {
+    fn tags(&self) -> Vec<&'static str> {
+        vec!["p2p"]
+    }
+
+    fn binary_roles(&self) -> Vec<&'static str> {
+        vec![ROLE_CKB2019, ROLE_CKB2021]
+    }
+
     fn case_options(&self) -> CaseOptions {
         CaseOptions {
             make_all_nodes_connected: false,
             make_all_nodes_synced: false,
             make_all_nodes_connected_and_synced: false,
             node_options: vec![
-                NodeOptions {
-                    node_name: String::from("node2019"),
-                    ckb_binary: CKB2019.read().unwrap().clone(),
-                    initial_database: "testdata/db/Height13TestData",
-                    chain_spec: "testdata/spec/ckb2019",
-                    app_config: "testdata/config/ckb2019",
-                },
-                NodeOptions {
-                    node_name: String::from("node2021"),
-                    ckb_binary: CKB2021.read().unwrap().clone(),
-                    initial_database: "testdata/db/Height13TestData",
-                    chain_spec: "testdata/spec/ckb2021",
-                    app_config: "testdata/config/ckb2021",
-                },
+                NodeOptions::ckb2019("node2019")
+                    .ckb_binary(ckb2019())
+                    .initial_database("testdata/db/Height13TestData")
+                    .build(),
+                NodeOptions::ckb2021("node2021")
+                    .ckb_binary(ckb2021())
+                    .initial_database("testdata/db/Height13TestData")
+                    .build(),
             ]
             .into_iter()
             .collect(),
+            ..Default::default()
         }
     }
 
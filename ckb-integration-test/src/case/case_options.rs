@@ -1,4 +1,8 @@
 use ckb_testkit::NodeOptions;
+use std::time::Duration;
+
+/// The default timeout of a case, see `Case::timeout`
+pub const DEFAULT_CASE_TIMEOUT: Duration = Duration::from_secs(20 * 60);
 
 #[derive(Debug, Clone)]
 pub struct CaseOptions {
@@ -6,6 +10,8 @@ pub struct CaseOptions {
     pub make_all_nodes_synced: bool,
     pub make_all_nodes_connected_and_synced: bool,
     pub node_options: Vec<NodeOptions>,
+    /// The runner kills the nodes and records the case as timed-out when it runs longer
+    pub timeout: Duration,
 }
 
 impl Default for CaseOptions {
@@ -15,6 +21,7 @@ impl Default for CaseOptions {
             make_all_nodes_synced: false,
             make_all_nodes_connected_and_synced: false,
             node_options: Default::default(),
+            timeout: DEFAULT_CASE_TIMEOUT,
         }
     }
 }
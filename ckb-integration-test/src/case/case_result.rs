@@ -0,0 +1,237 @@
+use crate::binaries::{Bindings, CkbBinary};
+use std::any::Any;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseStatus {
+    Passed,
+    Failed,
+    /// Not run, e.g. excluded via `--skip`
+    Skipped,
+    /// Killed by the runner after `Case::timeout`
+    TimedOut,
+}
+
+impl CaseStatus {
+    fn parse(status: &str) -> Option<Self> {
+        match status {
+            "passed" => Some(CaseStatus::Passed),
+            "failed" => Some(CaseStatus::Failed),
+            "skipped" => Some(CaseStatus::Skipped),
+            "timed-out" => Some(CaseStatus::TimedOut),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CaseStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let status = match self {
+            CaseStatus::Passed => "passed",
+            CaseStatus::Failed => "failed",
+            CaseStatus::Skipped => "skipped",
+            CaseStatus::TimedOut => "timed-out",
+        };
+        // Support width and alignment, used by the summary table
+        f.pad(status)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CaseResult {
+    pub case_name: String,
+    pub status: CaseStatus,
+    pub duration: Duration,
+    /// The panic message of a failed case
+    pub message: Option<String>,
+    /// The run.log of the nodes initialized by the case
+    pub node_logs: Vec<PathBuf>,
+    /// The tarball bundled when the case failed, see `CaseArtifacts`
+    pub artifacts: Option<PathBuf>,
+    /// The binaries which the case ran against
+    pub bindings: Bindings,
+}
+
+impl CaseResult {
+    pub fn passed(case_name: &str, duration: Duration) -> Self {
+        Self {
+            case_name: case_name.to_string(),
+            status: CaseStatus::Passed,
+            duration,
+            message: None,
+            node_logs: Vec::new(),
+            artifacts: None,
+            bindings: Vec::new(),
+        }
+    }
+
+    pub fn failed(case_name: &str, duration: Duration, message: String) -> Self {
+        Self {
+            case_name: case_name.to_string(),
+            status: CaseStatus::Failed,
+            duration,
+            message: Some(message),
+            node_logs: Vec::new(),
+            artifacts: None,
+            bindings: Vec::new(),
+        }
+    }
+
+    pub fn timed_out(case_name: &str, duration: Duration, timeout: Duration) -> Self {
+        Self {
+            case_name: case_name.to_string(),
+            status: CaseStatus::TimedOut,
+            duration,
+            message: Some(format!("timed out after {:?}", timeout)),
+            node_logs: Vec::new(),
+            artifacts: None,
+            bindings: Vec::new(),
+        }
+    }
+
+    pub fn skipped(case_name: &str) -> Self {
+        Self {
+            case_name: case_name.to_string(),
+            status: CaseStatus::Skipped,
+            duration: Duration::from_secs(0),
+            message: None,
+            node_logs: Vec::new(),
+            artifacts: None,
+            bindings: Vec::new(),
+        }
+    }
+
+    pub fn is_failure(&self) -> bool {
+        self.status == CaseStatus::Failed || self.status == CaseStatus::TimedOut
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "case_name": self.case_name,
+            "status": self.status.to_string(),
+            "duration": self.duration.as_secs_f64(),
+            "message": self.message,
+            "node_logs": self.node_logs,
+            "artifacts": self.artifacts,
+            "binaries": self.bindings.iter().map(|(role, binary)| serde_json::json!({
+                "role": role,
+                "name": binary.name,
+                "path": binary.path,
+                "version": binary.version,
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        let paths = |value: &serde_json::Value| {
+            value
+                .as_array()
+                .map(|paths| {
+                    paths
+                        .iter()
+                        .filter_map(|path| path.as_str().map(PathBuf::from))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+        };
+        Some(Self {
+            case_name: value["case_name"].as_str()?.to_string(),
+            status: CaseStatus::parse(value["status"].as_str()?)?,
+            duration: Duration::from_secs_f64(value["duration"].as_f64()?),
+            message: value["message"].as_str().map(ToString::to_string),
+            node_logs: paths(&value["node_logs"]),
+            artifacts: value["artifacts"].as_str().map(PathBuf::from),
+            bindings: value["binaries"]
+                .as_array()
+                .map(|binaries| {
+                    binaries
+                        .iter()
+                        .filter_map(|binary| {
+                            let binary_info = CkbBinary {
+                                name: binary["name"].as_str()?.to_string(),
+                                path: PathBuf::from(binary["path"].as_str()?),
+                                version: binary["version"].as_str()?.to_string(),
+                            };
+                            Some((binary["role"].as_str()?.to_string(), binary_info))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Write into `path`, read by the parent process via `CaseResult::read_from`.
+    pub fn write_to(&self, path: &Path) {
+        fs::write(path, self.to_json().to_string())
+            .unwrap_or_else(|err| panic!("failed to write {}, error: {}", path.display(), err));
+    }
+
+    /// Read the result written by a worker process via `CaseResult::write_to`.
+    pub fn read_from(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        let value = serde_json::from_str(&content).ok()?;
+        Self::from_json(&value)
+    }
+}
+
+/// Extract the message from the payload of `catch_unwind`.
+pub fn panic_message(panic_payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic_payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic_payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+/// Print a table of all case results, one case per line, with the first line of the failure
+/// message.
+pub fn print_summary(results: &[CaseResult]) {
+    let name_width = results
+        .iter()
+        .map(|result| result.case_name.len())
+        .max()
+        .unwrap_or(0)
+        .max("CASE".len());
+    println!();
+    println!(
+        "{:<width$}  {:<9}  {:>9}  MESSAGE",
+        "CASE",
+        "STATUS",
+        "DURATION",
+        width = name_width
+    );
+    for result in results {
+        let message = result
+            .message
+            .as_ref()
+            .and_then(|message| message.lines().next())
+            .unwrap_or("");
+        println!(
+            "{:<width$}  {:<9}  {:>8.1}s  {}",
+            result.case_name,
+            result.status,
+            result.duration.as_secs_f64(),
+            message,
+            width = name_width
+        );
+    }
+    let count = |status: CaseStatus| {
+        results
+            .iter()
+            .filter(|result| result.status == status)
+            .count()
+    };
+    println!(
+        "\n{} cases: {} passed, {} failed, {} timed-out, {} skipped",
+        results.len(),
+        count(CaseStatus::Passed),
+        count(CaseStatus::Failed),
+        count(CaseStatus::TimedOut),
+        count(CaseStatus::Skipped),
+    );
+}
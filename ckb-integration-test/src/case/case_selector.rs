@@ -0,0 +1,285 @@
+use crate::case::Case;
+
+/// Select cases by name patterns and tags.
+///
+/// Patterns are glob patterns, where `*` matches any sequence of characters and `?` matches any
+/// single character, e.g. "RFC0035*".
+#[derive(Debug, Clone, Default)]
+pub struct CaseSelector {
+    /// Select the cases matching any of the patterns, in the order of the patterns. All cases
+    /// are selected if empty.
+    pub patterns: Vec<String>,
+    /// Select the cases having any of the tags. Ignored if empty.
+    pub tags: Vec<String>,
+    /// Exclude the cases having any of the tags.
+    pub exclude_tags: Vec<String>,
+    /// Report the cases matching any of the patterns as skipped, e.g. known-broken cases.
+    pub skip: Vec<String>,
+}
+
+impl CaseSelector {
+    /// Return the selected cases and the skipped cases. Panic if any of `patterns` matches
+    /// nothing.
+    pub fn select(
+        &self,
+        all_cases: Vec<Box<dyn Case>>,
+    ) -> (Vec<Box<dyn Case>>, Vec<Box<dyn Case>>) {
+        let mut candidates: Vec<Option<Box<dyn Case>>> = all_cases.into_iter().map(Some).collect();
+        let mut matched = Vec::new();
+        if self.patterns.is_empty() {
+            matched.extend(candidates.into_iter().flatten());
+        } else {
+            for pattern in self.patterns.iter() {
+                let mut found = false;
+                for candidate in candidates.iter_mut() {
+                    let is_match = candidate
+                        .as_ref()
+                        .map(|case| glob_match(pattern, case.case_name()))
+                        .unwrap_or(false);
+                    if is_match {
+                        matched.push(candidate.take().expect("checked"));
+                        found = true;
+                    }
+                }
+                // Also OK if the cases were matched by the previous patterns
+                let matched_before = matched
+                    .iter()
+                    .any(|case| glob_match(pattern, case.case_name()));
+                if !found && !matched_before {
+                    panic!("unknown case \"{}\"", pattern);
+                }
+            }
+        }
+
+        let mut selected = Vec::new();
+        let mut skipped = Vec::new();
+        for case in matched {
+            let tags = case.tags();
+            if !self.tags.is_empty() && !tags.iter().any(|tag| self.tags.iter().any(|t| t == tag)) {
+                continue;
+            }
+            if tags
+                .iter()
+                .any(|tag| self.exclude_tags.iter().any(|t| t == tag))
+            {
+                continue;
+            }
+            if self
+                .skip
+                .iter()
+                .any(|pattern| glob_match(pattern, case.case_name()))
+            {
+                skipped.push(case);
+            } else {
+                selected.push(case);
+            }
+        }
+        (selected, skipped)
+    }
+}
+
+/// Match `name` against the glob `pattern`, which supports `*` and `?`.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    let (mut p, mut n) = (0, 0);
+    // The position of the last `*` in pattern, and the position in name it matched up to
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = backtrack {
+            // Let the last `*` match one more character
+            backtrack = Some((star_p, star_n + 1));
+            p = star_p + 1;
+            n = star_n + 1;
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{glob_match, CaseSelector};
+    use crate::case::{Case, CaseOptions};
+    use ckb_testkit::Nodes;
+
+    struct FakeCase {
+        name: &'static str,
+        tags: Vec<&'static str>,
+    }
+
+    impl Case for FakeCase {
+        fn case_name(&self) -> &str {
+            self.name
+        }
+
+        fn tags(&self) -> Vec<&'static str> {
+            self.tags.clone()
+        }
+
+        fn case_options(&self) -> CaseOptions {
+            Default::default()
+        }
+
+        fn run(&self, _nodes: Nodes) {}
+    }
+
+    fn all_cases() -> Vec<Box<dyn Case>> {
+        vec![
+            Box::new(FakeCase {
+                name: "BasicNetworking",
+                tags: vec!["p2p"],
+            }),
+            Box::new(FakeCase {
+                name: "RFC0035V2019",
+                tags: vec!["p2p", "hardfork", "rfc0035"],
+            }),
+            Box::new(FakeCase {
+                name: "RFC0035Discovery",
+                tags: vec!["p2p", "hardfork", "rfc0035", "slow"],
+            }),
+            Box::new(FakeCase {
+                name: "RFC0036",
+                tags: vec!["hardfork"],
+            }),
+        ]
+    }
+
+    fn names(cases: &[Box<dyn Case>]) -> Vec<&str> {
+        cases.iter().map(|case| case.case_name()).collect()
+    }
+
+    #[test]
+    fn test_glob_match() {
+        for (pattern, name, expected) in [
+            ("RFC0035", "RFC0035", true),
+            ("RFC0035", "RFC0035V2019", false),
+            ("RFC0035", "XRFC0035", false),
+            ("RFC0035*", "RFC0035", true),
+            ("RFC0035*", "RFC0035V2019", true),
+            ("RFC0035*", "RFC0036", false),
+            ("*2019", "RFC0035V2019", true),
+            ("*2019", "RFC0035V2019X", false),
+            ("*0035*", "RFC0035V2019", true),
+            ("*", "", true),
+            ("*", "RFC0035", true),
+            ("**", "RFC0035", true),
+            ("", "", true),
+            ("", "RFC0035", false),
+            ("RFC003?", "RFC0035", true),
+            ("RFC003?", "RFC003", false),
+            ("RFC003?", "RFC00355", false),
+            ("?FC*V20?9", "RFC0035V2019", true),
+            ("R*0*5", "RFC0035", true),
+            ("R*0*6", "RFC0035", false),
+            ("*a*b", "aaab", true),
+            ("*a*b", "aaba", false),
+        ] {
+            assert_eq!(
+                glob_match(pattern, name),
+                expected,
+                "glob_match({:?}, {:?})",
+                pattern,
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_select_by_patterns() {
+        let (selected, skipped) = CaseSelector::default().select(all_cases());
+        assert_eq!(
+            names(&selected),
+            vec![
+                "BasicNetworking",
+                "RFC0035V2019",
+                "RFC0035Discovery",
+                "RFC0036"
+            ]
+        );
+        assert!(skipped.is_empty());
+
+        // In the order of the patterns, each case is selected once
+        let selector = CaseSelector {
+            patterns: vec!["RFC0036".to_string(), "RFC*".to_string()],
+            ..Default::default()
+        };
+        let (selected, _) = selector.select(all_cases());
+        assert_eq!(
+            names(&selected),
+            vec!["RFC0036", "RFC0035V2019", "RFC0035Discovery"]
+        );
+
+        // A pattern matching only the cases matched before is fine
+        let selector = CaseSelector {
+            patterns: vec!["RFC*".to_string(), "RFC0036".to_string()],
+            ..Default::default()
+        };
+        let (selected, _) = selector.select(all_cases());
+        assert_eq!(
+            names(&selected),
+            vec!["RFC0035V2019", "RFC0035Discovery", "RFC0036"]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown case \"RFC0037\"")]
+    fn test_select_unknown_case() {
+        let selector = CaseSelector {
+            patterns: vec!["RFC0037".to_string()],
+            ..Default::default()
+        };
+        selector.select(all_cases());
+    }
+
+    #[test]
+    fn test_select_by_tags() {
+        let selector = CaseSelector {
+            tags: vec!["rfc0035".to_string(), "p2p".to_string()],
+            ..Default::default()
+        };
+        let (selected, _) = selector.select(all_cases());
+        assert_eq!(
+            names(&selected),
+            vec!["BasicNetworking", "RFC0035V2019", "RFC0035Discovery"]
+        );
+
+        // Excluding wins over including
+        let selector = CaseSelector {
+            tags: vec!["hardfork".to_string()],
+            exclude_tags: vec!["slow".to_string(), "p2p".to_string()],
+            ..Default::default()
+        };
+        let (selected, _) = selector.select(all_cases());
+        assert_eq!(names(&selected), vec!["RFC0036"]);
+
+        // The patterns and tags must both match
+        let selector = CaseSelector {
+            patterns: vec!["RFC*".to_string()],
+            exclude_tags: vec!["slow".to_string()],
+            ..Default::default()
+        };
+        let (selected, _) = selector.select(all_cases());
+        assert_eq!(names(&selected), vec!["RFC0035V2019", "RFC0036"]);
+    }
+
+    #[test]
+    fn test_skip() {
+        let selector = CaseSelector {
+            exclude_tags: vec!["slow".to_string()],
+            skip: vec!["RFC0035*".to_string()],
+            ..Default::default()
+        };
+        let (selected, skipped) = selector.select(all_cases());
+        assert_eq!(names(&selected), vec!["BasicNetworking", "RFC0036"]);
+        // The excluded cases are not reported as skipped
+        assert_eq!(names(&skipped), vec!["RFC0035V2019"]);
+    }
+}
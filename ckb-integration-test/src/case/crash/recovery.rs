@@ -0,0 +1,81 @@
+use crate::prelude::*;
+use ckb_testkit::{CrashHarness, CrashPoint};
+use std::env;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// SIGKILL a node at different points of a mining + transactions workload, restart it on the
+/// same working directory and check that it is consistent with what was acknowledged before
+/// the kill.
+///
+/// The random crash point is chosen via env `CKB_INTEGRATION_TEST_CRASH_SEED`, or the current
+/// time if it is not set. The seed is logged so that a failure can be reproduced.
+pub struct CrashRecovery;
+
+const N_STEPS: usize = 20;
+
+impl Case for CrashRecovery {
+    fn tags(&self) -> Vec<&'static str> {
+        vec!["crash", "slow"]
+    }
+
+    fn case_options(&self) -> CaseOptions {
+        Default::default()
+    }
+
+    fn run(&self, _nodes: Nodes) {
+        let seed = env::var("CKB_INTEGRATION_TEST_CRASH_SEED")
+            .ok()
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_nanos() as u64
+            });
+        ckb_testkit::info!("CKB_INTEGRATION_TEST_CRASH_SEED={}", seed);
+
+        let crash_points = vec![
+            CrashPoint::AfterStep(N_STEPS / 2),
+            CrashPoint::DuringSubmitBlock {
+                step: N_STEPS / 2,
+                delay: Duration::from_millis(0),
+            },
+            CrashPoint::DuringSubmitBlock {
+                step: N_STEPS / 2,
+                delay: Duration::from_millis(5),
+            },
+            CrashPoint::Random { seed },
+        ];
+        for (i, crash_point) in crash_points.into_iter().enumerate() {
+            let mut node = self.setup_node(i);
+            let harness = CrashHarness::new(N_STEPS, crash_point.clone());
+            let report = harness.run(&mut node).unwrap_or_else(|err| {
+                panic!(
+                    "crash_point: {:?}, node.log_path: {}, error: {}",
+                    crash_point,
+                    node.log_path().to_string_lossy(),
+                    err
+                )
+            });
+            ckb_testkit::info!(
+                "crash_point: {:?}, committed: {}, pending: {}, dropped: {}",
+                crash_point,
+                report.committed.len(),
+                report.pending.len(),
+                report.dropped.len()
+            );
+        }
+    }
+}
+
+impl CrashRecovery {
+    fn setup_node(&self, i: usize) -> Node {
+        let node_options = NodeOptions::ckb2021(format!("{}-{}", self.case_name(), i))
+            .ckb_binary(ckb2021())
+            .initial_database("testdata/db/Epoch2V2TestData")
+            .build();
+        let mut node = Node::init(self.case_name(), node_options, true);
+        node.start();
+        node
+    }
+}
@@ -14,18 +14,19 @@ use std::sync::{Arc, RwLock};
 pub struct ManipulatedAddresses;
 
 impl Case for ManipulatedAddresses {
+    fn tags(&self) -> Vec<&'static str> {
+        vec!["p2p", "discovery"]
+    }
+
     fn case_options(&self) -> CaseOptions {
         CaseOptions {
             make_all_nodes_connected: false,
             make_all_nodes_synced: false,
             make_all_nodes_connected_and_synced: false,
-            node_options: vec![NodeOptions {
-                node_name: String::from("node2021"),
-                ckb_binary: CKB2021.read().unwrap().clone(),
-                initial_database: "testdata/db/empty",
-                chain_spec: "testdata/spec/ckb2021",
-                app_config: "testdata/config/ckb2021",
-            }],
+            node_options: vec![NodeOptions::ckb2021("node2021")
+                .ckb_binary(ckb2021())
+                .build()],
+            ..Default::default()
         }
     }
 
@@ -1,3 +1,4 @@
+use crate::binaries::{ROLE_CKB2019, ROLE_CKB2021};
 use crate::prelude::*;
 use crate::util::{v0_100, v0_43};
 use ckb_testkit::{
@@ -18,18 +19,23 @@ use std::time::Duration;
 pub struct IdentifyConnection;
 
 impl Case for IdentifyConnection {
+    fn tags(&self) -> Vec<&'static str> {
+        vec!["p2p", "identify"]
+    }
+
+    fn binary_roles(&self) -> Vec<&'static str> {
+        vec![ROLE_CKB2019, ROLE_CKB2021]
+    }
+
     fn case_options(&self) -> CaseOptions {
         CaseOptions {
             make_all_nodes_connected: false,
             make_all_nodes_synced: false,
             make_all_nodes_connected_and_synced: false,
-            node_options: vec![NodeOptions {
-                node_name: String::from("node2021"),
-                ckb_binary: CKB2021.read().unwrap().clone(),
-                initial_database: "testdata/db/empty",
-                chain_spec: "testdata/spec/ckb2021",
-                app_config: "testdata/config/ckb2021",
-            }],
+            node_options: vec![NodeOptions::ckb2021("node2021")
+                .ckb_binary(ckb2021())
+                .build()],
+            ..Default::default()
         }
     }
 
@@ -1,5 +1,9 @@
 mod basic;
 mod case_options;
+mod case_result;
+mod case_selector;
+#[cfg(unix)]
+mod crash;
 mod discovery;
 mod identify;
 mod rfc0028;
@@ -10,12 +14,25 @@ mod rfc0032;
 mod rfc0034;
 mod rfc0035;
 mod rfc0036;
+mod table_case;
 
+use crate::binaries::{self, Bindings, ROLE_CKB2021};
 pub use case_options::CaseOptions;
-use ckb_testkit::{Node, Nodes};
+pub use case_result::{print_summary, CaseResult, CaseStatus};
+pub use case_selector::CaseSelector;
+use ckb_testkit::{CaseArtifacts, Node, Nodes};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+pub use table_case::{TableCase, TableRow};
+
+// How long to wait for a timed-out case to unwind after its nodes are killed
+const TIMEOUT_GRACE_PERIOD: Duration = Duration::from_secs(30);
 
 pub fn all_cases() -> Vec<Box<dyn Case>> {
-    vec![
+    #[allow(unused_mut)]
+    let mut cases: Vec<Box<dyn Case>> = vec![
         Box::new(basic::networking::BasicNetworking),
         Box::new(rfc0028::chained::RFC0028Chained),
         Box::new(rfc0028::rfc0028::RFC0028),
@@ -33,18 +50,135 @@ pub fn all_cases() -> Vec<Box<dyn Case>> {
         Box::new(identify::identical_key_pair::IdentifyIdenticalKeyPair),
         Box::new(discovery::flood_attack::DiscoveryFloodAttack),
         Box::new(discovery::manipulated_addresses::ManipulatedAddresses),
-    ]
+    ];
+    #[cfg(unix)]
+    cases.push(Box::new(crash::recovery::CrashRecovery));
+    cases
+}
+
+/// Return the case named `case_name`.
+pub fn find_case(case_name: &str) -> Box<dyn Case> {
+    all_cases()
+        .into_iter()
+        .find(|case| case.case_name() == case_name)
+        .unwrap_or_else(|| panic!("unknown case \"{}\"", case_name))
 }
 
-pub fn run_case(case: Box<dyn Case>) {
+/// A run of a case against a combination of binaries.
+#[derive(Debug, Clone)]
+pub struct CaseRun {
+    pub case_name: String,
+    pub bindings: Bindings,
+    /// The case name, suffixed with the binary combination when running a matrix, e.g.
+    /// "RFC0035V2019[ckb2019=v0.43,ckb2021=rc]"
+    pub label: String,
+}
+
+impl CaseRun {
+    pub fn new(case_name: &str, bindings: Bindings, is_matrix: bool) -> Self {
+        let label = if is_matrix {
+            format!("{}[{}]", case_name, binaries::label(&bindings))
+        } else {
+            case_name.to_string()
+        };
+        Self {
+            case_name: case_name.to_string(),
+            bindings,
+            label,
+        }
+    }
+}
+
+/// Run the case and return its result. The panic of a failing case is caught, so that the later
+/// cases still run. The nodes of a failing case are kept running until their state is dumped.
+///
+/// The case runs in its own thread, watched by the calling thread. When it runs longer than
+/// `Case::timeout`, the nodes' state is dumped and their ckb processes are killed, so the hung
+/// case fails soon after, and it is recorded as timed-out.
+pub fn run_case(case_run: &CaseRun) -> CaseResult {
+    let case = find_case(&case_run.case_name);
+    let case_name = case_run.label.clone();
     ckb_testkit::LOG_TARGET.with(|c| {
-        *c.borrow_mut() = case.case_name().to_string();
+        *c.borrow_mut() = case_name.clone();
     });
 
     ckb_testkit::info!("********** START **********");
-    let nodes = case.before_run();
-    case.run(nodes);
-    ckb_testkit::info!("********** END **********");
+    let timeout = case.timeout();
+    let start_time = Instant::now();
+    let artifacts = CaseArtifacts::new(&case_run.case_name);
+    artifacts.keep_nodes_on_panic();
+    let (sender, receiver) = mpsc::channel();
+    let case_thread_name = case_name.clone();
+    let bindings = case_run.bindings.clone();
+    thread::Builder::new()
+        .name(case_name.clone())
+        .spawn(move || {
+            ckb_testkit::LOG_TARGET.with(|c| {
+                *c.borrow_mut() = case_thread_name;
+            });
+            // Scoped to the case thread, which may outlive the case when it times out
+            binaries::bind_roles(&bindings);
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                let nodes = case.before_run();
+                case.run(nodes);
+            }));
+            let result =
+                result.map_err(|panic_payload| case_result::panic_message(&*panic_payload));
+            let _ = sender.send(result);
+        })
+        .unwrap_or_else(|err| panic!("failed to spawn thread for {}, error: {}", case_name, err));
+
+    let result = match receiver.recv_timeout(timeout) {
+        Ok(result) => Some(result),
+        Err(_) => {
+            ckb_testkit::error!("TIMEOUT after {:?}, kill the nodes", timeout);
+            artifacts.dump_nodes_state();
+            artifacts.kill_nodes();
+            // Leave the case thread alone if it still hangs
+            let _ = receiver.recv_timeout(TIMEOUT_GRACE_PERIOD);
+            None
+        }
+    };
+    let duration = start_time.elapsed();
+    let node_logs = artifacts.log_paths();
+    let mut case_result = match result {
+        Some(Ok(())) => {
+            // The nodes kept by the panics caught inside the case
+            artifacts.kill_nodes();
+            artifacts.remove_working_dirs();
+            ckb_testkit::info!("********** END **********");
+            CaseResult::passed(&case_name, duration)
+        }
+        Some(Err(message)) => {
+            artifacts.dump_nodes_state();
+            artifacts.kill_nodes();
+            let mut case_result = CaseResult::failed(&case_name, duration, message);
+            case_result.artifacts = bundle_artifacts(artifacts, &case_name);
+            case_result
+        }
+        None => {
+            let mut case_result = CaseResult::timed_out(&case_name, duration, timeout);
+            case_result.artifacts = bundle_artifacts(artifacts, &case_name);
+            case_result
+        }
+    };
+    case_result.node_logs = node_logs;
+    case_result.bindings = case_run.bindings.clone();
+    case_result
+}
+
+fn bundle_artifacts(artifacts: CaseArtifacts, name: &str) -> Option<::std::path::PathBuf> {
+    let artifacts_dir = crate::ARTIFACTS_DIR.read().unwrap().clone();
+    match artifacts.bundle_as(&artifacts_dir, name) {
+        Ok(tarball) => {
+            ckb_testkit::error!("FAILED, artifacts: {}", tarball.display());
+            Some(tarball)
+        }
+        Err(err) => {
+            ckb_testkit::error!("FAILED, failed to bundle artifacts: {}", err);
+            None
+        }
+    }
 }
 
 pub trait Case: Send {
@@ -52,18 +186,37 @@ pub trait Case: Send {
         case_name(self)
     }
 
+    /// Tags for selecting cases via `--tags` and `--exclude-tags`, e.g. "p2p", "hardfork",
+    /// "rfc0035" and "slow".
+    fn tags(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    /// The binary roles the case needs. The case runs against every combination of the
+    /// candidate binaries of the roles, see `BinaryMatrix`. Default is `["ckb2021"]`.
+    fn binary_roles(&self) -> Vec<&'static str> {
+        vec![ROLE_CKB2021]
+    }
+
     fn case_options(&self) -> CaseOptions;
 
+    /// The runner kills the nodes and records the case as timed-out if it runs longer. Default
+    /// is `CaseOptions::timeout`.
+    fn timeout(&self) -> Duration {
+        self.case_options().timeout
+    }
+
     fn before_run(&self) -> Nodes {
         let case_name = self.case_name();
         let case_options = self.case_options();
         let mut nodes = ::std::collections::HashMap::new();
         let mut first_node_name = None;
         for node_options in case_options.node_options.iter() {
+            crate::testdata::check_initial_database(node_options);
             let mut node = Node::init(
                 case_name,
                 node_options.clone(),
-                node_options.ckb_binary == *crate::CKB2021.read().unwrap(),
+                node_options.ckb_binary == crate::binaries::ckb2021(),
             );
             let node_name = node.node_name().to_string();
             node.start();
@@ -1,7 +1,7 @@
 use super::RFC0028_EPOCH_NUMBER;
+use crate::binaries::ckb2021;
 use crate::case::{Case, CaseOptions};
 use crate::util::estimate_start_number_of_epoch;
-use crate::CKB2021;
 use ckb_testkit::ckb_types::core::Capacity;
 use ckb_testkit::ckb_types::packed::OutPoint;
 use ckb_testkit::ckb_types::{
@@ -16,20 +16,22 @@ use ckb_testkit::{BuildInstruction, Nodes};
 pub struct RFC0028Chained;
 
 impl Case for RFC0028Chained {
+    fn tags(&self) -> Vec<&'static str> {
+        vec!["hardfork", "rfc0028"]
+    }
+
     fn case_options(&self) -> CaseOptions {
         CaseOptions {
             make_all_nodes_connected: false,
             make_all_nodes_synced: false,
             make_all_nodes_connected_and_synced: false,
-            node_options: vec![NodeOptions {
-                node_name: String::from("node2021"),
-                ckb_binary: CKB2021.read().unwrap().clone(),
-                initial_database: "testdata/db/Epoch2V2TestData",
-                chain_spec: "testdata/spec/ckb2021",
-                app_config: "testdata/config/ckb2021",
-            }]
+            node_options: vec![NodeOptions::ckb2021("node2021")
+                .ckb_binary(ckb2021())
+                .initial_database("testdata/db/Epoch2V2TestData")
+                .build()]
             .into_iter()
             .collect(),
+            ..Default::default()
         }
     }
 
@@ -2,7 +2,7 @@ use super::{ERROR_IMMATURE, RFC0028_EPOCH_NUMBER};
 use crate::prelude::*;
 use crate::util::estimate_start_number_of_epoch;
 use ckb_testkit::ckb_types::{
-    core::TransactionView,
+    core::{BlockNumber, TransactionView},
     packed::{CellInput, OutPoint},
 };
 use ckb_testkit::util::since_from_relative_timestamp;
@@ -59,18 +59,19 @@ use ckb_testkit::{assert_result_eq, BuildInstruction};
 pub struct RFC0028;
 
 impl Case for RFC0028 {
+    fn tags(&self) -> Vec<&'static str> {
+        vec!["hardfork", "rfc0028"]
+    }
+
     fn case_options(&self) -> CaseOptions {
         CaseOptions {
             make_all_nodes_connected: false,
             make_all_nodes_synced: false,
             make_all_nodes_connected_and_synced: false,
-            node_options: vec![NodeOptions {
-                node_name: String::from("node2021"),
-                ckb_binary: CKB2021.read().unwrap().clone(),
-                initial_database: "testdata/db/empty",
-                chain_spec: "testdata/spec/ckb2021",
-                app_config: "testdata/config/ckb2021",
-            }],
+            node_options: vec![NodeOptions::ckb2021("node2021")
+                .ckb_binary(ckb2021())
+                .build()],
+            ..Default::default()
         }
     }
 
@@ -97,104 +98,93 @@ impl Case for RFC0028 {
             node2021.always_success_transaction(&input)
         };
 
-        // Run cases 0, 1, 2.
-        //
-        // The current tip number is 2999, that has not activate fork.
-        // Please read struct documentation for detail.
-        //
-        // [(case_id, input_tx_committed_number, tip_number)]
-        let cases_before_switch = vec![
-            (0, 1998, Ok(())),
-            (1, 1999, Ok(())),
-            (2, 2000, Err(ERROR_IMMATURE)),
-        ];
-        for (case_id, input_tx_committed_number, expected_result_before_switch) in
-            cases_before_switch
-        {
-            let node = node2021.clone_node(&format!("case-{}-before-switch", case_id));
-
+        TableCase::new(node2021, self.cases_params(fork_switch_height)).run(|node, case| {
             let mut ins = construct_chain_instructions.clone();
             ins.extend(vec![
                 BuildInstruction::Propose {
-                    template_number: input_tx_committed_number - 2,
+                    template_number: case.input_tx_committed_number - 2,
                     proposal_short_id: input_tx.proposal_short_id(),
                 },
                 BuildInstruction::Commit {
-                    template_number: input_tx_committed_number,
+                    template_number: case.input_tx_committed_number,
                     transaction: input_tx.clone(),
                 },
             ]);
 
-            let tx = build_transaction(&node, &input_tx, since);
+            let tx = build_transaction(node, &input_tx, since);
             ins.extend(vec![
                 BuildInstruction::Propose {
-                    template_number: fork_switch_height - 1 - 2,
+                    template_number: case.tx_committed_number - 2,
                     proposal_short_id: tx.proposal_short_id(),
                 },
                 BuildInstruction::Commit {
-                    template_number: fork_switch_height - 1,
+                    template_number: case.tx_committed_number,
                     transaction: tx,
                 },
             ]);
-            let actual_result_before_switch =
-                node.build_according_to_instructions(fork_switch_height, ins);
-
-            assert_result_eq!(
-                expected_result_before_switch,
-                actual_result_before_switch,
-                "case-{}",
-                case_id,
-            );
-        }
-
-        // Run cases 4, 5, 6.
-        //
-        // The current tip number is 2999, that has not activate fork.
-        // Please read struct documentation for detail.
-        //
-        // [(case_id, input_tx_committed_number, tip_number)]
-        let cases_after_switch = vec![
-            (3, 1980, Ok(())),
-            (4, 1981, Ok(())),
-            (5, 1982, Err(ERROR_IMMATURE)),
-        ];
-        for (case_id, input_tx_committed_number, expected_result_after_switch) in cases_after_switch
-        {
-            let node = node2021.clone_node(&format!("case-{}-after-switch", case_id));
+            let actual_result = node.build_according_to_instructions(fork_switch_height, ins);
+            assert_result_eq!(case.expected_result, actual_result);
+        });
+    }
+}
 
-            let mut ins = construct_chain_instructions.clone();
-            ins.extend(vec![
-                BuildInstruction::Propose {
-                    template_number: input_tx_committed_number - 2,
-                    proposal_short_id: input_tx.proposal_short_id(),
-                },
-                BuildInstruction::Commit {
-                    template_number: input_tx_committed_number,
-                    transaction: input_tx.clone(),
-                },
-            ]);
+#[derive(Debug)]
+struct CaseParams {
+    id: usize,
+    input_tx_committed_number: BlockNumber,
+    tx_committed_number: BlockNumber,
+    expected_result: Result<(), &'static str>,
+}
 
-            let tx = build_transaction(&node, &input_tx, since);
-            ins.extend(vec![
-                BuildInstruction::Propose {
-                    template_number: fork_switch_height - 2,
-                    proposal_short_id: tx.proposal_short_id(),
-                },
-                BuildInstruction::Commit {
-                    template_number: fork_switch_height,
-                    transaction: tx,
-                },
-            ]);
-            let actual_result_after_switch =
-                node.build_according_to_instructions(fork_switch_height, ins);
+impl TableRow for CaseParams {
+    fn id(&self) -> usize {
+        self.id
+    }
+}
 
-            assert_result_eq!(
-                expected_result_after_switch,
-                actual_result_after_switch,
-                "case-{}",
-                case_id,
-            );
-        }
+impl RFC0028 {
+    // Please read struct documentation for detail.
+    fn cases_params(&self, fork_switch_height: BlockNumber) -> Vec<CaseParams> {
+        vec![
+            // The transaction is committed at 2999, that has not activate fork.
+            CaseParams {
+                id: 0,
+                input_tx_committed_number: 1998,
+                tx_committed_number: fork_switch_height - 1,
+                expected_result: Ok(()),
+            },
+            CaseParams {
+                id: 1,
+                input_tx_committed_number: 1999,
+                tx_committed_number: fork_switch_height - 1,
+                expected_result: Ok(()),
+            },
+            CaseParams {
+                id: 2,
+                input_tx_committed_number: 2000,
+                tx_committed_number: fork_switch_height - 1,
+                expected_result: Err(ERROR_IMMATURE),
+            },
+            // The transaction is committed at 3000, that has activated fork.
+            CaseParams {
+                id: 3,
+                input_tx_committed_number: 1980,
+                tx_committed_number: fork_switch_height,
+                expected_result: Ok(()),
+            },
+            CaseParams {
+                id: 4,
+                input_tx_committed_number: 1981,
+                tx_committed_number: fork_switch_height,
+                expected_result: Ok(()),
+            },
+            CaseParams {
+                id: 5,
+                input_tx_committed_number: 1982,
+                tx_committed_number: fork_switch_height,
+                expected_result: Err(ERROR_IMMATURE),
+            },
+        ]
     }
 }
 
@@ -22,6 +22,12 @@ struct CaseParams {
     expected_result: Result<(), &'static str>,
 }
 
+impl TableRow for CaseParams {
+    fn id(&self) -> usize {
+        self.id
+    }
+}
+
 /// ### Convention
 ///
 /// - `a1`, `a2` and `b1` are 3 cells
@@ -99,18 +105,20 @@ struct CaseParams {
 pub struct RFC0029;
 
 impl Case for RFC0029 {
+    fn tags(&self) -> Vec<&'static str> {
+        vec!["hardfork", "rfc0029"]
+    }
+
     fn case_options(&self) -> CaseOptions {
         CaseOptions {
             make_all_nodes_connected: false,
             make_all_nodes_synced: false,
             make_all_nodes_connected_and_synced: false,
-            node_options: vec![NodeOptions {
-                node_name: String::from("node2021"),
-                ckb_binary: CKB2021.read().unwrap().clone(),
-                initial_database: "testdata/db/Epoch2V2TestData",
-                chain_spec: "testdata/spec/ckb2021",
-                app_config: "testdata/config/ckb2021",
-            }],
+            node_options: vec![NodeOptions::ckb2021("node2021")
+                .ckb_binary(ckb2021())
+                .initial_database("testdata/db/Epoch2V2TestData")
+                .build()],
+            ..Default::default()
         }
     }
 
@@ -261,12 +269,7 @@ impl Case for RFC0029 {
         assert!(node2021.get_tip_block().epoch().number() < RFC0029_EPOCH_NUMBER);
 
         let input = node2021.get_spendable_always_success_cells()[0].to_owned();
-        for case in self.cases_params() {
-            let node = {
-                let node = node2021.clone_node(&format!("case-{}-node", case.id));
-                node.pull_node(node2021).unwrap();
-                node
-            };
+        TableCase::new(node2021, self.cases_params()).run(|node, case| {
             let tx = self.build_transaction(
                 &code_hash_via_data_hash,
                 &code_hash_via_type_hash,
@@ -274,7 +277,7 @@ impl Case for RFC0029 {
                 &deployer,
                 &input,
                 case.script_hash_type,
-                case.cell_deps,
+                case.cell_deps.clone(),
             );
             let actual_result = node.build_according_to_instructions(
                 case.height,
@@ -289,14 +292,8 @@ impl Case for RFC0029 {
                     },
                 ],
             );
-            assert_result_eq!(
-                case.expected_result,
-                actual_result,
-                "case.id: {}, node.log: {}",
-                case.id,
-                node.log_path().to_string_lossy()
-            );
-        }
+            assert_result_eq!(case.expected_result, actual_result);
+        });
     }
 }
 
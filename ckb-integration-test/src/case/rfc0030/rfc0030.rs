@@ -18,6 +18,12 @@ struct CaseParams {
     expected_result: Result<EpochNumberWithFraction, &'static str>,
 }
 
+impl TableRow for CaseParams {
+    fn id(&self) -> usize {
+        self.id
+    }
+}
+
 /// ## Note
 ///
 /// Our test target is that makes sure:
@@ -115,18 +121,19 @@ struct CaseParams {
 pub struct RFC0030;
 
 impl Case for RFC0030 {
+    fn tags(&self) -> Vec<&'static str> {
+        vec!["hardfork", "rfc0030"]
+    }
+
     fn case_options(&self) -> CaseOptions {
         CaseOptions {
             make_all_nodes_connected: false,
             make_all_nodes_synced: false,
             make_all_nodes_connected_and_synced: false,
-            node_options: vec![NodeOptions {
-                node_name: String::from("node2021"),
-                ckb_binary: CKB2021.read().unwrap().clone(),
-                initial_database: "testdata/db/empty",
-                chain_spec: "testdata/spec/ckb2021",
-                app_config: "testdata/config/ckb2021",
-            }],
+            node_options: vec![NodeOptions::ckb2021("node2021")
+                .ckb_binary(ckb2021())
+                .build()],
+            ..Default::default()
         }
     }
 
@@ -154,49 +161,41 @@ impl Case for RFC0030 {
             OutPoint::new(tip_cellbase_hash, 0)
         };
 
-        for case in self.cases_params() {
+        // Only the transactions expected to be mature at some epoch are committed
+        let cases_params = self
+            .cases_params()
+            .into_iter()
+            .filter(|case| case.expected_result.is_ok())
+            .collect();
+        TableCase::new(node2021, cases_params).run(|node, case| {
             let tx = build_transaction(node2021, &input_out_point, case.since);
-            if let Ok(expected_mature_epoch) = case.expected_result {
-                let node = node2021.clone_node(&format!("case-{}-node2021", case.id));
-                let mature_height = epoch_to_height(&node, expected_mature_epoch);
-                let immature_height = mature_height - 1;
-                let immature_result = node.build_according_to_instructions(
-                    immature_height,
-                    vec![
-                        BuildInstruction::Propose {
-                            proposal_short_id: tx.proposal_short_id(),
-                            template_number: immature_height - 2,
-                        },
-                        BuildInstruction::Commit {
-                            transaction: tx.clone(),
-                            template_number: immature_height,
-                        },
-                    ],
-                );
-                assert_result_eq!(
-                    Result::<(), &str>::Err(ERROR_IMMATURE),
-                    immature_result,
-                    "case.id: {}, node.log: {}",
-                    case.id,
-                    node.log_path().to_string_lossy()
-                );
-
-                let mature_result = node.build_according_to_instructions(
-                    mature_height,
-                    vec![BuildInstruction::Commit {
+            let expected_mature_epoch = case.expected_result.expect("checked");
+            let mature_height = epoch_to_height(node, expected_mature_epoch);
+            let immature_height = mature_height - 1;
+            let immature_result = node.build_according_to_instructions(
+                immature_height,
+                vec![
+                    BuildInstruction::Propose {
+                        proposal_short_id: tx.proposal_short_id(),
+                        template_number: immature_height - 2,
+                    },
+                    BuildInstruction::Commit {
                         transaction: tx.clone(),
-                        template_number: mature_height,
-                    }],
-                );
-                assert_eq!(
-                    Result::<(), String>::Ok(()),
-                    mature_result,
-                    "case.id: {}, node.log: {}",
-                    case.id,
-                    node.log_path().to_string_lossy()
-                );
-            }
-        }
+                        template_number: immature_height,
+                    },
+                ],
+            );
+            assert_result_eq!(Result::<(), &str>::Err(ERROR_IMMATURE), immature_result);
+
+            let mature_result = node.build_according_to_instructions(
+                mature_height,
+                vec![BuildInstruction::Commit {
+                    transaction: tx.clone(),
+                    template_number: mature_height,
+                }],
+            );
+            assert_eq!(Result::<(), String>::Ok(()), mature_result);
+        });
     }
 }
 
@@ -35,18 +35,20 @@ const RFC0031_BLOCK_NUMBER: BlockNumber = 3000;
 pub struct RFC0031;
 
 impl Case for RFC0031 {
+    fn tags(&self) -> Vec<&'static str> {
+        vec!["hardfork", "rfc0031"]
+    }
+
     fn case_options(&self) -> CaseOptions {
         CaseOptions {
             make_all_nodes_connected: false,
             make_all_nodes_synced: false,
             make_all_nodes_connected_and_synced: false,
-            node_options: vec![NodeOptions {
-                node_name: String::from("node2021"),
-                ckb_binary: CKB2021.read().unwrap().clone(),
-                initial_database: "testdata/db/Epoch2V2TestData",
-                chain_spec: "testdata/spec/ckb2021",
-                app_config: "testdata/config/ckb2021",
-            }],
+            node_options: vec![NodeOptions::ckb2021("node2021")
+                .ckb_binary(ckb2021())
+                .initial_database("testdata/db/Epoch2V2TestData")
+                .build()],
+            ..Default::default()
         }
     }
 
@@ -56,25 +58,19 @@ impl Case for RFC0031 {
         let fork_switch_height = estimate_start_number_of_epoch(node2021, RFC0031_EPOCH_NUMBER);
         node2021.mine_to(fork_switch_height - 6);
 
-        for case in self.cases_params() {
-            let node = node2021.clone_node(&format!("case-{}-node", case.id));
+        TableCase::new(node2021, self.cases_params()).run(|node, case| {
             node.mine_to(case.height - 1);
-            let block = self.build_block(&node, case.extension_size);
+            let block = self.build_block(node, case.extension_size);
             let actual_result = node
                 .rpc_client()
                 .submit_block("".to_owned(), block.data().into())
                 .map(|_| ());
-            assert_result_eq!(
-                case.expected_result,
-                actual_result,
-                "case.id: {}, node.log: {}",
-                case.id,
-                node.log_path().to_string_lossy()
-            );
-        }
+            assert_result_eq!(case.expected_result, actual_result);
+        });
     }
 }
 
+#[derive(Debug)]
 struct CaseParams {
     id: usize,
     extension_size: Option<usize>,
@@ -82,6 +78,12 @@ struct CaseParams {
     expected_result: Result<(), &'static str>,
 }
 
+impl TableRow for CaseParams {
+    fn id(&self) -> usize {
+        self.id
+    }
+}
+
 impl RFC0031 {
     fn build_block(&self, node: &Node, extension_size: Option<usize>) -> BlockView {
         let template = node.rpc_client().get_block_template(None, None, None);
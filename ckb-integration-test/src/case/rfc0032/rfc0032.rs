@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use crate::util::block_assembler_hash_type_is_data;
 use ckb_testkit::ckb_types::{
     core::{BlockNumber, Capacity, Cycle, ScriptHashType, TransactionBuilder, TransactionView},
     packed::{CellInput, CellOutput, OutPoint, Script},
@@ -14,7 +15,7 @@ use ckb_testkit::{assert_result_eq, Node, NodeOptions, Nodes, SYSTEM_CELL_ALWAYS
 ///
 /// * We determine the VM selection via checking the transaction cycles.
 /// * We want the input transaction is VM-determined, so in this case,
-/// we config node with `app_config: "testdata/config/ckb2021_block_assembler_hash_type_is_data/ckb.toml"`
+/// we config node with `block_assembler_hash_type_is_data`
 ///
 /// ## Cases
 ///
@@ -45,6 +46,10 @@ const HARDFORK_DELAY_WINDOW: u64 = 10;
 const ERROR_INVALID_VM_VERSION: &str = "Invalid VM Version";
 
 impl Case for RFC0032 {
+    fn tags(&self) -> Vec<&'static str> {
+        vec!["hardfork", "rfc0032"]
+    }
+
     fn case_options(&self) -> CaseOptions {
         Default::default()
     }
@@ -86,14 +91,13 @@ impl RFC0032 {
     }
 
     fn setup_node(&self, case: &CaseParams) -> Node {
-        let node_options = NodeOptions {
-            node_name: format!("{}-case-{}", self.case_name(), case.id),
-            ckb_binary: CKB2021.read().unwrap().clone(),
-            initial_database: "testdata/db/Epoch2V2TestData",
-            chain_spec: "testdata/spec/ckb2021",
-            // We want the input transaction is VM-determined
-            app_config: "testdata/config/ckb2021_block_assembler_hash_type_is_data",
-        };
+        // We want the input transaction is VM-determined
+        let node_options = block_assembler_hash_type_is_data(
+            NodeOptions::ckb2021(format!("{}-case-{}", self.case_name(), case.id))
+                .ckb_binary(ckb2021())
+                .initial_database("testdata/db/Epoch2V2TestData"),
+        )
+        .build();
         let mut node = Node::init(self.case_name(), node_options, true);
         node.start();
 
@@ -74,18 +74,20 @@ use ckb_testkit::ckb_types::{
 pub struct RFC0034;
 
 impl Case for RFC0034 {
+    fn tags(&self) -> Vec<&'static str> {
+        vec!["hardfork", "rfc0034"]
+    }
+
     fn case_options(&self) -> CaseOptions {
         CaseOptions {
             make_all_nodes_connected: false,
             make_all_nodes_synced: false,
             make_all_nodes_connected_and_synced: false,
-            node_options: vec![NodeOptions {
-                node_name: String::from("node2021"),
-                ckb_binary: CKB2021.read().unwrap().clone(),
-                initial_database: "testdata/db/Epoch2V2TestData",
-                chain_spec: "testdata/spec/ckb2021",
-                app_config: "testdata/config/ckb2021",
-            }],
+            node_options: vec![NodeOptions::ckb2021("node2021")
+                .ckb_binary(ckb2021())
+                .initial_database("testdata/db/Epoch2V2TestData")
+                .build()],
+            ..Default::default()
         }
     }
 
@@ -118,22 +120,11 @@ impl Case for RFC0034 {
             }
         }
 
-        for case in self.cases_params() {
-            let txs = self.build_transactions(node2021, &deployer, &case);
-            let node = node2021.clone_node(&format!("case-{}", case.id));
-            node.pull_node(node2021).unwrap();
-
-            let actual_result = self.run_case(&node, &txs);
-            assert_result_eq!(
-                case.expected_result,
-                actual_result,
-                "case: {}, expected: {:?}, actual: {:?}, node.log_path: {}",
-                case.id,
-                case.expected_result,
-                actual_result,
-                node.log_path().to_string_lossy(),
-            );
-        }
+        TableCase::new(node2021, self.cases_params()).run(|node, case| {
+            let txs = self.build_transactions(node2021, &deployer, case);
+            let actual_result = self.run_case(node, &txs);
+            assert_result_eq!(case.expected_result, actual_result);
+        });
     }
 }
 
@@ -161,6 +152,12 @@ struct CaseParams {
     expected_result: Result<(), String>,
 }
 
+impl TableRow for CaseParams {
+    fn id(&self) -> usize {
+        self.id
+    }
+}
+
 impl RFC0034 {
     fn run_case(&self, node: &Node, txs: &[TransactionView]) -> Result<(), String> {
         for tx in txs {
@@ -4,8 +4,8 @@
 //
 // Check the connections via RPC `get_peers`
 
+use crate::binaries::{ckb2019, ckb2021, ROLE_CKB2019, ROLE_CKB2021};
 use crate::case::{Case, CaseOptions};
-use crate::{CKB2019, CKB2021};
 use ckb_testkit::ckb_types::core::BlockNumber;
 use ckb_testkit::util::wait_until;
 use ckb_testkit::Nodes;
@@ -16,43 +16,40 @@ const RFC0035_BLOCK_NUMBER: BlockNumber = 3000;
 pub struct RFC0035V2021Connection;
 
 impl Case for RFC0035V2021Connection {
+    fn tags(&self) -> Vec<&'static str> {
+        vec!["hardfork", "p2p", "rfc0035", "slow"]
+    }
+
+    fn binary_roles(&self) -> Vec<&'static str> {
+        vec![ROLE_CKB2019, ROLE_CKB2021]
+    }
+
     fn case_options(&self) -> CaseOptions {
         CaseOptions {
             make_all_nodes_connected: true,
             make_all_nodes_synced: true,
             make_all_nodes_connected_and_synced: true,
             node_options: vec![
-                NodeOptions {
-                    node_name: String::from("node2019"),
-                    ckb_binary: CKB2019.read().unwrap().clone(),
-                    initial_database: "testdata/db/Epoch2V1TestData",
-                    chain_spec: "testdata/spec/ckb2019",
-                    app_config: "testdata/config/ckb2019",
-                },
-                NodeOptions {
-                    node_name: String::from("node2019_2"),
-                    ckb_binary: CKB2019.read().unwrap().clone(),
-                    initial_database: "testdata/db/Epoch2V1TestData",
-                    chain_spec: "testdata/spec/ckb2019",
-                    app_config: "testdata/config/ckb2019",
-                },
-                NodeOptions {
-                    node_name: String::from("node2021"),
-                    ckb_binary: CKB2021.read().unwrap().clone(),
-                    initial_database: "testdata/db/Epoch2V2TestData",
-                    chain_spec: "testdata/spec/ckb2021",
-                    app_config: "testdata/config/ckb2021",
-                },
-                NodeOptions {
-                    node_name: String::from("node2021_2"),
-                    ckb_binary: CKB2021.read().unwrap().clone(),
-                    initial_database: "testdata/db/Epoch2V2TestData",
-                    chain_spec: "testdata/spec/ckb2021",
-                    app_config: "testdata/config/ckb2021",
-                },
+                NodeOptions::ckb2019("node2019")
+                    .ckb_binary(ckb2019())
+                    .initial_database("testdata/db/Epoch2V1TestData")
+                    .build(),
+                NodeOptions::ckb2019("node2019_2")
+                    .ckb_binary(ckb2019())
+                    .initial_database("testdata/db/Epoch2V1TestData")
+                    .build(),
+                NodeOptions::ckb2021("node2021")
+                    .ckb_binary(ckb2021())
+                    .initial_database("testdata/db/Epoch2V2TestData")
+                    .build(),
+                NodeOptions::ckb2021("node2021_2")
+                    .ckb_binary(ckb2021())
+                    .initial_database("testdata/db/Epoch2V2TestData")
+                    .build(),
             ]
             .into_iter()
             .collect(),
+            ..Default::default()
         }
     }
 
@@ -92,13 +89,9 @@ impl Case for RFC0035V2021Connection {
 
         // TODO Actually, the below check is for SyncProtocol
         let mut fresh_node2021 = {
-            let node_options = NodeOptions {
-                node_name: String::from("fresh_node2021"),
-                ckb_binary: CKB2021.read().unwrap().clone(),
-                initial_database: "testdata/db/empty",
-                chain_spec: "testdata/spec/ckb2021",
-                app_config: "testdata/config/ckb2021",
-            };
+            let node_options = NodeOptions::ckb2021("fresh_node2021")
+                .ckb_binary(ckb2021())
+                .build();
             Node::init(self.case_name(), node_options, true)
         };
         fresh_node2021.start();
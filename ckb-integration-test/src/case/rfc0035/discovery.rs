@@ -2,9 +2,9 @@
 //
 // After fork2021, node2021s will still propagate info
 
+use crate::binaries::ckb2021;
 use crate::case::{Case, CaseOptions};
 use crate::util::estimate_start_number_of_epoch;
-use crate::CKB2021;
 use ckb_testkit::ckb_types::core::EpochNumber;
 use ckb_testkit::util::wait_until;
 use ckb_testkit::{NodeOptions, Nodes};
@@ -14,36 +14,35 @@ const RFC0035_EPOCH_NUMBER: EpochNumber = 3;
 pub struct RFC0035V2021Discovery;
 
 impl Case for RFC0035V2021Discovery {
+    fn tags(&self) -> Vec<&'static str> {
+        vec!["hardfork", "p2p", "rfc0035"]
+    }
+
     fn case_options(&self) -> CaseOptions {
         CaseOptions {
             make_all_nodes_connected: false,
             make_all_nodes_synced: false,
             make_all_nodes_connected_and_synced: false,
             node_options: vec![
-                NodeOptions {
-                    node_name: String::from("node2021_1"),
-                    ckb_binary: CKB2021.read().unwrap().clone(),
-                    initial_database: "testdata/db/Epoch2V2TestData",
-                    chain_spec: "testdata/spec/ckb2021",
-                    app_config: "testdata/config/connect_outbound_interval_secs",
-                },
-                NodeOptions {
-                    node_name: String::from("node2021_2"),
-                    ckb_binary: CKB2021.read().unwrap().clone(),
-                    initial_database: "testdata/db/Epoch2V2TestData",
-                    chain_spec: "testdata/spec/ckb2021",
-                    app_config: "testdata/config/connect_outbound_interval_secs",
-                },
-                NodeOptions {
-                    node_name: String::from("node2021_3"),
-                    ckb_binary: CKB2021.read().unwrap().clone(),
-                    initial_database: "testdata/db/Epoch2V2TestData",
-                    chain_spec: "testdata/spec/ckb2021",
-                    app_config: "testdata/config/connect_outbound_interval_secs",
-                },
+                NodeOptions::ckb2021("node2021_1")
+                    .ckb_binary(ckb2021())
+                    .initial_database("testdata/db/Epoch2V2TestData")
+                    .app_config_override("network.connect_outbound_interval_secs", 1i64)
+                    .build(),
+                NodeOptions::ckb2021("node2021_2")
+                    .ckb_binary(ckb2021())
+                    .initial_database("testdata/db/Epoch2V2TestData")
+                    .app_config_override("network.connect_outbound_interval_secs", 1i64)
+                    .build(),
+                NodeOptions::ckb2021("node2021_3")
+                    .ckb_binary(ckb2021())
+                    .initial_database("testdata/db/Epoch2V2TestData")
+                    .app_config_override("network.connect_outbound_interval_secs", 1i64)
+                    .build(),
             ]
             .into_iter()
             .collect(),
+            ..Default::default()
         }
     }
 
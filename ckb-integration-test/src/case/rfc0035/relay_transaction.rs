@@ -1,7 +1,8 @@
 use super::{HARDFORK_DELAY_WINDOW, RFC0035_BLOCK_NUMBER};
+use crate::binaries::{ROLE_CKB2019, ROLE_CKB2021};
 use crate::{
     prelude::*,
-    util::{v0_100, v0_43},
+    util::{block_assembler_hash_type_is_data, v0_100, v0_43},
 };
 use ckb_testkit::ckb_types::{
     core::{
@@ -115,20 +116,28 @@ use std::time::Duration;
 pub struct RFC0035RelayTransaction;
 
 impl Case for RFC0035RelayTransaction {
+    fn tags(&self) -> Vec<&'static str> {
+        vec!["hardfork", "p2p", "rfc0035"]
+    }
+
+    fn binary_roles(&self) -> Vec<&'static str> {
+        vec![ROLE_CKB2019, ROLE_CKB2021]
+    }
+
     fn case_options(&self) -> CaseOptions {
         CaseOptions {
             make_all_nodes_connected: false,
             make_all_nodes_synced: false,
             make_all_nodes_connected_and_synced: false,
-            node_options: vec![NodeOptions {
-                node_name: String::from("node2021"),
-                ckb_binary: CKB2021.read().unwrap().clone(),
-                initial_database: "testdata/db/Epoch2V2TestData",
-                chain_spec: "testdata/spec/ckb2021",
-                // Note: `input.script.hash_type == "data"` ensures the input's scripts are
-                // cycles-consistency
-                app_config: "testdata/config/ckb2021_block_assembler_hash_type_is_data",
-            }],
+            // Note: `input.script.hash_type == "data"` ensures the input's scripts are
+            // cycles-consistency
+            node_options: vec![block_assembler_hash_type_is_data(
+                NodeOptions::ckb2021("node2021")
+                    .ckb_binary(ckb2021())
+                    .initial_database("testdata/db/Epoch2V2TestData"),
+            )
+            .build()],
+            ..Default::default()
         }
     }
 
@@ -228,30 +237,14 @@ impl RFC0035RelayTransaction {
     fn setup_node(&self, case: &CaseParams, base_chain_node: &Node) -> Node {
         // We only test v0.100
         let is_ckb2021 = true;
-        let node_options = NodeOptions {
-            node_name: format!("case-{}", case.id),
-            ckb_binary: {
-                if is_ckb2021 {
-                    CKB2021.read().unwrap().clone()
-                } else {
-                    CKB2019.read().unwrap().clone()
-                }
-            },
-            chain_spec: {
-                if is_ckb2021 {
-                    "testdata/spec/ckb2021"
-                } else {
-                    "testdata/spec/ckb2019"
-                }
-            },
-            app_config: {
-                if is_ckb2021 {
-                    "testdata/config/ckb2021"
-                } else {
-                    "testdata/config/ckb2019"
-                }
-            },
-            initial_database: "testdata/db/empty",
+        let node_options = if is_ckb2021 {
+            NodeOptions::ckb2021(format!("case-{}", case.id))
+                .ckb_binary(ckb2021())
+                .build()
+        } else {
+            NodeOptions::ckb2019(format!("case-{}", case.id))
+                .ckb_binary(ckb2019())
+                .build()
         };
         let node = {
             let mut node = Node::init(self.case_name(), node_options, is_ckb2021);
@@ -1,5 +1,5 @@
+use crate::binaries::{ckb2019, ckb2021, ROLE_CKB2019, ROLE_CKB2021};
 use crate::case::{Case, CaseOptions};
-use crate::{CKB2019, CKB2021};
 use ckb_testkit::ckb_types::core::EpochNumber;
 use ckb_testkit::Nodes;
 use ckb_testkit::{Node, NodeOptions};
@@ -9,43 +9,40 @@ const RFC0035_EPOCH_NUMBER: EpochNumber = 3;
 pub struct RFC0035V2019;
 
 impl Case for RFC0035V2019 {
+    fn tags(&self) -> Vec<&'static str> {
+        vec!["hardfork", "p2p", "rfc0035"]
+    }
+
+    fn binary_roles(&self) -> Vec<&'static str> {
+        vec![ROLE_CKB2019, ROLE_CKB2021]
+    }
+
     fn case_options(&self) -> CaseOptions {
         CaseOptions {
             make_all_nodes_connected: false,
             make_all_nodes_synced: false,
             make_all_nodes_connected_and_synced: false,
             node_options: vec![
-                NodeOptions {
-                    node_name: String::from("node2019"),
-                    ckb_binary: CKB2019.read().unwrap().clone(),
-                    initial_database: "testdata/db/Epoch2V1TestData",
-                    chain_spec: "testdata/spec/ckb2019",
-                    app_config: "testdata/config/ckb2019",
-                },
-                NodeOptions {
-                    node_name: String::from("node2019_2"),
-                    ckb_binary: CKB2019.read().unwrap().clone(),
-                    initial_database: "testdata/db/Epoch2V1TestData",
-                    chain_spec: "testdata/spec/ckb2019",
-                    app_config: "testdata/config/ckb2019",
-                },
-                NodeOptions {
-                    node_name: String::from("node2021"),
-                    ckb_binary: CKB2021.read().unwrap().clone(),
-                    initial_database: "testdata/db/Epoch2V2TestData",
-                    chain_spec: "testdata/spec/ckb2021",
-                    app_config: "testdata/config/ckb2021",
-                },
-                NodeOptions {
-                    node_name: String::from("node2021_2"),
-                    ckb_binary: CKB2021.read().unwrap().clone(),
-                    initial_database: "testdata/db/Epoch2V2TestData",
-                    chain_spec: "testdata/spec/ckb2021",
-                    app_config: "testdata/config/ckb2021",
-                },
+                NodeOptions::ckb2019("node2019")
+                    .ckb_binary(ckb2019())
+                    .initial_database("testdata/db/Epoch2V1TestData")
+                    .build(),
+                NodeOptions::ckb2019("node2019_2")
+                    .ckb_binary(ckb2019())
+                    .initial_database("testdata/db/Epoch2V1TestData")
+                    .build(),
+                NodeOptions::ckb2021("node2021")
+                    .ckb_binary(ckb2021())
+                    .initial_database("testdata/db/Epoch2V2TestData")
+                    .build(),
+                NodeOptions::ckb2021("node2021_2")
+                    .ckb_binary(ckb2021())
+                    .initial_database("testdata/db/Epoch2V2TestData")
+                    .build(),
             ]
             .into_iter()
             .collect(),
+            ..Default::default()
         }
     }
 
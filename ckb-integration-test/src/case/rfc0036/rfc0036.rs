@@ -1,8 +1,8 @@
 use super::{ERROR_IMMATURE_HEADER, RFC0036_EPOCH_NUMBER};
 use crate::prelude::*;
 use crate::util::estimate_start_number_of_epoch;
-use ckb_testkit::ckb_types::core::{BlockNumber, TransactionView};
-use ckb_testkit::{assert_result_eq, BuildInstruction};
+use ckb_testkit::ckb_types::core::{BlockNumber, EpochNumberWithFraction, TransactionView};
+use ckb_testkit::{assert_result_eq, BuildInstruction, ChainSpecBuilder};
 
 const RFC0036_BLOCK_NUMBER: BlockNumber = 3000;
 
@@ -19,18 +19,24 @@ const RFC0036_BLOCK_NUMBER: BlockNumber = 3000;
 pub struct RFC0036;
 
 impl Case for RFC0036 {
+    fn tags(&self) -> Vec<&'static str> {
+        vec!["hardfork", "rfc0036"]
+    }
+
     fn case_options(&self) -> CaseOptions {
         CaseOptions {
             make_all_nodes_connected: false,
             make_all_nodes_synced: false,
             make_all_nodes_connected_and_synced: false,
-            node_options: vec![NodeOptions {
-                node_name: String::from("node2021"),
-                ckb_binary: CKB2021.read().unwrap().clone(),
-                initial_database: "testdata/db/Epoch2V2TestData",
-                chain_spec: "testdata/spec/cellbase_maturity_not_zero_2021",
-                app_config: "testdata/config/ckb2021",
-            }],
+            node_options: vec![NodeOptions::ckb2021("node2021")
+                .ckb_binary(ckb2021())
+                .initial_database("testdata/db/Epoch2V2TestData")
+                .chain_spec_builder(
+                    ChainSpecBuilder::ckb2021()
+                        .cellbase_maturity(EpochNumberWithFraction::new(0, 10, 1800)),
+                )
+                .build()],
+            ..Default::default()
         }
     }
 
@@ -44,13 +50,8 @@ impl Case for RFC0036 {
         let fork_switch_height = estimate_start_number_of_epoch(node2021, RFC0036_EPOCH_NUMBER);
         node2021.mine_to(fork_switch_height - 6);
 
-        for case in self.cases_params() {
-            let node = {
-                let node = node2021.clone_node(&format!("case-{}-node", case.id));
-                node.pull_node(node2021).unwrap();
-                node
-            };
-            let tx = self.build_transaction(&node);
+        TableCase::new(node2021, self.cases_params()).run(|node, case| {
+            let tx = self.build_transaction(node);
             let ins = vec![
                 BuildInstruction::Propose {
                     proposal_short_id: tx.proposal_short_id(),
@@ -62,14 +63,8 @@ impl Case for RFC0036 {
                 },
             ];
             let actual_result = node.build_according_to_instructions(case.height, ins);
-            assert_result_eq!(
-                case.expected_result,
-                actual_result,
-                "case.id: {}, node.log: {}",
-                case.id,
-                node.log_path().to_string_lossy(),
-            );
-        }
+            assert_result_eq!(case.expected_result, actual_result);
+        });
     }
 }
 
@@ -80,6 +75,12 @@ struct CaseParams {
     expected_result: Result<(), &'static str>,
 }
 
+impl TableRow for CaseParams {
+    fn id(&self) -> usize {
+        self.id
+    }
+}
+
 impl RFC0036 {
     fn cases_params(&self) -> Vec<CaseParams> {
         vec![
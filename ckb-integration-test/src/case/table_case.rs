@@ -0,0 +1,235 @@
+use super::case_result::panic_message;
+use crate::binaries;
+use ckb_testkit::Node;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+
+/// A row of a `TableCase`, e.g. the `CaseParams` of the hardfork cases.
+pub trait TableRow: Debug + Send {
+    /// The row id, used for naming the row's node and reporting the failed rows.
+    fn id(&self) -> usize;
+}
+
+/// A table-driven case, which runs every row against its own node cloned from `base_node`.
+///
+/// A failing row does not stop the later rows. After all rows ran, `run` panics with the ids
+/// and failure messages of all the failed rows, not only the first one.
+///
+/// ```ignore
+/// TableCase::new(node2021, self.cases_params()).run(|node, case| {
+///     let actual_result = node.build_according_to_instructions(case.height, ..);
+///     assert_result_eq!(case.expected_result, actual_result);
+/// });
+/// ```
+pub struct TableCase<'a, P> {
+    base_node: &'a Node,
+    rows: Vec<P>,
+    jobs: usize,
+}
+
+impl<'a, P: TableRow> TableCase<'a, P> {
+    pub fn new(base_node: &'a Node, rows: Vec<P>) -> Self {
+        Self {
+            base_node,
+            rows,
+            jobs: 1,
+        }
+    }
+
+    /// Run at most `jobs` rows at the same time, each in its own thread. Only for rows which do
+    /// not depend on each other. Default is 1, running rows one by one in order.
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        assert!(jobs > 0, "jobs should be greater than 0");
+        self.jobs = jobs;
+        self
+    }
+
+    /// Run every row via `run_row(cloned_node, row)`, which asserts the row's expected result.
+    pub fn run<F>(self, run_row: F)
+    where
+        F: Fn(&Node, &P) + Sync,
+    {
+        let base_node = self.base_node;
+        // Cloning pauses the base node, so clone one by one
+        let clone_lock = Mutex::new(());
+        run_rows(self.rows, self.jobs, |row| {
+            let row_id = row.id();
+            let cloned = {
+                let _guard = clone_lock.lock().unwrap();
+                panic::catch_unwind(AssertUnwindSafe(|| {
+                    base_node.clone_node(&format!("case-{}-node", row_id))
+                }))
+            };
+            let node = cloned.map_err(|panic_payload| {
+                format!("failed to clone node, {}", panic_message(&*panic_payload))
+            })?;
+            ckb_testkit::info!(
+                "row {} START, node.log: {}",
+                row_id,
+                node.log_path().display()
+            );
+            panic::catch_unwind(AssertUnwindSafe(|| run_row(&node, row))).map_err(|panic_payload| {
+                format!(
+                    "{}, node.log: {}",
+                    panic_message(&*panic_payload),
+                    node.log_path().display()
+                )
+            })
+        });
+    }
+}
+
+/// Run every row via `run_row`, at most `jobs` rows at the same time. After all rows ran, panic
+/// with the ids and failure messages of all the failed rows.
+fn run_rows<P, F>(rows: Vec<P>, jobs: usize, run_row: F)
+where
+    P: TableRow,
+    F: Fn(&P) -> Result<(), String> + Sync,
+{
+    let total = rows.len();
+    let jobs = jobs.min(total.max(1));
+    let pending = Mutex::new(rows.into_iter().collect::<VecDeque<_>>());
+    // [(row_id, failure)]
+    let failures: Mutex<Vec<(usize, String)>> = Mutex::new(Vec::new());
+    let log_target = ckb_testkit::LOG_TARGET.with(|c| c.borrow().clone());
+    let bindings = binaries::bound_roles();
+
+    let run_pending_rows = || {
+        ckb_testkit::LOG_TARGET.with(|c| {
+            *c.borrow_mut() = log_target.clone();
+        });
+        binaries::bind_roles(&bindings);
+        loop {
+            let row = match pending.lock().unwrap().pop_front() {
+                Some(row) => row,
+                None => break,
+            };
+            let row_id = row.id();
+            let result = panic::catch_unwind(AssertUnwindSafe(|| run_row(&row)))
+                .unwrap_or_else(|panic_payload| Err(panic_message(&*panic_payload)));
+            match result {
+                Ok(()) => ckb_testkit::info!("row {} PASSED", row_id),
+                Err(message) => {
+                    let message = format!("{}, row: {:?}", message, row);
+                    ckb_testkit::error!("row {} FAILED, {}", row_id, message);
+                    failures.lock().unwrap().push((row_id, message));
+                }
+            }
+        }
+    };
+    if jobs <= 1 {
+        run_pending_rows();
+    } else {
+        crossbeam::scope(|scope| {
+            for _ in 0..jobs {
+                scope.spawn(|_| run_pending_rows());
+            }
+        })
+        .unwrap_or_else(|_| panic!("failed to join the row threads"));
+    }
+
+    let mut failures = failures.into_inner().unwrap();
+    if !failures.is_empty() {
+        failures.sort_by_key(|(row_id, _)| *row_id);
+        let row_ids = failures
+            .iter()
+            .map(|(row_id, _)| *row_id)
+            .collect::<Vec<_>>();
+        let details = failures
+            .iter()
+            .map(|(row_id, message)| format!("row {}: {}", row_id, message))
+            .collect::<Vec<_>>()
+            .join("\n");
+        panic!(
+            "{} of {} rows failed, row ids: {:?}\n{}",
+            failures.len(),
+            total,
+            row_ids,
+            details
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_rows, TableRow};
+    use crate::case::case_result::panic_message;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::Mutex;
+
+    #[derive(Debug)]
+    struct Row(usize);
+
+    impl TableRow for Row {
+        fn id(&self) -> usize {
+            self.0
+        }
+    }
+
+    fn rows(n: usize) -> Vec<Row> {
+        (0..n).map(Row).collect()
+    }
+
+    #[test]
+    fn test_collect_failures_of_every_row() {
+        for jobs in [1, 4] {
+            let ran = Mutex::new(Vec::new());
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                run_rows(rows(10), jobs, |row| {
+                    ran.lock().unwrap().push(row.0);
+                    match row.0 {
+                        3 => panic!("row 3 panicked"),
+                        5 => Err("row 5 returned error".to_string()),
+                        7 => {
+                            assert_eq!(row.0, 0, "row 7 mismatched");
+                            Ok(())
+                        }
+                        _ => Ok(()),
+                    }
+                })
+            }));
+            let message = panic_message(&*result.unwrap_err());
+            assert!(
+                message.starts_with("3 of 10 rows failed, row ids: [3, 5, 7]\n"),
+                "{}",
+                message
+            );
+            assert!(message.contains("row 3: row 3 panicked, row: Row(3)"));
+            assert!(message.contains("row 5: row 5 returned error, row: Row(5)"));
+            assert!(message.contains("row 7: "));
+            assert!(message.contains("row 7 mismatched"));
+
+            // The failures do not stop the later rows
+            let mut ran = ran.into_inner().unwrap();
+            ran.sort_unstable();
+            assert_eq!(ran, (0..10).collect::<Vec<_>>(), "jobs: {}", jobs);
+        }
+    }
+
+    #[test]
+    fn test_jobs_run_every_row_once() {
+        for (n_rows, jobs) in [(0, 1), (1, 4), (20, 1), (20, 4), (3, 8)] {
+            let ran = Mutex::new(Vec::new());
+            run_rows(rows(n_rows), jobs, |row| {
+                ran.lock().unwrap().push(row.0);
+                Ok(())
+            });
+            let ran = ran.into_inner().unwrap();
+            if jobs == 1 {
+                // One by one in order
+                assert_eq!(ran, (0..n_rows).collect::<Vec<_>>());
+            }
+            let mut ran = ran;
+            ran.sort_unstable();
+            assert_eq!(
+                ran,
+                (0..n_rows).collect::<Vec<_>>(),
+                "n_rows: {}, jobs: {}",
+                n_rows,
+                jobs
+            );
+        }
+    }
+}
@@ -1,35 +1,40 @@
+pub mod binaries;
 pub mod case;
+mod parallel;
 pub mod prelude;
+mod report;
 pub mod testdata;
 pub mod util;
 
 use clap::{value_t_or_exit, App, Arg, ArgMatches, SubCommand};
 use lazy_static::lazy_static;
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::exit;
 use std::sync::RwLock;
+use std::time::Duration;
 
-// TODO Create a shortcut for CKB2019/CKB2021
+// `CKB2019` and `CKB2021` are the binaries given by `--ckb2019` and `--ckb2021`, read them via
+// `binaries::ckb2019()` and `binaries::ckb2021()`, which prefer the roles bound for the case
 lazy_static! {
     pub static ref CKB2019: RwLock<PathBuf> = RwLock::new(PathBuf::new());
     pub static ref CKB2021: RwLock<PathBuf> = RwLock::new(PathBuf::new());
+    pub static ref ARTIFACTS_DIR: RwLock<PathBuf> = RwLock::new(PathBuf::new());
 }
 
-fn filter_cases(arg_matches: &ArgMatches) -> Vec<Box<dyn case::Case>> {
-    if let Some(filtering_cases) = arg_matches.values_of("cases") {
-        filtering_cases
-            .filter_map(|case_name| {
-                for case in crate::case::all_cases() {
-                    if case.case_name() == case_name {
-                        return Some(case);
-                    }
-                }
-                panic!("unknown case \"{}\"", case_name);
-            })
-            .collect()
-    } else {
-        crate::case::all_cases()
+fn case_selector(arg_matches: &ArgMatches) -> case::CaseSelector {
+    let values = |name: &str| {
+        arg_matches
+            .values_of(name)
+            .map(|values| values.map(ToString::to_string).collect())
+            .unwrap_or_default()
+    };
+    case::CaseSelector {
+        patterns: values("cases"),
+        tags: values("tags"),
+        exclude_tags: values("exclude-tags"),
+        skip: values("skip"),
     }
 }
 
@@ -40,18 +45,185 @@ fn main() {
 
     match matches.subcommand() {
         ("run", Some(arg_matches)) => {
-            crate::init_ckb_binaries(&arg_matches);
-            for case in filter_cases(&arg_matches) {
-                crate::case::run_case(case);
+            let matrix = crate::init_binary_matrix(&arg_matches);
+            crate::init_artifacts_dir(&arg_matches);
+            if let Some(port_range) = arg_matches.value_of("port-range") {
+                crate::init_port_range(port_range);
+            }
+            let record_rpc = arg_matches.value_of("record-rpc").map(PathBuf::from);
+            let _recorder = record_rpc.as_deref().map(ckb_testkit::RpcRecorder::start);
+            let jobs = if arg_matches.is_present("jobs") {
+                value_t_or_exit!(arg_matches, "jobs", usize)
+            } else {
+                1
+            };
+            let (cases, skipped_cases) = case_selector(&arg_matches).select(case::all_cases());
+            let mut case_runs = Vec::new();
+            // The cases not run, i.e. skipped by the selector or missing the required binaries
+            let mut not_run_results = Vec::new();
+            for case in cases {
+                match matrix.combinations(&case.binary_roles()) {
+                    Ok(combinations) => {
+                        for bindings in combinations {
+                            let case_run =
+                                case::CaseRun::new(case.case_name(), bindings, matrix.is_matrix());
+                            case_runs.push(case_run);
+                        }
+                    }
+                    Err(message) => {
+                        // A missing binary is a misconfigured run rather than a reason to skip
+                        let result = case::CaseResult::failed(
+                            case.case_name(),
+                            Duration::from_secs(0),
+                            message,
+                        );
+                        not_run_results.push(result);
+                    }
+                }
+            }
+            for case in skipped_cases {
+                not_run_results.push(case::CaseResult::skipped(case.case_name()));
+            }
+            let mut results = if jobs > 1 {
+                let log_args = log_args(&matches);
+                crate::parallel::run_cases_in_parallel(
+                    case_runs,
+                    &matrix,
+                    jobs,
+                    &log_args,
+                    record_rpc.as_deref(),
+                )
+            } else {
+                run_cases(case_runs)
+            };
+            results.extend(not_run_results);
+            if let Some(result_file) = arg_matches.value_of("result-file") {
+                // Running as a worker of `--jobs`, report the result of the only case to the
+                // parent process
+                for result in results.iter() {
+                    result.write_to(Path::new(result_file));
+                }
+            } else {
+                crate::case::print_summary(&results);
+            }
+            if let Some(report_json) = arg_matches.value_of("report-json") {
+                crate::report::write_json_report(
+                    Path::new(report_json),
+                    &results,
+                    matrix.binaries(),
+                );
+            }
+            if let Some(report_junit) = arg_matches.value_of("report-junit") {
+                crate::report::write_junit_report(
+                    Path::new(report_junit),
+                    &results,
+                    matrix.binaries(),
+                );
+            }
+            if results.iter().any(|result| result.is_failure()) {
+                exit(1);
+            }
+        }
+        ("list", Some(arg_matches)) => {
+            let (cases, skipped_cases) = case_selector(&arg_matches).select(case::all_cases());
+            let name_width = cases
+                .iter()
+                .chain(skipped_cases.iter())
+                .map(|case| case.case_name().len())
+                .max()
+                .unwrap_or(0);
+            for case in cases.iter() {
+                println!(
+                    "{:<width$}  {}",
+                    case.case_name(),
+                    case.tags().join(","),
+                    width = name_width
+                );
+            }
+            for case in skipped_cases.iter() {
+                println!(
+                    "{:<width$}  {} (skipped)",
+                    case.case_name(),
+                    case.tags().join(","),
+                    width = name_width
+                );
             }
         }
         ("generate-testdata", Some(arg_matches)) => {
             crate::init_ckb_binaries(&arg_matches);
-            let testdatas = crate::testdata::all_testdata_generators();
-            for testdata in testdatas {
+            let names = arg_matches
+                .values_of("testdata")
+                .map(|values| values.collect::<Vec<_>>())
+                .unwrap_or_default();
+            let from_scratch = arg_matches.is_present("from-scratch");
+            for testdata in crate::testdata::select_testdata_generators(&names) {
+                let dbs = testdata.dbs();
+                if from_scratch {
+                    // Without manifests, the existing databases are not reused
+                    for db in dbs.iter() {
+                        let _ = fs::remove_file(testdata::TestdataManifest::path(&db.db_name));
+                    }
+                } else if dbs
+                    .iter()
+                    .all(|db| db.verify(testdata.testdata_name()).is_ok())
+                {
+                    ckb_testkit::info!("{} is up to date", testdata.testdata_name());
+                    continue;
+                }
                 testdata.generate();
             }
         }
+        ("verify-testdata", Some(arg_matches)) => {
+            crate::init_ckb_binaries(&arg_matches);
+            let names = arg_matches
+                .values_of("testdata")
+                .map(|values| values.collect::<Vec<_>>())
+                .unwrap_or_default();
+            let mut is_stale = false;
+            for testdata in crate::testdata::select_testdata_generators(&names) {
+                for db in testdata.dbs() {
+                    match db.verify(testdata.testdata_name()) {
+                        Ok(manifest) => println!(
+                            "{:<24}  ok     ckb {}, tip {} {}",
+                            db.db_name,
+                            manifest.binary_version,
+                            manifest.tip_number,
+                            manifest.tip_hash
+                        ),
+                        Err(reason) => {
+                            println!("{:<24}  stale  {}", db.db_name, reason);
+                            is_stale = true;
+                        }
+                    }
+                }
+            }
+            if is_stale {
+                exit(1);
+            }
+        }
+        ("replay-rpc", Some(arg_matches)) => {
+            let records_file = Path::new(arg_matches.value_of("records").unwrap());
+            let node_name = arg_matches.value_of("node").unwrap();
+            let case_name = arg_matches.value_of("case");
+            let records = ckb_testkit::RpcRecord::load_all(records_file)
+                .into_iter()
+                .filter(|record| record.node_name == node_name)
+                .filter(|record| case_name.map_or(true, |case_name| record.case_name == case_name))
+                .collect::<Vec<_>>();
+            if records.is_empty() {
+                eprintln!(
+                    "no records of node {} in {}",
+                    node_name,
+                    records_file.display()
+                );
+                exit(1);
+            }
+            let replayer = ckb_testkit::RpcReplayer::start(records);
+            println!("replaying node {} at {}", node_name, replayer.url());
+            loop {
+                std::thread::park();
+            }
+        }
         _ => {
             println!("invalid usage");
             exit(1);
@@ -59,6 +231,21 @@ fn main() {
     }
 }
 
+fn run_cases(case_runs: Vec<case::CaseRun>) -> Vec<case::CaseResult> {
+    case_runs.iter().map(crate::case::run_case).collect()
+}
+
+// The global arguments passed through to the worker processes
+fn log_args(matches: &ArgMatches) -> Vec<&'static str> {
+    if matches.is_present("trace") {
+        vec!["--trace"]
+    } else if matches.is_present("debug") {
+        vec!["--debug"]
+    } else {
+        vec![]
+    }
+}
+
 fn clap_app() -> App<'static, 'static> {
     App::new("ckb-integration-test")
         .version(git_version::git_version!())
@@ -101,6 +288,34 @@ fn clap_app() -> App<'static, 'static> {
                         .value_name("PATH")
                         .help("Path to ckb2021 executable"),
                 )
+                .arg(
+                    Arg::with_name("binary")
+                        .long("binary")
+                        .required(false)
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .value_name("NAME=PATH")
+                        .help("Add a named ckb binary, e.g. \"rc=/path/to/ckb\". --ckb2019 and --ckb2021 are named \"ckb2019\" and \"ckb2021\""),
+                )
+                .arg(
+                    Arg::with_name("binaries-config")
+                        .long("binaries-config")
+                        .required(false)
+                        .takes_value(true)
+                        .value_name("PATH")
+                        .help("TOML file with [binaries] NAME = PATH and [matrix] ROLE = [NAME, ...]"),
+                )
+                .arg(
+                    Arg::with_name("matrix")
+                        .long("matrix")
+                        .required(false)
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .value_name("ROLE=NAME[,NAME]")
+                        .help("Run the cases needing ROLE against each of the named binaries, e.g. \"ckb2021=v0.100,v0.101,rc\". Roles are \"ckb2019\", \"ckb2021\" or declared by cases"),
+                )
                 .arg(
                     Arg::with_name("cases")
                         .required(false)
@@ -108,32 +323,185 @@ fn clap_app() -> App<'static, 'static> {
                         .takes_value(true)
                         .multiple(true)
                         .value_name("CASE_NAME")
-                        .help("Only run specified cases. Run all cases if this parameter is not setting"),
+                        .help("Only run specified cases, glob patterns like \"RFC0035*\" are supported. Run all cases if this parameter is not setting"),
                 )
-        )
-        .subcommand(
-            SubCommand::with_name("generate-testdata")
-                .about("Run testdata generators")
+                .args(&selection_args())
                 .arg(
-                    Arg::with_name("ckb2019")
-                        // hide the help information about `--ckb2019`, we use built-in
-                        // ckb2019 binary, located in testdata/bin/
+                    Arg::with_name("jobs")
+                        .short("j")
+                        .long("jobs")
+                        .required(false)
+                        .takes_value(true)
+                        .value_name("N")
+                        .help("Run N cases concurrently in worker processes, each with a disjoint port range. Default is 1"),
+                )
+                .arg(
+                    Arg::with_name("port-range")
+                        // used by the worker processes of `--jobs`
                         .hidden(true)
+                        .long("port-range")
+                        .required(false)
+                        .takes_value(true)
+                        .value_name("START-END"),
+                )
+                .arg(
+                    Arg::with_name("result-file")
+                        // used by the worker processes of `--jobs`
+                        .hidden(true)
+                        .long("result-file")
+                        .required(false)
+                        .takes_value(true)
+                        .value_name("PATH"),
+                )
+                .arg(
+                    Arg::with_name("report-json")
+                        .long("report-json")
                         .required(false)
-                        .long("ckb2019")
                         .takes_value(true)
                         .value_name("PATH")
-                        .help("Path to ckb2019 executable"),
+                        .help("Write the case results and the ckb versions into PATH in JSON"),
                 )
                 .arg(
-                    Arg::with_name("ckb2021")
-                        .long("ckb2021")
+                    Arg::with_name("report-junit")
+                        .long("report-junit")
+                        .required(false)
                         .takes_value(true)
                         .value_name("PATH")
+                        .help("Write the case results and the ckb versions into PATH in JUnit XML"),
+                )
+                .arg(
+                    Arg::with_name("record-rpc")
+                        .long("record-rpc")
                         .required(false)
-                        .help("Path to ckb2021 executable"),
+                        .takes_value(true)
+                        .value_name("PATH")
+                        .help("Append every RPC request and response of the nodes into PATH in JSONL, for replaying via `replay-rpc`"),
+                )
+                .arg(
+                    Arg::with_name("artifacts-dir")
+                        .required(false)
+                        .long("artifacts-dir")
+                        .takes_value(true)
+                        .value_name("PATH")
+                        .help("Directory to store the logs, configs and RPC dumps of failed cases. \
+                               Default is $CKB_INTEGRATION_TEST_ARTIFACTS, or \"ckb-it-artifacts\" under the temp dir"),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("list")
+                .about("List test cases and their tags")
+                .arg(
+                    Arg::with_name("cases")
+                        .required(false)
+                        .long("cases")
+                        .takes_value(true)
+                        .multiple(true)
+                        .value_name("CASE_NAME")
+                        .help("Only list specified cases, glob patterns like \"RFC0035*\" are supported"),
                 )
+                .args(&selection_args())
+        )
+        .subcommand(
+            SubCommand::with_name("generate-testdata")
+                .about("Run testdata generators, skipping the ones whose databases are up to date")
+                .args(&testdata_args())
+                .arg(
+                    Arg::with_name("from-scratch")
+                        .long("from-scratch")
+                        .required(false)
+                        .help("Mine from zero instead of extending the existing databases"),
+                ),
         )
+        .subcommand(
+            SubCommand::with_name("verify-testdata")
+                .about("Check the testdata databases against their manifests, exit with 1 if any is stale")
+                .args(&testdata_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("replay-rpc")
+                .about("Serve the RPC responses of a node recorded via `run --record-rpc`, until killed")
+                .arg(
+                    Arg::with_name("records")
+                        .long("records")
+                        .required(true)
+                        .takes_value(true)
+                        .value_name("PATH")
+                        .help("The JSONL file written by `run --record-rpc`"),
+                )
+                .arg(
+                    Arg::with_name("node")
+                        .long("node")
+                        .required(true)
+                        .takes_value(true)
+                        .value_name("NODE_NAME")
+                        .help("Replay the node, e.g. \"node2021\""),
+                )
+                .arg(
+                    Arg::with_name("case")
+                        .long("case")
+                        .required(false)
+                        .takes_value(true)
+                        .value_name("CASE_NAME")
+                        .help("Only replay the records of the case"),
+                ),
+        )
+}
+
+// The arguments shared by `generate-testdata` and `verify-testdata`
+fn testdata_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("ckb2019")
+            // hide the help information about `--ckb2019`, we use built-in
+            // ckb2019 binary, located in testdata/bin/
+            .hidden(true)
+            .required(false)
+            .long("ckb2019")
+            .takes_value(true)
+            .value_name("PATH")
+            .help("Path to ckb2019 executable"),
+        Arg::with_name("ckb2021")
+            .long("ckb2021")
+            .takes_value(true)
+            .value_name("PATH")
+            .required(false)
+            .help("Path to ckb2021 executable"),
+        Arg::with_name("testdata")
+            .long("testdata")
+            .required(false)
+            .takes_value(true)
+            .multiple(true)
+            .value_name("TESTDATA_NAME")
+            .help("Only the specified testdata generators, e.g. \"Height100000TestData\". All but the slow ones if not set"),
+    ]
+}
+
+// The arguments selecting cases by tags, shared by `run` and `list`
+fn selection_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("tags")
+            .long("tags")
+            .required(false)
+            .takes_value(true)
+            .multiple(true)
+            .value_name("TAG")
+            .help(
+                "Only select the cases having any of the tags, e.g. p2p, hardfork, rfc0035, slow",
+            ),
+        Arg::with_name("exclude-tags")
+            .long("exclude-tags")
+            .required(false)
+            .takes_value(true)
+            .multiple(true)
+            .value_name("TAG")
+            .help("Exclude the cases having any of the tags"),
+        Arg::with_name("skip")
+            .long("skip")
+            .required(false)
+            .takes_value(true)
+            .multiple(true)
+            .value_name("CASE_NAME")
+            .help("Skip the cases matching any of the glob patterns and report them as skipped"),
+    ]
 }
 
 fn init_logger(clap_matches: &ArgMatches) -> ckb_logger_service::LoggerInitGuard {
@@ -159,12 +527,7 @@ fn init_ckb_binaries(matches: &ArgMatches) {
         if let Some(ckb2019_str) = matches.value_of("ckb2019") {
             PathBuf::from(ckb2019_str)
         } else {
-            // Use default ckb_v0.43.2 binary according to the running system
-            match os_info::get().os_type() {
-                os_info::Type::Macos => PathBuf::from("testdata/bin/ckb_v0.43.2-macOS"),
-                os_info::Type::Windows => PathBuf::from("testdata/bin/ckb_v0.43.2-Windows"),
-                _ => PathBuf::from("testdata/bin/ckb_v0.43.2-Linux"),
-            }
+            default_ckb2019()
         }
     };
     let ckb2021 = value_t_or_exit!(matches, "ckb2021", PathBuf);
@@ -178,6 +541,76 @@ fn init_ckb_binaries(matches: &ArgMatches) {
     *CKB2021.write().unwrap() = absolutize(ckb2021);
 }
 
+// Use default ckb_v0.43.2 binary according to the running system
+fn default_ckb2019() -> PathBuf {
+    match os_info::get().os_type() {
+        os_info::Type::Macos => PathBuf::from("testdata/bin/ckb_v0.43.2-macOS"),
+        os_info::Type::Windows => PathBuf::from("testdata/bin/ckb_v0.43.2-Windows"),
+        _ => PathBuf::from("testdata/bin/ckb_v0.43.2-Linux"),
+    }
+}
+
+fn init_binary_matrix(matches: &ArgMatches) -> binaries::BinaryMatrix {
+    let mut matrix = binaries::BinaryMatrix::default();
+    if let Some(ckb2019) = matches.value_of("ckb2019") {
+        matrix.add_binary(binaries::CkbBinary::new(binaries::ROLE_CKB2019, ckb2019));
+    } else {
+        let ckb2019 = default_ckb2019();
+        if ckb2019.is_file() {
+            matrix.add_binary(binaries::CkbBinary::new(binaries::ROLE_CKB2019, ckb2019));
+        }
+    }
+    if let Some(ckb2021) = matches.value_of("ckb2021") {
+        matrix.add_binary(binaries::CkbBinary::new(binaries::ROLE_CKB2021, ckb2021));
+    }
+    if let Some(config) = matches.value_of("binaries-config") {
+        matrix.load_config(Path::new(config));
+    }
+    for binary in matches.values_of("binary").into_iter().flatten() {
+        let mut parts = binary.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(name), Some(path)) => matrix.add_binary(binaries::CkbBinary::new(name, path)),
+            _ => panic!("invalid --binary \"{}\", expect NAME=PATH", binary),
+        }
+    }
+    for candidates in matches.values_of("matrix").into_iter().flatten() {
+        let mut parts = candidates.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(role), Some(names)) => {
+                let names = names.split(',').map(ToString::to_string).collect();
+                matrix.set_candidates(role, names);
+            }
+            _ => panic!(
+                "invalid --matrix \"{}\", expect ROLE=NAME[,NAME]",
+                candidates
+            ),
+        }
+    }
+    matrix
+}
+
+fn init_artifacts_dir(matches: &ArgMatches) {
+    let artifacts_dir = if let Some(artifacts_dir) = matches.value_of("artifacts-dir") {
+        PathBuf::from(artifacts_dir)
+    } else if let Ok(artifacts_dir) = env::var("CKB_INTEGRATION_TEST_ARTIFACTS") {
+        PathBuf::from(artifacts_dir)
+    } else {
+        env::temp_dir().join("ckb-it-artifacts")
+    };
+    *ARTIFACTS_DIR.write().unwrap() = absolutize(artifacts_dir);
+}
+
+fn init_port_range(port_range: &str) {
+    let parse = |port: Option<&str>| {
+        port.and_then(|port| port.parse::<u16>().ok())
+            .unwrap_or_else(|| panic!("invalid --port-range \"{}\"", port_range))
+    };
+    let mut ports = port_range.splitn(2, '-');
+    let start = parse(ports.next());
+    let end = parse(ports.next());
+    ckb_testkit::util::set_port_range(start, end);
+}
+
 fn absolutize(path: PathBuf) -> PathBuf {
     if path.is_relative() {
         env::current_dir()
@@ -0,0 +1,165 @@
+use crate::binaries::BinaryMatrix;
+use crate::case::{CaseResult, CaseRun};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+const PORT_RANGE_START: u16 = 9000;
+const PORT_RANGE_END: u16 = 65000;
+
+struct Worker {
+    label: String,
+    child: Child,
+    slot: usize,
+    start_time: Instant,
+    result_file: PathBuf,
+    log_file: PathBuf,
+}
+
+/// Run cases concurrently, each in a worker process which runs `ckb-integration-test run
+/// --cases <case_name>` against the binary combination of the case run. At most `jobs` workers
+/// run at the same time, each with a disjoint port range, and the output of each worker goes to
+/// "<artifacts_dir>/logs/<label>.log".
+///
+/// `log_args` are the global arguments passed through to workers, e.g. `--debug`. The workers
+/// append their RPC records into `record_rpc` if set.
+pub fn run_cases_in_parallel(
+    case_runs: Vec<CaseRun>,
+    matrix: &BinaryMatrix,
+    jobs: usize,
+    log_args: &[&str],
+    record_rpc: Option<&Path>,
+) -> Vec<CaseResult> {
+    assert!(
+        jobs > 0 && jobs <= 256,
+        "--jobs should be in [1, 256], but got {}",
+        jobs
+    );
+    let total = case_runs.len();
+    let ports_per_worker = (PORT_RANGE_END - PORT_RANGE_START) / jobs as u16;
+    let artifacts_dir = crate::ARTIFACTS_DIR.read().unwrap().clone();
+    let logs_dir = artifacts_dir.join("logs");
+    fs::create_dir_all(&logs_dir).unwrap_or_else(|err| {
+        panic!(
+            "failed to create dir \"{}\", error: {}",
+            logs_dir.display(),
+            err
+        )
+    });
+    let current_exe = env::current_exe()
+        .unwrap_or_else(|err| panic!("failed to get current executable, error: {}", err));
+
+    let labels = case_runs
+        .iter()
+        .map(|case_run| case_run.label.clone())
+        .collect::<Vec<_>>();
+    let mut pending = case_runs.into_iter();
+    let mut free_slots = (0..jobs).rev().collect::<Vec<_>>();
+    let mut workers: Vec<Worker> = Vec::new();
+    let mut results: Vec<CaseResult> = Vec::new();
+    loop {
+        while !free_slots.is_empty() {
+            let case_run = match pending.next() {
+                Some(case_run) => case_run,
+                None => break,
+            };
+            let slot = free_slots.pop().expect("checked non-empty");
+            let port_start = PORT_RANGE_START + slot as u16 * ports_per_worker;
+            let port_range = format!("{}-{}", port_start, port_start + ports_per_worker);
+            let result_file = logs_dir.join(format!("{}.result", case_run.label));
+            let log_file = logs_dir.join(format!("{}.log", case_run.label));
+            let _ = fs::remove_file(&result_file);
+            let log = fs::File::create(&log_file).unwrap_or_else(|err| {
+                panic!("failed to create {}, error: {}", log_file.display(), err)
+            });
+            let log_clone = log
+                .try_clone()
+                .unwrap_or_else(|err| panic!("failed to clone file handle, error: {}", err));
+            let mut command = Command::new(&current_exe);
+            command
+                .args(log_args)
+                .arg("run")
+                .args(matrix.worker_args(&case_run.bindings))
+                .arg("--artifacts-dir")
+                .arg(&artifacts_dir)
+                .arg("--port-range")
+                .arg(&port_range)
+                .arg("--result-file")
+                .arg(&result_file)
+                .arg("--cases")
+                .arg(&case_run.case_name);
+            if let Some(record_rpc) = record_rpc {
+                command.arg("--record-rpc").arg(record_rpc);
+            }
+            command.stdin(Stdio::null()).stdout(log).stderr(log_clone);
+            let child = command.spawn().unwrap_or_else(|err| {
+                panic!(
+                    "failed to spawn worker for {}, error: {}",
+                    case_run.label, err
+                )
+            });
+            ckb_testkit::info!(
+                "[{}/{}] START {}, ports: {}, log: {}",
+                results.len() + workers.len() + 1,
+                total,
+                case_run.label,
+                port_range,
+                log_file.display()
+            );
+            workers.push(Worker {
+                label: case_run.label,
+                child,
+                slot,
+                start_time: Instant::now(),
+                result_file,
+                log_file,
+            });
+        }
+        if workers.is_empty() {
+            break;
+        }
+
+        sleep(Duration::from_millis(200));
+        let mut running = Vec::new();
+        for mut worker in workers.drain(..) {
+            let status = match worker.child.try_wait() {
+                Ok(None) => {
+                    running.push(worker);
+                    continue;
+                }
+                Ok(Some(status)) => status.to_string(),
+                Err(err) => {
+                    let _ = worker.child.kill();
+                    let _ = worker.child.wait();
+                    err.to_string()
+                }
+            };
+            let result = CaseResult::read_from(&worker.result_file).unwrap_or_else(|| {
+                CaseResult::failed(
+                    &worker.label,
+                    worker.start_time.elapsed(),
+                    format!("worker exited without result, {}", status),
+                )
+            });
+            let _ = fs::remove_file(&worker.result_file);
+            ckb_testkit::info!(
+                "[{}/{}] {} {} in {:.1}s, log: {}",
+                results.len() + 1,
+                total,
+                result.status,
+                result.case_name,
+                result.duration.as_secs_f64(),
+                worker.log_file.display()
+            );
+            free_slots.push(worker.slot);
+            results.push(result);
+        }
+        workers = running;
+    }
+    // Report in the original order rather than the completion order
+    results.sort_by_key(|result| labels.iter().position(|label| label == &result.case_name));
+    results
+}
@@ -1,3 +1,3 @@
-pub use crate::case::{Case, CaseOptions};
-pub use crate::{CKB2019, CKB2021};
+pub use crate::binaries::{ckb2019, ckb2021};
+pub use crate::case::{Case, CaseOptions, TableCase, TableRow};
 pub use ckb_testkit::{Node, NodeOptions, Nodes};
@@ -0,0 +1,244 @@
+use crate::binaries::CkbBinary;
+use crate::case::{CaseResult, CaseStatus};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+const SUITE_NAME: &str = "ckb-integration-test";
+
+/// Write the case results into `path` in JSON, along with the versions of the ckb binaries.
+pub fn write_json_report(path: &Path, results: &[CaseResult], binaries: &[CkbBinary]) {
+    let report = serde_json::json!({
+        "versions": versions(binaries),
+        "cases": results.iter().map(CaseResult::to_json).collect::<Vec<_>>(),
+    });
+    let content = serde_json::to_string_pretty(&report).expect("serialize report");
+    write_report(path, &content);
+}
+
+/// Write the case results into `path` in JUnit XML, which is recognized by most CI systems.
+/// The versions of the ckb binaries are written as the properties of the test suite.
+pub fn write_junit_report(path: &Path, results: &[CaseResult], binaries: &[CkbBinary]) {
+    let count = |status: CaseStatus| {
+        results
+            .iter()
+            .filter(|result| result.status == status)
+            .count()
+    };
+    let total_time = results
+        .iter()
+        .map(|result| result.duration.as_secs_f64())
+        .sum::<f64>();
+
+    let mut xml = String::new();
+    writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+        xml,
+        r#"<testsuites name="{name}" tests="{tests}" failures="{failures}" skipped="{skipped}" time="{time:.3}">"#,
+        name = SUITE_NAME,
+        tests = results.len(),
+        failures = count(CaseStatus::Failed) + count(CaseStatus::TimedOut),
+        skipped = count(CaseStatus::Skipped),
+        time = total_time,
+    )
+    .unwrap();
+    writeln!(
+        xml,
+        r#"  <testsuite name="{name}" tests="{tests}" failures="{failures}" skipped="{skipped}" time="{time:.3}">"#,
+        name = SUITE_NAME,
+        tests = results.len(),
+        failures = count(CaseStatus::Failed) + count(CaseStatus::TimedOut),
+        skipped = count(CaseStatus::Skipped),
+        time = total_time,
+    )
+    .unwrap();
+    writeln!(xml, "    <properties>").unwrap();
+    for (name, version) in versions(binaries) {
+        writeln!(
+            xml,
+            r#"      <property name="{}" value="{}"/>"#,
+            escape(&name),
+            escape(version.as_str().unwrap_or_default())
+        )
+        .unwrap();
+    }
+    writeln!(xml, "    </properties>").unwrap();
+    for result in results {
+        writeln!(
+            xml,
+            r#"    <testcase name="{}" classname="{}" time="{:.3}">"#,
+            escape(&result.case_name),
+            SUITE_NAME,
+            result.duration.as_secs_f64()
+        )
+        .unwrap();
+        match result.status {
+            CaseStatus::Passed => {}
+            CaseStatus::Failed | CaseStatus::TimedOut => {
+                let message = result.message.as_deref().unwrap_or("");
+                writeln!(
+                    xml,
+                    r#"      <failure message="{}">{}</failure>"#,
+                    escape(message.lines().next().unwrap_or("")),
+                    escape(message)
+                )
+                .unwrap();
+            }
+            CaseStatus::Skipped => writeln!(xml, "      <skipped/>").unwrap(),
+        }
+        let mut system_out = String::new();
+        for node_log in result.node_logs.iter() {
+            writeln!(system_out, "node log: {}", node_log.display()).unwrap();
+        }
+        for (role, binary) in result.bindings.iter() {
+            writeln!(system_out, "{}: {} ({})", role, binary.name, binary.version).unwrap();
+        }
+        if let Some(artifacts) = result.artifacts.as_ref() {
+            writeln!(system_out, "artifacts: {}", artifacts.display()).unwrap();
+        }
+        if !system_out.is_empty() {
+            writeln!(
+                xml,
+                "      <system-out>{}</system-out>",
+                escape(&system_out)
+            )
+            .unwrap();
+        }
+        writeln!(xml, "    </testcase>").unwrap();
+    }
+    writeln!(xml, "  </testsuite>").unwrap();
+    writeln!(xml, "</testsuites>").unwrap();
+    write_report(path, &xml);
+}
+
+// binary name => version
+fn versions(binaries: &[CkbBinary]) -> serde_json::Map<String, serde_json::Value> {
+    binaries
+        .iter()
+        .map(|binary| (binary.name.clone(), binary.version.clone().into()))
+        .collect()
+}
+
+fn write_report(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap_or_else(|err| {
+            panic!(
+                "failed to create dir \"{}\", error: {}",
+                parent.display(),
+                err
+            )
+        });
+    }
+    fs::write(path, content)
+        .unwrap_or_else(|err| panic!("failed to write {}, error: {}", path.display(), err));
+}
+
+// Escape the XML special characters. The control characters, which are illegal in XML 1.0
+// except tab and newlines, are removed, along with the ANSI escape sequences in panic messages
+// and node output, e.g. "\x1b[31m".
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            '\x1b' => {
+                // Skip the CSI sequence up to its final byte in '@'..='~'
+                if chars.peek() == Some(&'[') {
+                    chars.next();
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+            }
+            c if (c as u32) < 0x20 => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, write_junit_report};
+    use crate::binaries::CkbBinary;
+    use crate::case::CaseResult;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape(r#"<a href="x">Tom & Jerry's</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;"
+        );
+        assert_eq!(
+            escape("\x1b[31merror\x1b[0m: failed\x07\x00"),
+            "error: failed"
+        );
+        assert_eq!(escape("a\tb\r\nc"), "a\tb\r\nc");
+        assert_eq!(escape("tip \u{2192} 13"), "tip \u{2192} 13");
+    }
+
+    #[test]
+    fn test_write_junit_report() {
+        let binary = CkbBinary {
+            name: "v0.101".to_string(),
+            path: PathBuf::from("/path/to/ckb"),
+            version: "ckb 0.101.0 (abc 2021-10-01)".to_string(),
+        };
+        let mut failed = CaseResult::failed(
+            "RFC0035V2021Connection",
+            Duration::from_millis(2500),
+            "\x1b[31massertion failed\x1b[0m: tip < 13\nbacktrace".to_string(),
+        );
+        failed.node_logs = vec![PathBuf::from("/tmp/node2021/data/logs/run.log")];
+        failed.bindings = vec![("ckb2021".to_string(), binary.clone())];
+        let results = vec![
+            CaseResult::passed("BasicNetworking", Duration::from_millis(1000)),
+            failed,
+            CaseResult::timed_out(
+                "RFC0036",
+                Duration::from_secs(600),
+                Duration::from_secs(600),
+            ),
+            CaseResult::skipped("RFC0028Chained"),
+        ];
+        let path = ::std::env::temp_dir().join(format!(
+            "ckb-integration-test-junit-{}.xml",
+            ::std::process::id()
+        ));
+        write_junit_report(&path, &results, &[binary]);
+        let xml = fs::read_to_string(&path)
+            .unwrap_or_else(|err| panic!("failed to read {}, error: {}", path.display(), err));
+        let _ = fs::remove_file(&path);
+
+        assert!(
+            xml.chars()
+                .all(|c| c as u32 >= 0x20 || c == '\t' || c == '\n' || c == '\r'),
+            "{}",
+            xml
+        );
+        assert!(xml.contains(r#"tests="4" failures="2" skipped="1" time="603.500""#));
+        assert!(xml.contains(r#"<property name="v0.101" value="ckb 0.101.0 (abc 2021-10-01)"/>"#));
+        assert!(xml.contains(
+            r#"<failure message="assertion failed: tip &lt; 13">assertion failed: tip &lt; 13
+backtrace</failure>"#
+        ));
+        assert!(xml.contains("timed out after 600s"));
+        assert!(xml.contains("<skipped/>"));
+        assert!(xml.contains("node log: /tmp/node2021/data/logs/run.log"));
+        assert!(xml.contains("ckb2021: v0.101 (ckb 0.101.0 (abc 2021-10-01))"));
+        // Every testcase is closed
+        assert_eq!(xml.matches("<testcase ").count(), 4);
+        assert_eq!(xml.matches("</testcase>").count(), 4);
+    }
+}
@@ -1,44 +1,32 @@
-use crate::testdata::{dump_testdata, Testdata};
-use crate::{CKB2019, CKB2021};
-use ckb_testkit::{Node, NodeOptions};
+use crate::testdata::{dump_testdata, Testdata, TestdataDb};
+use ckb_testkit::Node;
 
 pub struct Epoch2TestData;
 
 impl Testdata for Epoch2TestData {
+    fn dbs(&self) -> Vec<TestdataDb> {
+        vec![
+            TestdataDb::ckb2019("Epoch2V1TestData"),
+            TestdataDb::ckb2021("Epoch2V2TestData"),
+        ]
+    }
+
     fn generate(&self) {
-        let mut node2019 = {
-            let node_options = NodeOptions {
-                node_name: String::from("node2019"),
-                ckb_binary: CKB2019.read().unwrap().clone(),
-                initial_database: "testdata/db/empty",
-                chain_spec: "testdata/spec/ckb2019",
-                app_config: "testdata/config/ckb2019",
-            };
-            Node::init("Epoch2V1TestData", node_options, false)
-        };
-        let mut node2021 = {
-            let node_options = NodeOptions {
-                node_name: String::from("node2021"),
-                ckb_binary: CKB2021.read().unwrap().clone(),
-                initial_database: "testdata/db/empty",
-                chain_spec: "testdata/spec/ckb2021",
-                app_config: "testdata/config/ckb2021",
-            };
-            Node::init("Epoch2V2TestData", node_options, true)
-        };
+        let dbs = self.dbs();
+        let (db2019, db2021) = (&dbs[0], &dbs[1]);
+        let mut node2019 = Node::init("Epoch2V1TestData", db2019.node_options("node2019"), false);
+        let mut node2021 = Node::init("Epoch2V2TestData", db2021.node_options("node2021"), true);
 
         node2019.start();
         node2021.start();
-        loop {
+        while node2019.get_tip_block().epoch().number() < 2 {
             node2019.mine(1);
-            let tip = node2019.get_tip_block();
-            node2021.submit_block(&tip);
-            if tip.epoch().number() >= 2 {
-                break;
-            }
         }
+        node2021
+            .pull_node(&node2019)
+            .expect("node2021 should accept the blocks of node2019");
 
-        dump_testdata(node2019, "Epoch2V1TestData");
-        dump_testdata(node2021, "Epoch2V2TestData");
+        dump_testdata(node2019, &db2019.db_name, self.testdata_name());
+        dump_testdata(node2021, &db2021.db_name, self.testdata_name());
     }
 }
@@ -1,24 +1,35 @@
-use crate::testdata::{dump_testdata, Testdata};
-use crate::CKB2021;
-use ckb_testkit::{Node, NodeOptions};
+use crate::testdata::{dump_testdata, Testdata, TestdataDb};
+use ckb_testkit::ckb_types::core::BlockNumber;
+use ckb_testkit::Node;
+
+const TIP_NUMBER: BlockNumber = 100000;
+// Dump the database every so many blocks, so that an interrupted generating resumes from there
+const CHECKPOINT_INTERVAL: BlockNumber = 10000;
 
 pub struct Height100000TestData;
 
 impl Testdata for Height100000TestData {
-    fn generate(&self) {
-        let mut node2021 = {
-            let node_options = NodeOptions {
-                node_name: String::from("node2021"),
-                ckb_binary: CKB2021.read().unwrap().clone(),
-                initial_database: "testdata/db/empty",
-                chain_spec: "testdata/spec/ckb2021",
-                app_config: "testdata/config/ckb2021",
-            };
-            Node::init("Height1000002V2TestData", node_options, true)
-        };
+    fn dbs(&self) -> Vec<TestdataDb> {
+        vec![TestdataDb::ckb2021(self.testdata_name()).tip_number(TIP_NUMBER)]
+    }
 
-        node2021.start();
-        node2021.mine_to(100000);
-        dump_testdata(node2021, "Height1000002V2TestData");
+    fn is_slow(&self) -> bool {
+        true
+    }
+
+    fn generate(&self) {
+        let db = TestdataDb::ckb2021(self.testdata_name());
+        loop {
+            let node_options = db.node_options("node2021");
+            let mut node2021 = Node::init(self.testdata_name(), node_options, true);
+            node2021.start();
+            let tip_number = node2021.get_tip_block_number();
+            let checkpoint = TIP_NUMBER.min(tip_number + CHECKPOINT_INTERVAL);
+            node2021.mine_to(checkpoint);
+            dump_testdata(node2021, &db.db_name, self.testdata_name());
+            if checkpoint >= TIP_NUMBER {
+                break;
+            }
+        }
     }
 }
@@ -1,21 +1,19 @@
-use crate::testdata::{dump_testdata, Testdata};
-use crate::CKB2019;
-use ckb_testkit::{Node, NodeOptions};
+use crate::testdata::{dump_testdata, Testdata, TestdataDb};
+use ckb_testkit::Node;
 
 pub struct Height13TestData;
 
 impl Testdata for Height13TestData {
+    fn dbs(&self) -> Vec<TestdataDb> {
+        vec![TestdataDb::ckb2019(self.testdata_name()).tip_number(13)]
+    }
+
     fn generate(&self) {
-        let node_options = NodeOptions {
-            node_name: String::from("node2019"),
-            ckb_binary: CKB2019.read().unwrap().clone(),
-            initial_database: "testdata/db/empty",
-            chain_spec: "testdata/spec/ckb2019",
-            app_config: "testdata/config/ckb2019",
-        };
+        let db = TestdataDb::ckb2019(self.testdata_name());
+        let node_options = db.node_options("node2019");
         let mut node = Node::init(self.testdata_name(), node_options, false);
         node.start();
-        node.mine(13);
-        dump_testdata(node, self.testdata_name());
+        node.mine_to(13);
+        dump_testdata(node, &db.db_name, self.testdata_name());
     }
 }
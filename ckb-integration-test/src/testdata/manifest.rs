@@ -0,0 +1,115 @@
+use crate::testdata::TESTDATA_DIR;
+use ckb_testkit::ckb_types::core::BlockNumber;
+use ckb_testkit::{Node, SnapshotKey};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Record of how a database under "testdata/db/" was generated, stored next to the database as
+/// "testdata/db/<db_name>.manifest.json".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestdataManifest {
+    pub db_name: String,
+    /// The name of the `Testdata` which generated the database
+    pub generator: String,
+    /// The version of the binary which generated the database, e.g. "0.100.0"
+    pub binary_version: String,
+    /// See `SnapshotKey::spec_hash`
+    pub spec_hash: String,
+    pub tip_number: BlockNumber,
+    pub tip_hash: String,
+}
+
+impl TestdataManifest {
+    /// Describe the database of the running node.
+    pub fn from_node(node: &Node, db_name: &str, generator: &str) -> Self {
+        let tip_block = node.get_tip_block();
+        let key = SnapshotKey::new(
+            &node.rpc_client().local_node_info().version,
+            &node.working_dir().join("integration.toml"),
+            tip_block.number(),
+        );
+        Self {
+            db_name: db_name.to_string(),
+            generator: generator.to_string(),
+            binary_version: key.version,
+            spec_hash: key.spec_hash,
+            tip_number: tip_block.number(),
+            tip_hash: format!("{:#x}", tip_block.hash()),
+        }
+    }
+
+    /// "testdata/db/<db_name>.manifest.json"
+    pub fn path(db_name: &str) -> PathBuf {
+        PathBuf::from(TESTDATA_DIR)
+            .join("db")
+            .join(format!("{}.manifest.json", db_name))
+    }
+
+    /// Return the manifest of the database, `None` if not exists. Return an error if the
+    /// manifest is malformed, e.g. missing a field.
+    pub fn load(db_name: &str) -> Result<Option<Self>, String> {
+        let path = Self::path(db_name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|err| format!("failed to read {}, error: {}", path.display(), err))?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|err| format!("invalid manifest {}, error: {}", path.display(), err))
+    }
+
+    pub fn write(&self) {
+        let path = Self::path(&self.db_name);
+        let content = serde_json::to_string_pretty(self).expect("serialize manifest");
+        fs::write(&path, content)
+            .unwrap_or_else(|err| panic!("failed to write {}, error: {}", path.display(), err));
+    }
+}
+
+/// The spec hash of the chain spec file, the same as `SnapshotKey::spec_hash`.
+pub fn spec_hash(spec_file: &Path) -> String {
+    SnapshotKey::new("", spec_file, 0).spec_hash
+}
+
+/// The version of the binary, the same as `SnapshotKey::version`, e.g. "0.100.0".
+pub fn binary_version(ckb_binary: &Path) -> String {
+    crate::binaries::binary_version(ckb_binary)
+        .trim_start_matches("ckb ")
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TestdataManifest;
+
+    #[test]
+    fn test_json() {
+        let manifest = TestdataManifest {
+            db_name: "Epoch2V2TestData".to_string(),
+            generator: "Epoch2V2TestData".to_string(),
+            binary_version: "0.100.0".to_string(),
+            spec_hash: "0x01".to_string(),
+            tip_number: 2000,
+            tip_hash: "0x02".to_string(),
+        };
+        let json = serde_json::to_string_pretty(&manifest).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TestdataManifest>(&json).unwrap(),
+            manifest
+        );
+
+        let mut value = serde_json::to_value(&manifest).unwrap();
+        value.as_object_mut().unwrap().remove("tip_hash");
+        let err = serde_json::from_value::<TestdataManifest>(value).unwrap_err();
+        assert!(
+            err.to_string().contains("missing field `tip_hash`"),
+            "{}",
+            err
+        );
+    }
+}
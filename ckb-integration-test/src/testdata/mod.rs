@@ -1,38 +1,253 @@
-use ckb_testkit::Node;
+use ckb_testkit::ckb_types::core::BlockNumber;
+use ckb_testkit::{Node, NodeOptions};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub const TESTDATA_DIR: &str = "./testdata";
 
 mod epoch2;
 mod height100000;
 mod height13;
+mod manifest;
+
+pub use manifest::TestdataManifest;
 
 pub trait Testdata {
     fn testdata_name(&self) -> &str {
         testdata_name(self)
     }
+
+    /// The databases generated into "testdata/db/".
+    fn dbs(&self) -> Vec<TestdataDb>;
+
+    /// Slow generators only run when selected by name.
+    fn is_slow(&self) -> bool {
+        false
+    }
+
+    /// Generate the databases. The nodes start from the existing databases if they are still
+    /// reusable, see `TestdataDb::node_options`.
     fn generate(&self);
 }
 
+/// A database generated by a `Testdata`, located at "testdata/db/<db_name>".
+#[derive(Debug, Clone)]
+pub struct TestdataDb {
+    pub db_name: String,
+    pub is_ckb2021: bool,
+    /// The tip number the database should reach, if known before generating
+    pub tip_number: Option<BlockNumber>,
+}
+
+impl TestdataDb {
+    pub fn ckb2019(db_name: &str) -> Self {
+        Self {
+            db_name: db_name.to_string(),
+            is_ckb2021: false,
+            tip_number: None,
+        }
+    }
+
+    pub fn ckb2021(db_name: &str) -> Self {
+        Self {
+            db_name: db_name.to_string(),
+            is_ckb2021: true,
+            tip_number: None,
+        }
+    }
+
+    pub fn tip_number(mut self, tip_number: BlockNumber) -> Self {
+        self.tip_number = Some(tip_number);
+        self
+    }
+
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(TESTDATA_DIR).join("db").join(&self.db_name)
+    }
+
+    /// The current chain spec file, "testdata/spec/<ckb2019|ckb2021>/integration.toml"
+    pub fn spec_file(&self) -> PathBuf {
+        let spec = if self.is_ckb2021 {
+            "ckb2021"
+        } else {
+            "ckb2019"
+        };
+        PathBuf::from(TESTDATA_DIR)
+            .join("spec")
+            .join(spec)
+            .join("integration.toml")
+    }
+
+    /// The current binary, `ckb2019()` or `ckb2021()`
+    pub fn ckb_binary(&self) -> PathBuf {
+        if self.is_ckb2021 {
+            crate::binaries::ckb2021()
+        } else {
+            crate::binaries::ckb2019()
+        }
+    }
+
+    /// Node options for generating the database. The node starts from the existing database
+    /// if it was generated on the current chain spec by the current binary, so that generating
+    /// extends it instead of mining from zero.
+    pub fn node_options(&self, node_name: &str) -> NodeOptions {
+        let builder = if self.is_ckb2021 {
+            NodeOptions::ckb2021(node_name)
+        } else {
+            NodeOptions::ckb2019(node_name)
+        };
+        let builder = builder.ckb_binary(self.ckb_binary());
+        // A malformed manifest is regenerated along with the database
+        let reusable = TestdataManifest::load(&self.db_name)
+            .ok()
+            .flatten()
+            .map(|manifest| {
+                self.path().is_dir()
+                    && manifest.spec_hash == manifest::spec_hash(&self.spec_file())
+                    && manifest.binary_version == manifest::binary_version(&self.ckb_binary())
+            })
+            .unwrap_or(false);
+        if reusable {
+            ckb_testkit::info!("extend the existing testdata {}", self.db_name);
+            builder.initial_database(self.path()).build()
+        } else {
+            builder.build()
+        }
+    }
+
+    /// Check the database against its manifest. Return the reason if it is stale, i.e. not
+    /// generated by `generator` on the current chain spec and binary, or not reaching
+    /// `tip_number`.
+    pub fn verify(&self, generator: &str) -> Result<TestdataManifest, String> {
+        if !self.path().is_dir() {
+            return Err(format!("{} not found", self.path().display()));
+        }
+        let manifest = TestdataManifest::load(&self.db_name)?.ok_or_else(|| {
+            format!(
+                "{} not found",
+                TestdataManifest::path(&self.db_name).display()
+            )
+        })?;
+        if manifest.generator != generator {
+            return Err(format!(
+                "generated by {}, expected {}",
+                manifest.generator, generator
+            ));
+        }
+        let spec_hash = manifest::spec_hash(&self.spec_file());
+        if manifest.spec_hash != spec_hash {
+            return Err(format!(
+                "generated on spec {}, but {} is {}",
+                manifest.spec_hash,
+                self.spec_file().display(),
+                spec_hash
+            ));
+        }
+        let binary_version = manifest::binary_version(&self.ckb_binary());
+        if manifest.binary_version != binary_version {
+            return Err(format!(
+                "generated by ckb {}, but the current binary is ckb {}",
+                manifest.binary_version, binary_version
+            ));
+        }
+        if let Some(tip_number) = self.tip_number {
+            if manifest.tip_number < tip_number {
+                return Err(format!(
+                    "tip number is {}, expected {}",
+                    manifest.tip_number, tip_number
+                ));
+            }
+        }
+        Ok(manifest)
+    }
+}
+
 pub fn all_testdata_generators() -> Vec<Box<dyn Testdata>> {
     vec![
         Box::new(height13::Height13TestData),
         Box::new(epoch2::Epoch2TestData),
-        // Box::new(height100000::Height100000TestData),
+        Box::new(height100000::Height100000TestData),
     ]
 }
 
+/// Return the generators named `names`, or all the non-slow generators if `names` is empty.
+pub fn select_testdata_generators(names: &[&str]) -> Vec<Box<dyn Testdata>> {
+    if names.is_empty() {
+        return all_testdata_generators()
+            .into_iter()
+            .filter(|testdata| !testdata.is_slow())
+            .collect();
+    }
+    names
+        .iter()
+        .map(|name| {
+            all_testdata_generators()
+                .into_iter()
+                .find(|testdata| testdata.testdata_name() == *name)
+                .unwrap_or_else(|| panic!("unknown testdata \"{}\"", name))
+        })
+        .collect()
+}
+
+/// Check the manifest of `node_options.initial_database` if it is a generated database, panic
+/// if it was generated on another chain spec than the node's, and warn if it was generated by
+/// another binary version than the node's.
+///
+/// Databases without manifest are not checked.
+pub fn check_initial_database(node_options: &NodeOptions) {
+    if node_options.chain_spec_builder.is_some() {
+        return;
+    }
+    let testdata_db_dir = Path::new(TESTDATA_DIR).join("db");
+    let db_name = match node_options
+        .initial_database
+        .strip_prefix(&testdata_db_dir)
+        .or_else(|_| node_options.initial_database.strip_prefix("testdata/db"))
+    {
+        Ok(db_name) => db_name.to_string_lossy().to_string(),
+        Err(_) => return,
+    };
+    let manifest = TestdataManifest::load(&db_name).unwrap_or_else(|err| panic!("{}", err));
+    if let Some(manifest) = manifest {
+        let spec_file = node_options.chain_spec.join("integration.toml");
+        let spec_hash = manifest::spec_hash(&spec_file);
+        if manifest.spec_hash != spec_hash {
+            panic!(
+                "testdata {} is stale, it was generated on spec {} but {} is {}, please run \
+                 `generate-testdata --testdata {}`",
+                db_name,
+                manifest.spec_hash,
+                spec_file.display(),
+                spec_hash,
+                manifest.generator
+            );
+        }
+        let binary_version = manifest::binary_version(&node_options.ckb_binary);
+        if manifest.binary_version != binary_version {
+            ckb_testkit::warn!(
+                "testdata {} was generated by ckb {}, but the node runs ckb {}, run \
+                 `generate-testdata --testdata {}` if it is stale",
+                db_name,
+                manifest.binary_version,
+                binary_version,
+                manifest.generator
+            );
+        }
+    }
+}
+
 fn testdata_name<T: ?Sized>(_: &T) -> &str {
     let type_name = ::std::any::type_name::<T>();
     type_name.split_terminator("::").last().unwrap()
 }
 
-fn dump_testdata(mut node: Node, testdata_name: &str) {
+/// Stop the node and move its database into "testdata/db/<db_name>", along with its manifest.
+fn dump_testdata(mut node: Node, db_name: &str, generator: &str) {
+    let manifest = TestdataManifest::from_node(&node, db_name, generator);
     let testdata_dir = PathBuf::from(TESTDATA_DIR);
     let working_dir = node.working_dir();
     let source_dir = format!("{}/data/db", working_dir.display());
-    let target_dir = format!("{}/db/{}", testdata_dir.display(), testdata_name);
+    let target_dir = format!("{}/db/{}", testdata_dir.display(), db_name);
     node.stop();
 
     if !testdata_dir.exists() {
@@ -55,4 +270,5 @@ fn dump_testdata(mut node: Node, testdata_name: &str) {
             source_dir, target_dir, err
         )
     });
+    manifest.write();
 }
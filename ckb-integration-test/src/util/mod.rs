@@ -4,7 +4,11 @@ pub mod shortcuts;
 pub use shortcuts::{v0_100, v0_43};
 
 use ckb_testkit::ckb_types::core::{BlockNumber, EpochNumber};
-use ckb_testkit::Node;
+use ckb_testkit::{Node, NodeOptionsBuilder};
+
+/// The data hash of the genesis always-success cell
+pub const ALWAYS_SUCCESS_DATA_HASH: &str =
+    "0x28e83a1277d48add8e72fadaa9248559e1b632bab2bd60b27955ebc4c03800a5";
 
 pub fn estimate_start_number_of_epoch(node: &Node, epoch_number: EpochNumber) -> BlockNumber {
     assert!(node.consensus().permanent_difficulty_in_dummy);
@@ -14,3 +18,11 @@ pub fn estimate_start_number_of_epoch(node: &Node, epoch_number: EpochNumber) ->
         .expect("genesis epoch should exist");
     genesis_epoch.length.value() * epoch_number
 }
+
+/// Config the block assembler to use the always-success script with `hash_type = "data"`, so
+/// that the scripts of cellbase outputs are VM-determined.
+pub fn block_assembler_hash_type_is_data(builder: NodeOptionsBuilder) -> NodeOptionsBuilder {
+    builder
+        .app_config_override("block_assembler.code_hash", ALWAYS_SUCCESS_DATA_HASH)
+        .app_config_override("block_assembler.hash_type", "data")
+}
@@ -1,29 +1,11 @@
-use crate::prelude::*;
-use lazy_static::lazy_static;
-
-lazy_static! {
-    static ref V0_43: String = {
-        let stdout = ::std::process::Command::new(CKB2019.read().unwrap().clone())
-            .arg("--version")
-            .output()
-            .expect("failed to execute process")
-            .stdout;
-        String::from_utf8(stdout).unwrap()
-    };
-    static ref V0_100: String = {
-        let stdout = ::std::process::Command::new(CKB2021.read().unwrap().clone())
-            .arg("--version")
-            .output()
-            .expect("failed to execute process")
-            .stdout;
-        String::from_utf8(stdout).unwrap()
-    };
-}
+use crate::binaries::{binary_version, role_binary, ROLE_CKB2019, ROLE_CKB2021};
 
+/// The output of `$CKB2019 --version`
 pub fn v0_43() -> String {
-    V0_43.clone()
+    binary_version(&role_binary(ROLE_CKB2019))
 }
 
+/// The output of `$CKB2021 --version`
 pub fn v0_100() -> String {
-    V0_100.clone()
+    binary_version(&role_binary(ROLE_CKB2021))
 }
@@ -0,0 +1,318 @@
+use crate::rpc::HTTP_CLIENT;
+use flate2::{write::GzEncoder, Compression};
+use lazy_static::lazy_static;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// The files collected from every node's working dir, relative to the working dir
+const COLLECTED_FILES: &[&str] = &[
+    "data/logs/run.log",
+    "stdout.log",
+    "stderr.log",
+    "ckb.toml",
+    "integration.toml",
+];
+// The RPC state dumped into "<working_dir>/dump/<method>.json" when the case fails
+const DUMPED_RPCS: &[(&str, &str)] = &[
+    ("local_node_info", "[]"),
+    ("get_tip_header", "[]"),
+    ("get_blockchain_info", "[]"),
+    ("get_peers", "[]"),
+    ("get_raw_tx_pool", "[true]"),
+    ("get_banned_addresses", "[]"),
+];
+
+lazy_static! {
+    // case_name => nodes initialized by the case
+    static ref REGISTRY: Mutex<HashMap<String, Vec<RegisteredNode>>> = Default::default();
+    // the cases whose ckb processes are kept running when panicking, see
+    // `CaseArtifacts::keep_nodes_on_panic`
+    static ref KEEP_ON_PANIC: Mutex<HashSet<String>> = Default::default();
+}
+
+#[derive(Debug, Clone)]
+struct RegisteredNode {
+    node_name: String,
+    working_dir: PathBuf,
+    rpc_url: String,
+    // The running ckb process
+    pid: Option<u32>,
+}
+
+/// Record a node created via `Node::init`, so that its files can be collected by
+/// `CaseArtifacts` even after the `Node` itself was dropped.
+pub(crate) fn register(case_name: &str, node_name: &str, working_dir: &Path, rpc_url: &str) {
+    REGISTRY
+        .lock()
+        .unwrap()
+        .entry(case_name.to_string())
+        .or_default()
+        .push(RegisteredNode {
+            node_name: node_name.to_string(),
+            working_dir: working_dir.to_path_buf(),
+            rpc_url: rpc_url.to_string(),
+            pid: None,
+        });
+}
+
+/// Record the running ckb process of the node at `working_dir`, `None` after it stopped.
+pub(crate) fn set_pid(working_dir: &Path, pid: Option<u32>) {
+    for nodes in REGISTRY.lock().unwrap().values_mut() {
+        for node in nodes.iter_mut() {
+            if node.working_dir == working_dir {
+                node.pid = pid;
+            }
+        }
+    }
+}
+
+/// Forget the ckb process `pid`, which has been killed.
+pub(crate) fn unset_pid(pid: u32) {
+    for nodes in REGISTRY.lock().unwrap().values_mut() {
+        for node in nodes.iter_mut() {
+            if node.pid == Some(pid) {
+                node.pid = None;
+            }
+        }
+    }
+}
+
+/// Whether the ckb process `pid` should be kept running while its node is dropped during
+/// unwinding, see `CaseArtifacts::keep_nodes_on_panic`.
+pub(crate) fn keeps_on_panic(pid: u32) -> bool {
+    let keep_on_panic = KEEP_ON_PANIC.lock().unwrap();
+    REGISTRY
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(case_name, _)| keep_on_panic.contains(*case_name))
+        .any(|(_, nodes)| nodes.iter().any(|node| node.pid == Some(pid)))
+}
+
+/// The working dirs of all the nodes initialized by a case, via `Node::init(case_name, ..)`.
+///
+/// Call `keep_nodes_on_panic` before running the case. When the case fails, call
+/// `dump_nodes_state` while the nodes are still running, `kill_nodes`, and then `bundle` to pack
+/// every node's logs, configs and dumped RPC state into "<artifacts_dir>/<case_name>.tar.gz".
+/// When the case passes, call `remove_working_dirs`.
+pub struct CaseArtifacts {
+    case_name: String,
+}
+
+impl CaseArtifacts {
+    pub fn new<S: ToString>(case_name: S) -> Self {
+        Self {
+            case_name: case_name.to_string(),
+        }
+    }
+
+    /// Keep the ckb processes of the case running when the nodes are dropped during unwinding,
+    /// instead of killing them, so that `dump_nodes_state` can still dump them after the panic
+    /// is caught. The caller must `kill_nodes` afterwards.
+    pub fn keep_nodes_on_panic(&self) {
+        KEEP_ON_PANIC.lock().unwrap().insert(self.case_name.clone());
+    }
+
+    /// The run.log paths of every node.
+    pub fn log_paths(&self) -> Vec<PathBuf> {
+        self.nodes()
+            .iter()
+            .map(|node| node.working_dir.join("data/logs/run.log"))
+            .collect()
+    }
+
+    /// Dump the tip, peers, tx-pool and banned addresses of every node via RPC, into
+    /// "<working_dir>/dump/". Never panics, errors are written into the dumped files instead.
+    ///
+    /// Only the first dump is kept, since the later ones are usually caused by the first failure,
+    /// e.g. the nodes were killed.
+    pub fn dump_nodes_state(&self) {
+        for node in self.nodes() {
+            let dump_dir = node.working_dir.join("dump");
+            if dump_dir.exists() {
+                continue;
+            }
+            if let Err(err) = fs::create_dir_all(&dump_dir) {
+                crate::error!(
+                    "failed to create dir \"{}\", error: {}",
+                    dump_dir.display(),
+                    err
+                );
+                continue;
+            }
+            for (method, params) in DUMPED_RPCS {
+                let content = match rpc_call(&node.rpc_url, method, params) {
+                    Ok(result) => result,
+                    Err(err) => format!("failed to call {}, error: {}", method, err),
+                };
+                let path = dump_dir.join(format!("{}.json", method));
+                if let Err(err) = fs::write(&path, content) {
+                    crate::error!("failed to write {}, error: {}", path.display(), err);
+                }
+            }
+        }
+    }
+
+    /// Kill the running ckb processes of every node, e.g. when the case hangs.
+    pub fn kill_nodes(&self) {
+        for node in self.nodes() {
+            if let Some(pid) = node.pid {
+                crate::error!("[Node {}] KILL pid: {}", node.node_name, pid);
+                kill_process(pid);
+                unset_pid(pid);
+            }
+        }
+    }
+
+    /// Pack the collected files of every node into "<artifacts_dir>/<case_name>.tar.gz" and
+    /// return its path. The working dirs are kept.
+    pub fn bundle(self, artifacts_dir: &Path) -> io::Result<PathBuf> {
+        let name = self.case_name.clone();
+        self.bundle_as(artifacts_dir, &name)
+    }
+
+    /// The same as `bundle`, but into "<artifacts_dir>/<name>.tar.gz", e.g. when the case runs
+    /// more than once.
+    pub fn bundle_as(self, artifacts_dir: &Path, name: &str) -> io::Result<PathBuf> {
+        fs::create_dir_all(artifacts_dir)?;
+        let tarball = artifacts_dir.join(format!("{}.tar.gz", name));
+        let encoder = GzEncoder::new(fs::File::create(&tarball)?, Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for node in self.nodes() {
+            let prefix = Path::new(&self.case_name).join(&node.node_name);
+            for file in COLLECTED_FILES {
+                let path = node.working_dir.join(file);
+                if path.is_file() {
+                    let name = Path::new(file).file_name().expect("checked is_file");
+                    builder.append_path_with_name(&path, prefix.join(name))?;
+                }
+            }
+            let dump_dir = node.working_dir.join("dump");
+            if dump_dir.is_dir() {
+                builder.append_dir_all(prefix.join("dump"), &dump_dir)?;
+            }
+        }
+        builder.into_inner()?.finish()?;
+        self.unregister();
+        Ok(tarball)
+    }
+
+    /// Remove the working dirs of every node.
+    pub fn remove_working_dirs(self) {
+        for node in self.nodes() {
+            if let Err(err) = fs::remove_dir_all(&node.working_dir) {
+                if err.kind() != io::ErrorKind::NotFound {
+                    crate::error!(
+                        "failed to remove dir \"{}\", error: {}",
+                        node.working_dir.display(),
+                        err
+                    );
+                }
+            }
+        }
+        self.unregister();
+    }
+
+    fn nodes(&self) -> Vec<RegisteredNode> {
+        REGISTRY
+            .lock()
+            .unwrap()
+            .get(&self.case_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn unregister(&self) {
+        REGISTRY.lock().unwrap().remove(&self.case_name);
+        KEEP_ON_PANIC.lock().unwrap().remove(&self.case_name);
+    }
+}
+
+// Kill and reap the ckb process, so that it isn't left as a zombie. The `Child` handle was
+// dropped without waiting when kept running on panic, see `CaseArtifacts::keep_nodes_on_panic`.
+#[cfg(unix)]
+fn kill_process(pid: u32) {
+    let ret = unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) };
+    if ret != 0 {
+        crate::error!(
+            "failed to kill ckb process {}, error: {}",
+            pid,
+            io::Error::last_os_error()
+        );
+        return;
+    }
+    let mut status = 0;
+    let ret = unsafe { libc::waitpid(pid as libc::pid_t, &mut status, 0) };
+    if ret == -1 {
+        let err = io::Error::last_os_error();
+        // ECHILD: reaped by the `Node` still owning the process, e.g. a timed-out case's thread
+        if err.raw_os_error() != Some(libc::ECHILD) {
+            crate::error!("failed to wait ckb process {}, error: {}", pid, err);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_process(pid: u32) {
+    let result = ::std::process::Command::new("taskkill")
+        .args(&["/F", "/PID", &pid.to_string()])
+        .output();
+    if let Err(err) = result {
+        crate::error!("failed to kill ckb process {}, error: {}", pid, err);
+    }
+}
+
+// Call RPC and return the raw result, which works for any ckb version
+fn rpc_call(rpc_url: &str, method: &str, params: &str) -> Result<String, String> {
+    let request = format!(
+        r#"{{"id": 0, "jsonrpc": "2.0", "method": "{}", "params": {}}}"#,
+        method, params
+    );
+    let response = HTTP_CLIENT
+        .post(rpc_url)
+        .header("content-type", "application/json")
+        .body(request)
+        .timeout(::std::time::Duration::from_secs(5))
+        .send()
+        .map_err(|err| err.to_string())?;
+    let response: serde_json::Value = response.json().map_err(|err| err.to_string())?;
+    serde_json::to_string_pretty(&response).map_err(|err| err.to_string())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::{register, set_pid, CaseArtifacts};
+    use std::path::Path;
+    use std::process::Command;
+
+    #[test]
+    fn test_kill_nodes_reaps_processes() {
+        let case_name = "test_kill_nodes_reaps_processes";
+        let working_dir = Path::new("/nonexistent/test_kill_nodes_reaps_processes");
+        let child = Command::new("sleep")
+            .arg("60")
+            .spawn()
+            .unwrap_or_else(|err| panic!("failed to spawn sleep, error: {}", err));
+        let pid = child.id();
+        // The `Child` handle is dropped without waiting, as the nodes kept running on panic
+        drop(child);
+        register(case_name, "node", working_dir, "http://127.0.0.1:1/");
+        set_pid(working_dir, Some(pid));
+
+        let artifacts = CaseArtifacts::new(case_name);
+        artifacts.kill_nodes();
+        assert!(artifacts.nodes().iter().all(|node| node.pid.is_none()));
+        // Neither running nor a zombie waiting to be reaped
+        let ret =
+            unsafe { libc::waitpid(pid as libc::pid_t, ::std::ptr::null_mut(), libc::WNOHANG) };
+        assert_eq!(ret, -1);
+        assert_eq!(
+            ::std::io::Error::last_os_error().raw_os_error(),
+            Some(libc::ECHILD)
+        );
+        artifacts.remove_working_dirs();
+    }
+}
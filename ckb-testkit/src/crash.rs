@@ -0,0 +1,381 @@
+use crate::Node;
+use ckb_jsonrpc_types::Status;
+use ckb_types::{
+    core::{BlockNumber, BlockView, TransactionView},
+    packed::{self, Byte32, OutPoint},
+    prelude::*,
+};
+use std::collections::HashSet;
+use std::io;
+use std::thread::{self, sleep};
+use std::time::Duration;
+
+/// The point at which `CrashHarness` SIGKILLs the node.
+#[derive(Debug, Clone)]
+pub enum CrashPoint {
+    /// Kill the node right after the workload step `step` has been acknowledged.
+    AfterStep(usize),
+    /// Kill the node `delay` after sending the `submit_block` request of workload step `step`,
+    /// so that the node dies while processing the block.
+    DuringSubmitBlock { step: usize, delay: Duration },
+    /// Choose one of the above points according to `seed`. Log the seed to reproduce a failure.
+    Random { seed: u64 },
+}
+
+/// The blocks and transactions acknowledged via RPC before the node was killed.
+#[derive(Debug, Clone, Default)]
+pub struct Acknowledged {
+    pub blocks: Vec<(BlockNumber, Byte32)>,
+    pub transactions: Vec<TransactionView>,
+    /// The hashes of the acknowledged transactions committed in the acknowledged blocks
+    pub committed: HashSet<Byte32>,
+}
+
+impl Acknowledged {
+    fn push_block(&mut self, block: &BlockView, hash: Byte32) {
+        let acknowledged = self
+            .transactions
+            .iter()
+            .map(|tx| tx.hash())
+            .collect::<HashSet<_>>();
+        for tx in block.transactions().iter().skip(1) {
+            if acknowledged.contains(&tx.hash()) {
+                self.committed.insert(tx.hash());
+            }
+        }
+        self.blocks.push((block.number(), hash));
+    }
+}
+
+/// What became of the acknowledged transactions after the node was restarted.
+#[derive(Debug, Clone, Default)]
+pub struct CrashReport {
+    pub acknowledged: Acknowledged,
+    /// The acknowledged transactions committed after the restart
+    pub committed: Vec<Byte32>,
+    /// The acknowledged transactions still in the tx-pool after the restart
+    pub pending: Vec<Byte32>,
+    /// The acknowledged transactions that were pending before the kill and are unknown after
+    /// the restart, as the tx-pool is not persisted when the node is killed
+    pub dropped: Vec<Byte32>,
+}
+
+/// Drive a node through a workload, SIGKILL it at a `CrashPoint`, restart it on the same working
+/// directory and verify that the chain, tx-pool and live cells are consistent with what was
+/// acknowledged before the kill.
+///
+/// Every workload step sends a transaction spending one spendable always-success cell, if any,
+/// and then mines a block. So the node should be initialized with a database that contains
+/// mature always-success cells, e.g. "testdata/db/Epoch2V2TestData".
+pub struct CrashHarness {
+    n_steps: usize,
+    crash_point: CrashPoint,
+}
+
+impl CrashHarness {
+    pub fn new(n_steps: usize, crash_point: CrashPoint) -> Self {
+        assert!(n_steps > 0);
+        Self {
+            n_steps,
+            crash_point,
+        }
+    }
+
+    /// Run the workload, kill and restart the node, then verify it. Return what became of the
+    /// acknowledged transactions.
+    pub fn run(&self, node: &mut Node) -> Result<CrashReport, String> {
+        let (crash_step, submit_delay) = self.resolve_crash_point();
+        crate::info!(
+            "[Node {}] crash harness, crash_point: {:?}, crash_step: {}, submit_delay: {:?}",
+            node.node_name(),
+            self.crash_point,
+            crash_step,
+            submit_delay
+        );
+
+        let acknowledged = self.run_workload(node, crash_step, submit_delay)?;
+        crate::info!(
+            "[Node {}] killed, acknowledged {} blocks and {} transactions",
+            node.node_name(),
+            acknowledged.blocks.len(),
+            acknowledged.transactions.len()
+        );
+
+        node.stop();
+        node.try_start()
+            .map_err(|err| format!("failed to restart the killed node, error: {}", err))?;
+        let report = verify(node, acknowledged)?;
+        if !report.dropped.is_empty() {
+            crate::info!(
+                "[Node {}] {} acknowledged pending transactions dropped after restart: {:?}",
+                node.node_name(),
+                report.dropped.len(),
+                report.dropped
+            );
+        }
+        Ok(report)
+    }
+
+    fn resolve_crash_point(&self) -> (usize, Option<Duration>) {
+        match self.crash_point {
+            CrashPoint::AfterStep(step) => (step.min(self.n_steps - 1), None),
+            CrashPoint::DuringSubmitBlock { step, delay } => {
+                (step.min(self.n_steps - 1), Some(delay))
+            }
+            CrashPoint::Random { seed } => {
+                let mut rng = XorShift64::new(seed);
+                let step = (rng.next() % self.n_steps as u64) as usize;
+                if rng.next() % 2 == 0 {
+                    (step, None)
+                } else {
+                    let delay = Duration::from_micros(rng.next() % 50_000);
+                    (step, Some(delay))
+                }
+            }
+        }
+    }
+
+    fn run_workload(
+        &self,
+        node: &Node,
+        crash_step: usize,
+        submit_delay: Option<Duration>,
+    ) -> Result<Acknowledged, String> {
+        let pid = node
+            .pid()
+            .ok_or_else(|| format!("[Node {}] is not started", node.node_name()))?;
+        let mut acknowledged = Acknowledged::default();
+        let mut spent: HashSet<OutPoint> = HashSet::new();
+        for step in 0..=crash_step {
+            let input = node
+                .get_spendable_always_success_cells()
+                .into_iter()
+                .find(|cell| !spent.contains(&cell.out_point));
+            if let Some(input) = input {
+                let tx = node.always_success_transaction(&input);
+                node.rpc_client()
+                    .send_transaction_result(tx.data().into())
+                    .map_err(|err| {
+                        format!(
+                            "failed to send transaction {:#x}, error: {}",
+                            tx.hash(),
+                            err
+                        )
+                    })?;
+                spent.insert(input.out_point);
+                acknowledged.transactions.push(tx);
+            }
+
+            let template = node.rpc_client().get_block_template(None, None, None);
+            let block = packed::Block::from(template).into_view();
+            match submit_delay {
+                Some(delay) if step == crash_step => {
+                    let killer = thread::spawn(move || {
+                        sleep(delay);
+                        kill(pid)
+                    });
+                    let result = node
+                        .rpc_client()
+                        .submit_block("".to_owned(), block.data().into());
+                    killer
+                        .join()
+                        .expect("join killer thread")
+                        .map_err(|err| format!("failed to kill ckb process, error: {}", err))?;
+                    // The block is acknowledged only if the RPC returned before the kill
+                    if let Ok(hash) = result {
+                        acknowledged.push_block(&block, hash);
+                    }
+                    return Ok(acknowledged);
+                }
+                _ => {
+                    let hash = node
+                        .rpc_client()
+                        .submit_block("".to_owned(), block.data().into())
+                        .map_err(|err| {
+                            format!("failed to submit block {}, error: {}", block.number(), err)
+                        })?;
+                    acknowledged.push_block(&block, hash);
+                    node.wait_for_tx_pool();
+                }
+            }
+        }
+        kill(pid).map_err(|err| format!("failed to kill ckb process, error: {}", err))?;
+        Ok(acknowledged)
+    }
+}
+
+/// Verify that the restarted node is consistent with what was acknowledged before the kill:
+///
+/// * the acknowledged blocks are still on the main chain;
+/// * the acknowledged transactions committed in the acknowledged blocks are still committed;
+/// * the tx-pool contains nothing but the acknowledged transactions not committed; the pending
+///   ones unknown by the restarted node are reported as dropped, since the tx-pool is not
+///   persisted when the node is killed;
+/// * the live cells reported by the indexer are live, and the inputs of committed transactions
+///   are dead.
+fn verify(node: &Node, acknowledged: Acknowledged) -> Result<CrashReport, String> {
+    let tip_number = node.get_tip_block_number();
+    if let Some((number, hash)) = acknowledged.blocks.last() {
+        if tip_number < *number {
+            return Err(format!(
+                "tip {} is lower than the acknowledged block {} {:#x}",
+                tip_number, number, hash
+            ));
+        }
+    }
+    for (number, hash) in acknowledged.blocks.iter() {
+        let actual = node.rpc_client().get_block_hash(*number);
+        if actual.as_ref() != Some(hash) {
+            return Err(format!(
+                "acknowledged block {} {:#x} lost, actual: {:?}",
+                number, hash, actual
+            ));
+        }
+    }
+
+    let mut report = CrashReport::default();
+    let mut committed = Vec::new();
+    for tx in acknowledged.transactions.iter() {
+        let status = node
+            .rpc_client()
+            .get_transaction(tx.hash())
+            .map(|tx_with_status| tx_with_status.tx_status.status);
+        match status {
+            Some(Status::Committed) => {
+                report.committed.push(tx.hash());
+                committed.push(tx);
+            }
+            _ if acknowledged.committed.contains(&tx.hash()) => {
+                return Err(format!(
+                    "transaction {:#x} committed in an acknowledged block, but the status is {:?} after restart",
+                    tx.hash(),
+                    status
+                ));
+            }
+            Some(_) => report.pending.push(tx.hash()),
+            None => report.dropped.push(tx.hash()),
+        }
+    }
+    let tx_pool_info = node.rpc_client().tx_pool_info();
+    let tx_pool_size = tx_pool_info.pending.value() + tx_pool_info.proposed.value();
+    if tx_pool_size != report.pending.len() as u64 {
+        return Err(format!(
+            "tx-pool contains {} transactions after restart, but {} of them are acknowledged: {:?}",
+            tx_pool_size,
+            report.pending.len(),
+            report.pending
+        ));
+    }
+
+    for cell in node.get_spendable_always_success_cells() {
+        let cell_with_status = node
+            .rpc_client()
+            .get_live_cell(cell.out_point.clone().into(), false);
+        if cell_with_status.status != "live" {
+            return Err(format!(
+                "indexer reports live cell {:?}, but the node reports {}",
+                cell.out_point, cell_with_status.status
+            ));
+        }
+    }
+    for tx in committed {
+        for out_point in tx.input_pts_iter() {
+            let cell_with_status = node
+                .rpc_client()
+                .get_live_cell(out_point.clone().into(), false);
+            if cell_with_status.status == "live" {
+                return Err(format!(
+                    "input {:?} of committed transaction {:#x} is still live",
+                    out_point,
+                    tx.hash()
+                ));
+            }
+        }
+    }
+    report.acknowledged = acknowledged;
+    Ok(report)
+}
+
+fn kill(pid: u32) -> io::Result<()> {
+    let ret = unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+// A tiny PRNG, good enough to choose a crash point reproducibly from a seed
+struct XorShift64(u64);
+
+impl XorShift64 {
+    fn new(seed: u64) -> Self {
+        // xorshift gets stuck at zero
+        Self(seed | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CrashHarness, CrashPoint, XorShift64};
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    #[test]
+    fn test_xorshift64() {
+        // The zero seed doesn't get stuck
+        let mut rng = XorShift64::new(0);
+        assert_ne!(rng.next(), 0);
+
+        let sequence = |seed| {
+            let mut rng = XorShift64::new(seed);
+            (0..10).map(|_| rng.next()).collect::<Vec<_>>()
+        };
+        assert_eq!(sequence(42), sequence(42));
+        assert_ne!(sequence(42), sequence(43));
+        assert_eq!(sequence(42).into_iter().collect::<HashSet<_>>().len(), 10);
+    }
+
+    #[test]
+    fn test_resolve_fixed_crash_point() {
+        let harness = CrashHarness::new(10, CrashPoint::AfterStep(3));
+        assert_eq!(harness.resolve_crash_point(), (3, None));
+        let harness = CrashHarness::new(10, CrashPoint::AfterStep(100));
+        assert_eq!(harness.resolve_crash_point(), (9, None));
+
+        let delay = Duration::from_millis(5);
+        let harness = CrashHarness::new(10, CrashPoint::DuringSubmitBlock { step: 100, delay });
+        assert_eq!(harness.resolve_crash_point(), (9, Some(delay)));
+    }
+
+    #[test]
+    fn test_resolve_random_crash_point() {
+        let n_steps = 10;
+        let mut steps = HashSet::new();
+        let mut kinds = HashSet::new();
+        for seed in 0..1000 {
+            let harness = CrashHarness::new(n_steps, CrashPoint::Random { seed });
+            let (step, delay) = harness.resolve_crash_point();
+            // The same seed chooses the same crash point
+            assert_eq!(harness.resolve_crash_point(), (step, delay));
+            assert!(step < n_steps);
+            if let Some(delay) = delay {
+                assert!(delay < Duration::from_millis(50));
+            }
+            steps.insert(step);
+            kinds.insert(delay.is_some());
+        }
+        // Every step and both kinds of crash points can be chosen
+        assert_eq!(steps.len(), n_steps);
+        assert_eq!(kinds.len(), 2);
+    }
+}
@@ -1,23 +1,42 @@
+mod artifacts;
 pub mod connector;
+#[cfg(unix)]
+mod crash;
+mod log_watcher;
 pub mod logger;
+mod mock;
 mod node;
 mod nodes;
 mod rpc;
-#[cfg(feature = "with_subscribe")]
 mod subscribe;
 mod user;
 pub mod util;
 
+pub use artifacts::CaseArtifacts;
 pub use connector::{compress, decompress, Connector, ConnectorBuilder, SupportProtocols};
+#[cfg(unix)]
+pub use crash::{Acknowledged, CrashHarness, CrashPoint, CrashReport};
+pub use log_watcher::{LogEvent, LogWatcher};
 pub use logger::LOG_TARGET;
-pub use node::{BuildInstruction, Node, NodeOptions};
+pub use mock::{MockChain, MockCkb, MockPeer};
+pub use node::{
+    BuildInstruction, ChainSpecBuilder, HardForkEpochs, Node, NodeOptions, NodeOptionsBuilder,
+    NodeStartError, Snapshot, SnapshotKey,
+};
 pub use nodes::Nodes;
+pub use rpc::types as rpc_types;
+pub use rpc::{AsyncRpcClient, Call, RpcClient, RpcError, RpcRecord, RpcRecorder, RpcReplayer};
+pub use subscribe::{
+    SubscriptionAddress, SubscriptionEvent, SubscriptionTopic, Subscriptions,
+    SUBSCRIPTION_QUEUE_CAPACITY,
+};
 pub use user::User;
 
 pub use ckb_crypto;
 pub use ckb_jsonrpc_types;
 pub use ckb_types;
 pub use p2p;
+pub use toml_edit;
 
 use ckb_types::{h256, H256};
 
@@ -0,0 +1,317 @@
+use lazy_static::lazy_static;
+use log::Level;
+use regex::Regex;
+use std::fs::{File, Metadata};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+lazy_static! {
+    // ckb log line: "<date> <time> <timezone> <thread> <level> <target>  <message>", e.g.
+    // "2021-09-01 10:00:00.000 +08:00 main INFO ckb_bin::subcommand::run  ckb version: 0.100.0"
+    static ref LOG_LINE: Regex = Regex::new(
+        r"^(\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}\.\d+ [+-]\d{2}:\d{2}) +(\S+) +(TRACE|DEBUG|INFO|WARN|ERROR) +(\S+) +(.*)$"
+    )
+    .expect("valid regex");
+}
+
+/// A parsed line of ckb's run.log. Lines which don't start with a log header, e.g. backtraces,
+/// are appended to the message of the previous event.
+#[derive(Debug, Clone)]
+pub struct LogEvent {
+    pub timestamp: String,
+    pub thread: String,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+impl LogEvent {
+    fn parse(line: &str) -> Option<Self> {
+        let captures = LOG_LINE.captures(line)?;
+        Some(Self {
+            timestamp: captures[1].to_string(),
+            thread: captures[2].to_string(),
+            level: Level::from_str(&captures[3]).ok()?,
+            target: captures[4].to_string(),
+            message: captures[5].to_string(),
+        })
+    }
+
+    /// Whether it is an `ERROR` line or a panic
+    pub fn is_error(&self) -> bool {
+        self.level == Level::Error || self.target == "panic" || self.message.contains("panicked at")
+    }
+}
+
+/// Tail ckb's run.log and parse it into `LogEvent`s.
+pub struct LogWatcher {
+    path: PathBuf,
+    offset: u64,
+    // the identity of the watched file, to detect that the log file was rotated
+    file_id: Option<u64>,
+    // the trailing incomplete line
+    partial: String,
+    events: Vec<LogEvent>,
+}
+
+impl LogWatcher {
+    /// Watch `path` from the beginning.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            offset: 0,
+            file_id: None,
+            partial: String::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Watch `path` from its current end, i.e. only the lines appended afterwards.
+    pub fn from_end<P: AsRef<Path>>(path: P) -> Self {
+        let mut watcher = Self::new(path);
+        if let Ok(metadata) = watcher.path.metadata() {
+            watcher.offset = metadata.len();
+            watcher.file_id = file_id(&metadata);
+        }
+        watcher
+    }
+
+    /// All the events parsed so far.
+    pub fn events(&self) -> &[LogEvent] {
+        &self.events
+    }
+
+    /// Read the newly appended lines and return the newly parsed events.
+    pub fn poll(&mut self) -> Vec<LogEvent> {
+        let mut appended = String::new();
+        if let Ok(mut file) = File::open(&self.path) {
+            let (len, id) = file
+                .metadata()
+                .map(|metadata| (metadata.len(), file_id(&metadata)))
+                .unwrap_or((0, None));
+            if len < self.offset || id != self.file_id {
+                // The log file was rotated or truncated
+                self.offset = 0;
+                self.partial.clear();
+                self.file_id = id;
+            }
+            if file.seek(SeekFrom::Start(self.offset)).is_ok() {
+                let mut buf = Vec::new();
+                if let Ok(n) = file.read_to_end(&mut buf) {
+                    self.offset += n as u64;
+                    appended = String::from_utf8_lossy(&buf).to_string();
+                }
+            }
+        }
+
+        self.partial.push_str(&appended);
+        let mut new_events = Vec::new();
+        while let Some(pos) = self.partial.find('\n') {
+            let line = self.partial[..pos].trim_end_matches('\r').to_string();
+            self.partial.drain(..=pos);
+            if let Some(event) = LogEvent::parse(&line) {
+                new_events.push(event.clone());
+                self.events.push(event);
+            } else if let Some(last) = self.events.last_mut() {
+                last.message.push('\n');
+                last.message.push_str(&line);
+                if let Some(new_last) = new_events.last_mut() {
+                    *new_last = last.clone();
+                }
+            }
+        }
+        new_events
+    }
+
+    /// Wait for an event whose message matches `pattern`, only the lines appended since the
+    /// last poll are checked.
+    pub fn wait_for_log(&mut self, pattern: &Regex, timeout: Duration) -> Option<LogEvent> {
+        let start = Instant::now();
+        loop {
+            if let Some(event) = self
+                .poll()
+                .into_iter()
+                .find(|event| pattern.is_match(&event.message))
+            {
+                return Some(event);
+            }
+            if start.elapsed() > timeout {
+                return None;
+            }
+            sleep(Duration::from_millis(100));
+        }
+    }
+
+    /// Return the `ERROR` and panic events parsed so far.
+    pub fn errors(&mut self) -> Vec<LogEvent> {
+        self.poll();
+        self.events
+            .iter()
+            .filter(|event| event.is_error())
+            .cloned()
+            .collect()
+    }
+
+    /// Panic if any `ERROR` or panic line was emitted.
+    pub fn assert_no_errors(&mut self) {
+        let errors = self.errors();
+        assert!(
+            errors.is_empty(),
+            "{} contains error lines: {:#?}",
+            self.path.display(),
+            errors
+        );
+    }
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.ino())
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &Metadata) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LogEvent, LogWatcher};
+    use log::Level;
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+    use std::path::Path;
+
+    const INFO_LINE: &str =
+        "2021-09-01 10:00:00.000 +08:00 main INFO ckb_bin::subcommand::run  ckb version: 0.100.0";
+    const ERROR_LINE: &str =
+        "2021-09-01 10:00:01.123 -07:00 ChainService ERROR ckb_chain::chain  block verify error";
+
+    fn append(path: &Path, content: &str) {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_parse() {
+        let event = LogEvent::parse(INFO_LINE).unwrap();
+        assert_eq!(event.timestamp, "2021-09-01 10:00:00.000 +08:00");
+        assert_eq!(event.thread, "main");
+        assert_eq!(event.level, Level::Info);
+        assert_eq!(event.target, "ckb_bin::subcommand::run");
+        assert_eq!(event.message, "ckb version: 0.100.0");
+        assert!(!event.is_error());
+
+        let event = LogEvent::parse(ERROR_LINE).unwrap();
+        assert_eq!(event.timestamp, "2021-09-01 10:00:01.123 -07:00");
+        assert_eq!(event.thread, "ChainService");
+        assert_eq!(event.level, Level::Error);
+        assert_eq!(event.message, "block verify error");
+        assert!(event.is_error());
+
+        let event = LogEvent::parse(
+            "2021-09-01 10:00:02.000 +00:00 GlobalRt-1 ERROR panic  thread 'GlobalRt-1' panicked at 'oops', src/lib.rs:1:1",
+        )
+        .unwrap();
+        assert_eq!(event.target, "panic");
+        assert!(event.is_error());
+
+        for line in [
+            "",
+            "   0: ckb_bin::main",
+            "stack backtrace:",
+            "2021-09-01 10:00:00.000 main INFO ckb  missing timezone",
+            "2021-09-01 10:00:00.000 +08:00 main NOTICE ckb  unknown level",
+        ] {
+            assert!(LogEvent::parse(line).is_none(), "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn test_continuation_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.log");
+        // The lines before the first event have nothing to be appended to
+        append(
+            &path,
+            &format!(
+                "orphan line\n{}\nstack backtrace:\n   0: ckb_bin::main\n{}\n",
+                ERROR_LINE, INFO_LINE
+            ),
+        );
+
+        let mut watcher = LogWatcher::new(&path);
+        let events = watcher.poll();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0].message,
+            "block verify error\nstack backtrace:\n   0: ckb_bin::main"
+        );
+        assert_eq!(events[1].message, "ckb version: 0.100.0");
+
+        // A continuation line appended later goes to the last event
+        append(&path, "   1: std::rt::lang_start\n");
+        assert!(watcher.poll().is_empty());
+        assert_eq!(
+            watcher.events().last().unwrap().message,
+            "ckb version: 0.100.0\n   1: std::rt::lang_start"
+        );
+        assert_eq!(watcher.errors().len(), 1);
+    }
+
+    #[test]
+    fn test_partial_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.log");
+        let mut watcher = LogWatcher::new(&path);
+        // The file doesn't exist yet
+        assert!(watcher.poll().is_empty());
+
+        let (head, tail) = INFO_LINE.split_at(20);
+        append(&path, head);
+        assert!(watcher.poll().is_empty());
+        append(&path, tail);
+        assert!(watcher.poll().is_empty());
+        append(&path, "\r\n");
+        let events = watcher.poll();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].message, "ckb version: 0.100.0");
+        assert!(watcher.poll().is_empty());
+    }
+
+    #[test]
+    fn test_truncate_and_rotate() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.log");
+        append(&path, &format!("{}\n{}\n", INFO_LINE, INFO_LINE));
+
+        let mut watcher = LogWatcher::from_end(&path);
+        assert!(watcher.poll().is_empty());
+        append(&path, &format!("{}\n", ERROR_LINE));
+        assert_eq!(watcher.poll().len(), 1);
+
+        // Truncated, along with a pending partial line
+        append(&path, "2021-09-01");
+        assert!(watcher.poll().is_empty());
+        fs::write(&path, format!("{}\n", ERROR_LINE)).unwrap();
+        let events = watcher.poll();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].level, Level::Error);
+
+        // Rotated, i.e. renamed and recreated
+        fs::rename(&path, dir.path().join("run.log.1")).unwrap();
+        append(&path, &format!("{}\n", INFO_LINE));
+        let events = watcher.poll();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].level, Level::Info);
+        assert_eq!(watcher.events().len(), 3);
+    }
+}
@@ -0,0 +1,379 @@
+use ckb_jsonrpc_types::BlockTemplate;
+use ckb_types::{
+    bytes::Bytes,
+    core::{
+        BlockBuilder, BlockNumber, BlockView, Capacity, EpochNumberWithFraction,
+        TransactionBuilder, TransactionView,
+    },
+    packed::{self, Byte32, CellInput, CellOutput},
+    prelude::*,
+    H256,
+};
+use std::collections::{HashMap, HashSet};
+
+// The error codes of the ckb RPC
+pub(super) const CKB_INTERNAL_ERROR: i64 = -1;
+pub(super) const TRANSACTION_FAILED_TO_RESOLVE: i64 = -301;
+pub(super) const POOL_REJECTED_DUPLICATED_TRANSACTION: i64 = -1107;
+
+const GENESIS_TIMESTAMP: u64 = 1_600_000_000_000;
+const COMPACT_TARGET: u32 = 0x2010_0000;
+const EPOCH_LENGTH: u64 = 1000;
+const DEFAULT_BLOCK_INTERVAL: u64 = 1000;
+
+/// A peer connected to the mock node, via the RPC `add_node` or `MockChain::add_peer`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockPeer {
+    pub node_id: String,
+    pub address: String,
+}
+
+/// The in-memory state behind `MockCkb`: the blocks, the main chain, the tx-pool and the peers.
+///
+/// Nothing is verified. A block is accepted as long as its parent is known, and the longest
+/// chain is the main chain. The block templates commit all the transactions in the tx-pool, so
+/// a sent transaction is committed by the next mined block.
+pub struct MockChain {
+    node_id: String,
+    blocks: HashMap<Byte32, BlockView>,
+    main_chain: Vec<Byte32>,
+    tx_pool: Vec<TransactionView>,
+    peers: Vec<MockPeer>,
+    network_active: bool,
+    block_interval: u64,
+    responses: HashMap<String, serde_json::Value>,
+}
+
+impl MockChain {
+    pub(super) fn new(node_id: String) -> Self {
+        // The genesis block is the same for all the mocks, so that they share the same chain
+        let genesis_block = BlockBuilder::default()
+            .number(0.pack())
+            .timestamp(GENESIS_TIMESTAMP.pack())
+            .compact_target(COMPACT_TARGET.pack())
+            .epoch(epoch(0).full_value().pack())
+            .transaction(cellbase(0))
+            .build();
+        let genesis_hash = genesis_block.hash();
+        let mut blocks = HashMap::new();
+        blocks.insert(genesis_hash.clone(), genesis_block);
+        Self {
+            node_id,
+            blocks,
+            main_chain: vec![genesis_hash],
+            tx_pool: Vec::new(),
+            peers: Vec::new(),
+            network_active: true,
+            block_interval: DEFAULT_BLOCK_INTERVAL,
+            responses: HashMap::new(),
+        }
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    pub fn genesis_block(&self) -> &BlockView {
+        self.block_by_number(0).expect("genesis block exists")
+    }
+
+    pub fn tip_block(&self) -> &BlockView {
+        self.block_by_number(self.tip_number())
+            .expect("tip block exists")
+    }
+
+    pub fn tip_number(&self) -> BlockNumber {
+        self.main_chain.len() as BlockNumber - 1
+    }
+
+    /// Return the block of the main chain or the forks
+    pub fn block(&self, hash: &Byte32) -> Option<&BlockView> {
+        self.blocks.get(hash)
+    }
+
+    /// Return the block of the main chain
+    pub fn block_by_number(&self, number: BlockNumber) -> Option<&BlockView> {
+        self.main_chain
+            .get(number as usize)
+            .map(|hash| &self.blocks[hash])
+    }
+
+    pub fn is_main_chain(&self, hash: &Byte32) -> bool {
+        self.blocks
+            .get(hash)
+            .map(|block| self.main_chain.get(block.number() as usize) == Some(hash))
+            .unwrap_or(false)
+    }
+
+    /// Set the timestamp difference between the mined blocks and their parents, default is
+    /// 1000ms. Mocks mining with different intervals on the same parent produce forks.
+    pub fn set_block_interval(&mut self, block_interval: u64) {
+        self.block_interval = block_interval;
+    }
+
+    /// Answer `method` with `result` instead of the chain state, e.g. to mock the RPC methods
+    /// the mock does not implement.
+    pub fn set_response(&mut self, method: &str, result: serde_json::Value) {
+        self.responses.insert(method.to_string(), result);
+    }
+
+    pub fn clear_response(&mut self, method: &str) {
+        self.responses.remove(method);
+    }
+
+    pub(super) fn response(&self, method: &str) -> Option<&serde_json::Value> {
+        self.responses.get(method)
+    }
+
+    /// The template of the next block on the tip, committing all the transactions in the tx-pool
+    pub fn block_template(&self) -> BlockTemplate {
+        let tip_block = self.tip_block();
+        let number = tip_block.number() + 1;
+        let cellbase = cellbase(number);
+        let transactions = self
+            .tx_pool
+            .iter()
+            .map(|tx| {
+                serde_json::json!({
+                    "hash": hash_of(&tx.hash()),
+                    "required": false,
+                    "cycles": null,
+                    "depends": null,
+                    "data": ckb_jsonrpc_types::Transaction::from(tx.data()),
+                })
+            })
+            .collect::<Vec<_>>();
+        let template = serde_json::json!({
+            "version": "0x0",
+            "compact_target": format!("{:#x}", COMPACT_TARGET),
+            "current_time": format!("{:#x}", tip_block.timestamp() + self.block_interval),
+            "number": format!("{:#x}", number),
+            "epoch": format!("{:#x}", epoch(number).full_value()),
+            "parent_hash": hash_of(&tip_block.hash()),
+            "cycles_limit": "0xd09dc300",
+            "bytes_limit": "0x91c08",
+            "uncles_count_limit": "0x2",
+            "uncles": [],
+            "transactions": transactions,
+            "proposals": [],
+            "cellbase": {
+                "hash": hash_of(&cellbase.hash()),
+                "cycles": null,
+                "data": ckb_jsonrpc_types::Transaction::from(cellbase.data()),
+            },
+            "work_id": "0x0",
+            "dao": H256::default(),
+        });
+        serde_json::from_value(template)
+            .unwrap_or_else(|err| panic!("failed to build the block template, error: {}", err))
+    }
+
+    /// Mine `n_blocks` blocks on the tip
+    pub fn mine(&mut self, n_blocks: u64) {
+        for _ in 0..n_blocks {
+            let block = packed::Block::from(self.block_template()).into_view();
+            self.process_block(block)
+                .unwrap_or_else(|err| panic!("failed to process the mined block, error: {}", err));
+        }
+    }
+
+    /// Accept the block if its parent is known, and switch the main chain to it if it is longer.
+    pub fn process_block(&mut self, block: BlockView) -> Result<Byte32, jsonrpc_core::Error> {
+        let hash = block.hash();
+        if self.blocks.contains_key(&hash) {
+            return Ok(hash);
+        }
+        let parent_number = self
+            .blocks
+            .get(&block.parent_hash())
+            .map(|parent| parent.number())
+            .ok_or_else(|| {
+                rpc_error(
+                    CKB_INTERNAL_ERROR,
+                    "UnknownParentError",
+                    format!("unknown parent {}", block.parent_hash()),
+                )
+            })?;
+        if parent_number + 1 != block.number() {
+            return Err(rpc_error(
+                CKB_INTERNAL_ERROR,
+                "NumberError",
+                format!(
+                    "expect block number {}, actual {}",
+                    parent_number + 1,
+                    block.number()
+                ),
+            ));
+        }
+        let number = block.number();
+        self.blocks.insert(hash.clone(), block);
+        if number > self.tip_number() {
+            self.switch_main_chain(hash.clone());
+        }
+        Ok(hash)
+    }
+
+    /// Roll the main chain back to the block, removing the blocks after it and clearing the
+    /// tx-pool, as the RPC `truncate` does.
+    pub fn truncate(&mut self, hash: &Byte32) -> Result<(), jsonrpc_core::Error> {
+        if !self.is_main_chain(hash) {
+            return Err(rpc_error(
+                CKB_INTERNAL_ERROR,
+                "TruncateError",
+                format!("{} is not on the main chain", hash),
+            ));
+        }
+        let number = self.blocks[hash].number();
+        for detached in self.main_chain.split_off(number as usize + 1) {
+            self.blocks.remove(&detached);
+        }
+        self.tx_pool.clear();
+        Ok(())
+    }
+
+    pub fn tx_pool(&self) -> &[TransactionView] {
+        &self.tx_pool
+    }
+
+    pub fn send_transaction(&mut self, tx: TransactionView) -> Result<Byte32, jsonrpc_core::Error> {
+        let hash = tx.hash();
+        if self.tx_pool.iter().any(|pending| pending.hash() == hash) {
+            return Err(rpc_error(
+                POOL_REJECTED_DUPLICATED_TRANSACTION,
+                "PoolRejectedDuplicatedTransaction",
+                format!("Duplicated({})", hash),
+            ));
+        }
+        if self.get_transaction(&hash).is_some() {
+            return Err(rpc_error(
+                TRANSACTION_FAILED_TO_RESOLVE,
+                "TransactionFailedToResolve",
+                format!("Resolve failed Dead({})", hash),
+            ));
+        }
+        self.tx_pool.push(tx);
+        Ok(hash)
+    }
+
+    /// Return the transaction and the main chain block committing it, `None` for the pending
+    /// transaction.
+    pub fn get_transaction(&self, hash: &Byte32) -> Option<(TransactionView, Option<Byte32>)> {
+        if let Some(tx) = self.tx_pool.iter().find(|tx| &tx.hash() == hash) {
+            return Some((tx.clone(), None));
+        }
+        self.main_chain.iter().find_map(|block_hash| {
+            self.blocks[block_hash]
+                .transactions()
+                .into_iter()
+                .find(|tx| &tx.hash() == hash)
+                .map(|tx| (tx, Some(block_hash.clone())))
+        })
+    }
+
+    pub fn remove_transaction(&mut self, hash: &Byte32) -> bool {
+        let len = self.tx_pool.len();
+        self.tx_pool.retain(|tx| &tx.hash() != hash);
+        self.tx_pool.len() != len
+    }
+
+    pub fn clear_tx_pool(&mut self) {
+        self.tx_pool.clear();
+    }
+
+    pub fn peers(&self) -> &[MockPeer] {
+        &self.peers
+    }
+
+    pub fn add_peer(&mut self, node_id: &str, address: &str) {
+        if self.peers.iter().all(|peer| peer.node_id != node_id) {
+            self.peers.push(MockPeer {
+                node_id: node_id.to_string(),
+                address: address.to_string(),
+            });
+        }
+    }
+
+    pub fn remove_peer(&mut self, node_id: &str) {
+        self.peers.retain(|peer| peer.node_id != node_id);
+    }
+
+    pub fn network_active(&self) -> bool {
+        self.network_active
+    }
+
+    /// Disconnect all the peers when deactivated, as ckb does
+    pub fn set_network_active(&mut self, active: bool) {
+        self.network_active = active;
+        if !active {
+            self.peers.clear();
+        }
+    }
+
+    fn switch_main_chain(&mut self, new_tip: Byte32) {
+        // Walk back from the new tip to the fork point on the main chain
+        let mut attached = Vec::new();
+        let mut hash = new_tip;
+        while !self.is_main_chain(&hash) {
+            let block = &self.blocks[&hash];
+            attached.push(hash.clone());
+            hash = block.parent_hash();
+        }
+        let fork_number = self.blocks[&hash].number();
+
+        // The transactions of the detached blocks go back to the tx-pool
+        let detached = self.main_chain.split_off(fork_number as usize + 1);
+        for detached_hash in detached {
+            for tx in self.blocks[&detached_hash]
+                .transactions()
+                .into_iter()
+                .skip(1)
+            {
+                if self
+                    .tx_pool
+                    .iter()
+                    .all(|pending| pending.hash() != tx.hash())
+                {
+                    self.tx_pool.push(tx);
+                }
+            }
+        }
+
+        let mut committed = HashSet::new();
+        for attached_hash in attached.into_iter().rev() {
+            for tx in self.blocks[&attached_hash].transactions() {
+                committed.insert(tx.hash());
+            }
+            self.main_chain.push(attached_hash);
+        }
+        self.tx_pool.retain(|tx| !committed.contains(&tx.hash()));
+    }
+}
+
+pub(super) fn rpc_error(code: i64, message: &str, data: String) -> jsonrpc_core::Error {
+    jsonrpc_core::Error {
+        code: jsonrpc_core::ErrorCode::ServerError(code),
+        message: message.to_string(),
+        data: Some(serde_json::Value::String(data)),
+    }
+}
+
+pub(super) fn hash_of(hash: &Byte32) -> H256 {
+    hash.unpack()
+}
+
+fn epoch(number: BlockNumber) -> EpochNumberWithFraction {
+    EpochNumberWithFraction::new(number / EPOCH_LENGTH, number % EPOCH_LENGTH, EPOCH_LENGTH)
+}
+
+fn cellbase(number: BlockNumber) -> TransactionView {
+    TransactionBuilder::default()
+        .input(CellInput::new_cellbase_input(number))
+        .output(
+            CellOutput::new_builder()
+                .capacity(Capacity::shannons(0).pack())
+                .build(),
+        )
+        .output_data(Bytes::new().pack())
+        .witness(Bytes::new().pack())
+        .build()
+}
@@ -0,0 +1,343 @@
+mod chain;
+#[cfg(test)]
+mod tests;
+
+pub use chain::{MockChain, MockPeer};
+
+use crate::rpc::http_server::{self, HttpServer};
+use crate::{Node, RpcClient};
+use chain::hash_of;
+use ckb_jsonrpc_types::{BlockNumber, HeaderView, TransactionView};
+use ckb_types::{packed, prelude::*, H256};
+use serde::de::DeserializeOwned;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+static NEXT_MOCK_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// An in-process ckb node serving the subset of the RPC used by `RpcClient` and the testkit
+/// helpers, backed by a `MockChain` that tests can script, so that the helpers can be tested
+/// without a ckb binary.
+///
+/// The methods not implemented are answered with the "method not found" error, unless mocked via
+/// `MockChain::set_response`. The server stops when dropped.
+///
+/// ```ignore
+/// let mock = MockCkb::start();
+/// let node = mock.node();
+/// node.mine(3);
+/// assert_eq!(mock.chain().tip_number(), 3);
+/// ```
+pub struct MockCkb {
+    chain: Arc<Mutex<MockChain>>,
+    server: HttpServer,
+}
+
+impl MockCkb {
+    pub fn start() -> Self {
+        let node_id = format!("QmMockCkb{}", NEXT_MOCK_ID.fetch_add(1, Ordering::SeqCst));
+        let chain = Arc::new(Mutex::new(MockChain::new(node_id)));
+        let chain_clone = Arc::clone(&chain);
+        let handler = move |request: &serde_json::Value| handle(&chain_clone, request);
+        Self {
+            chain,
+            server: HttpServer::start(Arc::new(handler)),
+        }
+    }
+
+    /// The rpc url, e.g. "http://127.0.0.1:34567/"
+    pub fn url(&self) -> &str {
+        self.server.url()
+    }
+
+    pub fn rpc_client(&self) -> RpcClient {
+        RpcClient::new(self.url(), true)
+    }
+
+    /// The `Node` connected to the mock, without indexer
+    pub fn node(&self) -> Node {
+        Node::init_from_url(self.url(), PathBuf::new())
+    }
+
+    /// Lock the chain state to inspect or script it. Release the guard before calling the RPC,
+    /// otherwise the call blocks.
+    pub fn chain(&self) -> MutexGuard<'_, MockChain> {
+        self.chain.lock().unwrap()
+    }
+}
+
+type RpcResult = Result<serde_json::Value, jsonrpc_core::Error>;
+
+fn handle(chain: &Mutex<MockChain>, request: &serde_json::Value) -> serde_json::Value {
+    let method = request
+        .get("method")
+        .and_then(|method| method.as_str())
+        .unwrap_or_default();
+    let params = request
+        .get("params")
+        .cloned()
+        .unwrap_or(serde_json::Value::Null);
+    let mut chain = chain.lock().unwrap();
+    let result = match chain.response(method) {
+        Some(result) => Ok(result.clone()),
+        None => call(&mut chain, method, &params),
+    };
+    match result {
+        Ok(result) => http_server::success(request, result),
+        Err(error) => serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request.get("id").cloned().unwrap_or(serde_json::Value::Null),
+            "error": error,
+        }),
+    }
+}
+
+fn call(chain: &mut MockChain, method: &str, params: &serde_json::Value) -> RpcResult {
+    let result = match method {
+        // Chain
+        "get_block" => {
+            let hash = param::<H256>(params, 0)?.pack();
+            let block = chain
+                .block(&hash)
+                .filter(|_| chain.is_main_chain(&hash))
+                .cloned();
+            to_json(block.map(ckb_jsonrpc_types::BlockView::from))
+        }
+        "get_fork_block" => {
+            let hash = param::<H256>(params, 0)?.pack();
+            let block = chain
+                .block(&hash)
+                .filter(|_| !chain.is_main_chain(&hash))
+                .cloned();
+            to_json(block.map(ckb_jsonrpc_types::BlockView::from))
+        }
+        "get_block_by_number" => {
+            let number = param::<BlockNumber>(params, 0)?.value();
+            let block = chain.block_by_number(number).cloned();
+            to_json(block.map(ckb_jsonrpc_types::BlockView::from))
+        }
+        "get_header" => {
+            let hash = param::<H256>(params, 0)?.pack();
+            let header = chain
+                .block(&hash)
+                .filter(|_| chain.is_main_chain(&hash))
+                .map(|block| HeaderView::from(block.header()));
+            to_json(header)
+        }
+        "get_header_by_number" => {
+            let number = param::<BlockNumber>(params, 0)?.value();
+            let header = chain
+                .block_by_number(number)
+                .map(|block| HeaderView::from(block.header()));
+            to_json(header)
+        }
+        "get_block_hash" => {
+            let number = param::<BlockNumber>(params, 0)?.value();
+            let hash = chain
+                .block_by_number(number)
+                .map(|block| hash_of(&block.hash()));
+            to_json(hash)
+        }
+        "get_tip_header" => to_json(HeaderView::from(chain.tip_block().header())),
+        "get_tip_block_number" => to_json(BlockNumber::from(chain.tip_number())),
+        "get_transaction" => {
+            let hash = param::<H256>(params, 0)?.pack();
+            let tx_with_status = chain.get_transaction(&hash).map(|(tx, block_hash)| {
+                let (status, block_hash) = match block_hash {
+                    Some(block_hash) => ("committed", Some(hash_of(&block_hash))),
+                    None => ("pending", None),
+                };
+                serde_json::json!({
+                    "transaction": TransactionView::from(tx),
+                    "tx_status": {
+                        "status": status,
+                        "block_hash": block_hash,
+                    },
+                })
+            });
+            to_json(tx_with_status)
+        }
+        "get_consensus" => consensus(chain),
+
+        // Block production
+        "get_block_template" => to_json(chain.block_template()),
+        "submit_block" => {
+            let block = param::<ckb_jsonrpc_types::Block>(params, 1)?;
+            let hash = chain.process_block(packed::Block::from(block).into_view())?;
+            to_json(hash_of(&hash))
+        }
+        "process_block_without_verify" => {
+            let block = param::<ckb_jsonrpc_types::Block>(params, 0)?;
+            let hash = chain
+                .process_block(packed::Block::from(block).into_view())
+                .ok()
+                .map(|hash| hash_of(&hash));
+            to_json(hash)
+        }
+        // The dao field is not calculated, the template's is returned as is
+        "calculate_dao_field" => {
+            let template = param::<ckb_jsonrpc_types::BlockTemplate>(params, 0)?;
+            to_json(template.dao)
+        }
+        "truncate" => {
+            let hash = param::<H256>(params, 0)?.pack();
+            chain.truncate(&hash)?;
+            serde_json::Value::Null
+        }
+
+        // Pool
+        "send_transaction" => {
+            let tx = param::<ckb_jsonrpc_types::Transaction>(params, 0)?;
+            let hash = chain.send_transaction(packed::Transaction::from(tx).into_view())?;
+            to_json(hash_of(&hash))
+        }
+        "tx_pool_info" => {
+            let tip_block = chain.tip_block();
+            serde_json::json!({
+                "tip_hash": hash_of(&tip_block.hash()),
+                "tip_number": BlockNumber::from(tip_block.number()),
+                "pending": format!("{:#x}", chain.tx_pool().len()),
+                "proposed": "0x0",
+                "orphan": "0x0",
+                "total_tx_size": "0x0",
+                "total_tx_cycles": "0x0",
+                "min_fee_rate": "0x3e8",
+                "last_txs_updated_at": "0x0",
+            })
+        }
+        "get_raw_tx_pool" => {
+            let pending = chain
+                .tx_pool()
+                .iter()
+                .map(|tx| hash_of(&tx.hash()))
+                .collect::<Vec<_>>();
+            serde_json::json!({
+                "pending": pending,
+                "proposed": [],
+            })
+        }
+        "clear_tx_pool" => {
+            chain.clear_tx_pool();
+            serde_json::Value::Null
+        }
+        "remove_transaction" => {
+            let hash = param::<H256>(params, 0)?.pack();
+            to_json(chain.remove_transaction(&hash))
+        }
+
+        // Net
+        "local_node_info" => {
+            let node_id = chain.node_id();
+            serde_json::json!({
+                "version": "0.101.0",
+                "node_id": node_id,
+                "active": chain.network_active(),
+                "addresses": [{
+                    "address": format!("/ip4/0.0.0.0/tcp/8115/p2p/{}", node_id),
+                    "score": "0x1",
+                }],
+                "protocols": [],
+                "connections": format!("{:#x}", chain.peers().len()),
+            })
+        }
+        "get_peers" => {
+            let peers = chain
+                .peers()
+                .iter()
+                .map(|peer| {
+                    serde_json::json!({
+                        "version": "0.101.0",
+                        "node_id": peer.node_id,
+                        "addresses": [{
+                            "address": peer.address,
+                            "score": "0x1",
+                        }],
+                        "is_outbound": true,
+                        "connected_duration": "0x0",
+                        "last_ping_duration": null,
+                        "sync_state": null,
+                        "protocols": [],
+                    })
+                })
+                .collect::<Vec<_>>();
+            serde_json::Value::Array(peers)
+        }
+        "add_node" => {
+            let node_id = param::<String>(params, 0)?;
+            let address = param::<String>(params, 1)?;
+            if chain.network_active() {
+                chain.add_peer(&node_id, &address);
+            }
+            serde_json::Value::Null
+        }
+        "remove_node" => {
+            let node_id = param::<String>(params, 0)?;
+            chain.remove_peer(&node_id);
+            serde_json::Value::Null
+        }
+        "set_network_active" => {
+            let active = param::<bool>(params, 0)?;
+            chain.set_network_active(active);
+            serde_json::Value::Null
+        }
+        "get_banned_addresses" => serde_json::json!([]),
+        "clear_banned_addresses" => serde_json::Value::Null,
+
+        _ => {
+            let mut error = jsonrpc_core::Error::method_not_found();
+            error.data = Some(serde_json::Value::String(format!(
+                "{} is not implemented by MockCkb, mock it via MockChain::set_response",
+                method
+            )));
+            return Err(error);
+        }
+    };
+    Ok(result)
+}
+
+fn consensus(chain: &MockChain) -> serde_json::Value {
+    serde_json::json!({
+        "id": "ckb_mock",
+        "genesis_hash": hash_of(&chain.genesis_block().hash()),
+        "dao_type_hash": null,
+        "secp256k1_blake160_sighash_all_type_hash": null,
+        "secp256k1_blake160_multisig_all_type_hash": null,
+        "initial_primary_epoch_reward": "0x71afd498d000",
+        "secondary_epoch_reward": "0x37d0c8e28542",
+        "max_uncles_num": "0x2",
+        "orphan_rate_target": { "numer": "0x1", "denom": "0x28" },
+        "epoch_duration_target": "0x3840",
+        "tx_proposal_window": { "closest": "0x2", "farthest": "0xa" },
+        "proposer_reward_ratio": { "numer": "0x4", "denom": "0xa" },
+        "cellbase_maturity": "0x0",
+        "median_time_block_count": "0x25",
+        "max_block_cycles": "0xd09dc300",
+        "max_block_bytes": "0x91c08",
+        "block_version": "0x0",
+        "tx_version": "0x0",
+        "type_id_code_hash": "0x00000000000000000000000000000000000000000000000000545950455f4944",
+        "max_block_proposals_limit": "0x5dc",
+        "primary_epoch_reward_halving_interval": "0x2238",
+        "permanent_difficulty_in_dummy": true,
+        "hardfork_features": [],
+    })
+}
+
+// The positional param at `index`, the missing optional params are `null`
+fn param<T: DeserializeOwned>(
+    params: &serde_json::Value,
+    index: usize,
+) -> Result<T, jsonrpc_core::Error> {
+    let param = params
+        .get(index)
+        .cloned()
+        .unwrap_or(serde_json::Value::Null);
+    serde_json::from_value(param).map_err(|err| {
+        jsonrpc_core::Error::invalid_params(format!("invalid param {}, error: {}", index, err))
+    })
+}
+
+fn to_json<T: serde::Serialize>(value: T) -> serde_json::Value {
+    serde_json::to_value(value).expect("serialize the response")
+}
@@ -0,0 +1,233 @@
+use super::MockCkb;
+use crate::{BuildInstruction, Call, Node, Nodes};
+use ckb_jsonrpc_types::Status;
+use ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, TransactionBuilder, TransactionView},
+    packed::CellOutput,
+    prelude::*,
+    H256,
+};
+
+fn dummy_transaction(capacity: u64) -> TransactionView {
+    TransactionBuilder::default()
+        .output(
+            CellOutput::new_builder()
+                .capacity(Capacity::shannons(capacity).pack())
+                .build(),
+        )
+        .output_data(Bytes::new().pack())
+        .build()
+}
+
+// Two mocks sharing the blocks until `fixed_number`, then mining `n_blocks` blocks each on
+// their own forks
+fn forked_mocks(fixed_number: u64, n_blocks: u64) -> (MockCkb, MockCkb) {
+    let mock_a = MockCkb::start();
+    let mock_b = MockCkb::start();
+    mock_a.chain().mine(fixed_number);
+    for number in 1..=fixed_number {
+        let block = mock_a.chain().block_by_number(number).cloned().unwrap();
+        mock_b.chain().process_block(block).unwrap();
+    }
+    mock_b.chain().set_block_interval(2000);
+    mock_a.chain().mine(n_blocks);
+    mock_b.chain().mine(n_blocks);
+    (mock_a, mock_b)
+}
+
+#[test]
+fn test_mine_and_get_blocks() {
+    let mock = MockCkb::start();
+    let node = mock.node();
+    assert_eq!(node.get_tip_block_number(), 0);
+    assert_eq!(node.genesis_block(), mock.chain().genesis_block());
+
+    node.mine(3);
+    assert_eq!(node.get_tip_block_number(), 3);
+    assert_eq!(&node.get_tip_block(), mock.chain().tip_block());
+
+    let blocks = node.get_blocks_by_number(0..=3);
+    let headers = node.get_headers_by_number(0..=3);
+    assert_eq!(blocks.len(), 4);
+    for (number, (block, header)) in blocks.iter().zip(headers.iter()).enumerate() {
+        assert_eq!(block.number(), number as u64);
+        assert_eq!(block.header(), *header);
+        assert_eq!(Some(block), mock.chain().block_by_number(number as u64));
+    }
+}
+
+#[test]
+fn test_batch_with_unknown_method() {
+    let mock = MockCkb::start();
+    let results = mock.rpc_client().batch(vec![
+        Call::new("get_tip_block_number", serde_json::Value::Null),
+        Call::new("get_unknown", serde_json::Value::Null),
+    ]);
+    assert_eq!(results[0].as_ref().unwrap(), &serde_json::json!("0x0"));
+    assert!(results[1].is_err());
+
+    mock.chain()
+        .set_response("get_unknown", serde_json::json!("mocked"));
+    let results = mock
+        .rpc_client()
+        .batch(vec![Call::new("get_unknown", serde_json::Value::Null)]);
+    assert_eq!(results[0].as_ref().unwrap(), &serde_json::json!("mocked"));
+}
+
+#[test]
+fn test_send_transaction() {
+    let mock = MockCkb::start();
+    let node = mock.node();
+    let tx = dummy_transaction(100);
+    let hash = node.submit_transaction(&tx);
+    assert_eq!(hash, tx.hash());
+    assert_eq!(node.rpc_client().tx_pool_info().pending.value(), 1);
+    assert!(node
+        .rpc_client()
+        .send_transaction_result(tx.data().into())
+        .is_err());
+
+    node.mine(1);
+    let tx_status = node.rpc_client().get_transaction(tx.hash()).unwrap();
+    let tip_hash: H256 = node.get_tip_block().hash().unpack();
+    assert_eq!(tx_status.tx_status.status, Status::Committed);
+    assert_eq!(tx_status.tx_status.block_hash, Some(tip_hash));
+    assert!(mock.chain().tx_pool().is_empty());
+}
+
+#[test]
+fn test_reorg_returns_transactions_to_pool() {
+    let (mock_a, mock_b) = forked_mocks(2, 1);
+    let tx = dummy_transaction(100);
+    mock_a.chain().send_transaction(tx.clone()).unwrap();
+    mock_a.chain().mine(1);
+    assert!(mock_a.chain().tx_pool().is_empty());
+
+    // Switch mock_a to the longer fork of mock_b, which does not commit tx
+    mock_b.chain().mine(2);
+    let node_a = mock_a.node();
+    node_a.pull_node(&mock_b.node()).unwrap();
+    assert_eq!(mock_a.chain().tip_block(), mock_b.chain().tip_block());
+    assert_eq!(mock_a.chain().tx_pool(), &[tx]);
+}
+
+#[test]
+fn test_pull_node_with_common_prefix() {
+    let source = MockCkb::start();
+    let target = MockCkb::start();
+    source.chain().mine(250);
+
+    let target_node = target.node();
+    target_node.pull_node(&source.node()).unwrap();
+    assert_eq!(target.chain().tip_block(), source.chain().tip_block());
+}
+
+#[test]
+fn test_pull_node_across_fork() {
+    let (source, target) = forked_mocks(120, 5);
+    source.chain().mine(10);
+    assert_ne!(
+        target.chain().block_by_number(125),
+        source.chain().block_by_number(125)
+    );
+
+    let target_node = target.node();
+    target_node.pull_node(&source.node()).unwrap();
+    assert_eq!(target.chain().tip_block(), source.chain().tip_block());
+    assert!(target
+        .chain()
+        .block_by_number(121)
+        .map(|block| source.chain().is_main_chain(&block.hash()))
+        .unwrap());
+}
+
+#[test]
+fn test_get_fixed_header() {
+    let (mock_a, mock_b) = forked_mocks(150, 30);
+    let mock_c = MockCkb::start();
+    let nodes = Nodes::from(vec![mock_a.node(), mock_b.node()]);
+    let fixed_header = nodes.get_fixed_header();
+    assert_eq!(fixed_header.number(), 150);
+    assert_eq!(
+        &fixed_header,
+        &mock_a.chain().block_by_number(150).unwrap().header()
+    );
+
+    // Only the genesis block is shared with mock_c
+    let nodes = Nodes::from(vec![mock_a.node(), mock_b.node(), mock_c.node()]);
+    assert_eq!(nodes.get_fixed_header().number(), 0);
+}
+
+#[test]
+fn test_build_according_to_instructions() {
+    let mock = MockCkb::start();
+    let node = mock.node();
+    let tx = dummy_transaction(100);
+    let timestamp = mock.chain().genesis_block().timestamp() + 123_456;
+    let instructions = vec![
+        BuildInstruction::Commit {
+            template_number: 2,
+            transaction: tx.clone(),
+        },
+        BuildInstruction::HeaderTimestamp {
+            template_number: 3,
+            timestamp,
+        },
+        BuildInstruction::ProcessWithoutVerify { template_number: 4 },
+    ];
+    node.build_according_to_instructions(5, instructions)
+        .unwrap();
+
+    assert_eq!(node.get_tip_block_number(), 5);
+    let block2 = node.get_block_by_number(2);
+    assert!(block2
+        .transactions()
+        .iter()
+        .any(|committed| committed.hash() == tx.hash()));
+    assert_eq!(node.get_block_by_number(3).timestamp(), timestamp);
+}
+
+#[test]
+fn test_indexer_follows_reorg() {
+    let mock = MockCkb::start();
+    let working_dir = tempfile::tempdir().unwrap();
+    let node = Node::init_from_url(mock.url(), working_dir.path().to_path_buf());
+    node.mine(3);
+    let tip = node.indexer().tip().unwrap();
+    assert_eq!(tip, Some((3, node.get_tip_block().hash())));
+
+    // Replace the blocks 2 and 3 by a longer fork
+    let block1 = node.get_block_by_number(1);
+    {
+        let mut chain = mock.chain();
+        chain.truncate(&block1.hash()).unwrap();
+        chain.set_block_interval(2000);
+        chain.mine(3);
+    }
+    let tip = node.indexer().tip().unwrap();
+    assert_eq!(tip, Some((4, node.get_tip_block().hash())));
+}
+
+#[test]
+fn test_p2p_connect_and_disconnect() {
+    let mock_a = MockCkb::start();
+    let mock_b = MockCkb::start();
+    let node_a = mock_a.node();
+    let node_b = mock_b.node();
+    assert_ne!(node_a.node_id(), node_b.node_id());
+
+    node_a.p2p_connect(&node_b);
+    assert!(node_a.is_p2p_connected(&node_b));
+    assert_eq!(
+        node_a.get_peer(&node_b).map(|peer| peer.node_id),
+        Some(node_b.node_id().to_string())
+    );
+
+    node_a.p2p_disconnect(&node_b);
+    assert!(!node_a.is_p2p_connected(&node_b));
+
+    node_a.p2p_connect(&node_b);
+    node_a.set_network_active(false);
+    assert!(!node_a.is_p2p_connected(&node_b));
+}
@@ -1,3 +1,5 @@
+#[cfg(unix)]
+use crate::util::temp_path;
 use crate::{Node, NodeOptions};
 use ckb_jsonrpc_types::TransactionTemplate;
 use ckb_types::{
@@ -6,6 +8,11 @@ use ckb_types::{
     prelude::*,
 };
 use std::collections::HashMap;
+#[cfg(unix)]
+use std::fs;
+
+// The number of blocks fetched in a batch request when pulling blocks from another node
+const PULL_BATCH_SIZE: BlockNumber = 100;
 
 #[derive(Debug, Clone)]
 pub enum BuildInstruction {
@@ -137,7 +144,7 @@ impl Node {
                 let updated_block: packed::Block = {
                     let dao_field = self
                         .rpc_client()
-                        .calculate_dao_field(template.clone())
+                        .try_calculate_dao_field(template.clone())
                         .map_err(|err| {
                             format!(
                                 "failed to calculate dao field, block number: {}, error: {}",
@@ -168,15 +175,21 @@ impl Node {
     }
 
     /// Return the cloned node with `node_name`.
+    ///
+    /// The database is copied from the source node if we own its process, otherwise the blocks
+    /// are replayed via RPC.
     pub fn clone_node(&self, node_name: &str) -> Node {
-        let mut target_node = {
-            let node_options = NodeOptions {
-                node_name: String::from(node_name),
-                ..self.node_options().clone()
-            };
-            let is_ckb2021 = self.rpc_client().ckb2021;
-            Node::init("cloned_node", node_options, is_ckb2021)
-        };
+        #[cfg(unix)]
+        {
+            if self.pid().is_some() {
+                return self.clone_node_by_copying_database(node_name);
+            }
+        }
+
+        let mut target_node = self.init_cloned_node(NodeOptions {
+            node_name: String::from(node_name),
+            ..self.node_options().clone()
+        });
         target_node.start();
 
         target_node
@@ -186,27 +199,141 @@ impl Node {
         target_node
     }
 
+    #[cfg(unix)]
+    fn clone_node_by_copying_database(&self, node_name: &str) -> Node {
+        let database = temp_path("cloned_node_db", node_name);
+        self.copy_database(&database);
+        let mut target_node = self.init_cloned_node(NodeOptions {
+            node_name: String::from(node_name),
+            initial_database: database.clone(),
+            ..self.node_options().clone()
+        });
+        let _ = fs::remove_dir_all(&database);
+        target_node.start();
+        target_node
+    }
+
+    // Initialize the cloned node under the case of the source node, so that its files are
+    // collected and removed along with the case's other nodes by `CaseArtifacts`
+    fn init_cloned_node(&self, node_options: NodeOptions) -> Node {
+        let case_name = if self.case_name().is_empty() {
+            "cloned_node"
+        } else {
+            self.case_name()
+        };
+        let is_ckb2021 = self.rpc_client().ckb2021;
+        Node::init(case_name, node_options, is_ckb2021)
+    }
+
     pub fn pull_node(&self, source_node: &Node) -> Result<(), String> {
         assert!(self.get_tip_block_number() <= source_node.get_tip_block_number());
         let min_tip_number = self.get_tip_block_number();
         let max_tip_number = source_node.get_tip_block_number();
         let mut fixed_number = min_tip_number;
 
-        for number in (0..=min_tip_number).rev() {
-            if self.rpc_client().get_block_hash(number)
-                == source_node.rpc_client().get_block_hash(number)
-            {
-                fixed_number = number;
+        // Search the highest common block backward, a batch of headers at a time
+        let mut end = min_tip_number;
+        loop {
+            let start = end.saturating_sub(PULL_BATCH_SIZE - 1);
+            let headers = self.get_headers_by_number(start..=end);
+            let source_headers = source_node.get_headers_by_number(start..=end);
+            let common = headers
+                .iter()
+                .zip(source_headers.iter())
+                .rev()
+                .find(|(header, source_header)| header.hash() == source_header.hash());
+            if let Some((header, _)) = common {
+                fixed_number = header.number();
+                break;
+            }
+            if start == 0 {
                 break;
             }
+            end = start - 1;
         }
 
-        for number in fixed_number + 1..=max_tip_number {
-            let block = source_node.get_block_by_number(number);
-            self.rpc_client()
-                .submit_block(block.number().to_string(), block.data().into())
-                .map_err(|err| err.to_string())?;
+        let mut start = fixed_number + 1;
+        while start <= max_tip_number {
+            let end = (start + PULL_BATCH_SIZE - 1).min(max_tip_number);
+            for block in source_node.get_blocks_by_number(start..=end) {
+                self.rpc_client()
+                    .submit_block(block.number().to_string(), block.data().into())
+                    .map_err(|err| err.to_string())?;
+            }
+            start = end + 1;
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{CaseArtifacts, Node, NodeOptions};
+    use flate2::read::GzDecoder;
+    use std::fs::File;
+    use std::path::{Path, PathBuf};
+
+    fn node_options(node_name: &str) -> NodeOptions {
+        let testdata =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../ckb-integration-test/testdata");
+        NodeOptions::builder()
+            .node_name(node_name)
+            .ckb_binary("ckb")
+            .initial_database(testdata.join("db/empty"))
+            .chain_spec(testdata.join("spec/ckb2021"))
+            .app_config(testdata.join("config/ckb2021"))
+            .build()
+    }
+
+    fn tarball_entries(tarball: &Path) -> Vec<PathBuf> {
+        let file = File::open(tarball)
+            .unwrap_or_else(|err| panic!("failed to open {}, error: {}", tarball.display(), err));
+        tar::Archive::new(GzDecoder::new(file))
+            .entries()
+            .expect("read tarball entries")
+            .map(|entry| {
+                entry
+                    .expect("read tarball entry")
+                    .path()
+                    .unwrap()
+                    .into_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cloned_node_is_bundled_with_its_case() {
+        let case_name = "test_cloned_node_is_bundled";
+        let source = Node::init(case_name, node_options("source"), true);
+        let cloned = source.init_cloned_node(node_options("cloned"));
+        assert_eq!(cloned.case_name(), case_name);
+
+        let artifacts_dir = tempfile::tempdir()
+            .unwrap_or_else(|err| panic!("failed to create tempdir, error: {}", err));
+        let tarball = CaseArtifacts::new(case_name)
+            .bundle(artifacts_dir.path())
+            .unwrap_or_else(|err| panic!("failed to bundle artifacts, error: {}", err));
+        let entries = tarball_entries(&tarball);
+        for node_name in &["source", "cloned"] {
+            let expected = Path::new(case_name).join(node_name).join("ckb.toml");
+            assert!(
+                entries.contains(&expected),
+                "{} is missing in {:?}",
+                expected.display(),
+                entries
+            );
+        }
+    }
+
+    #[test]
+    fn test_cloned_node_working_dir_is_removed_with_its_case() {
+        let case_name = "test_cloned_node_working_dir_is_removed";
+        let source = Node::init(case_name, node_options("source"), true);
+        let cloned = source.init_cloned_node(node_options("cloned"));
+        assert!(cloned.working_dir().exists());
+
+        CaseArtifacts::new(case_name).remove_working_dirs();
+        assert!(!source.working_dir().exists());
+        assert!(!cloned.working_dir().exists());
+    }
+}
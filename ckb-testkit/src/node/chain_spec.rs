@@ -0,0 +1,345 @@
+use ckb_types::{
+    core::{Capacity, EpochNumber, EpochNumberWithFraction},
+    packed::Script,
+};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const ALWAYS_SUCCESS: &[u8] = include_bytes!("cells/always_success");
+// The type-id hash of the genesis always-success cell, which is also the genesis cell lock,
+// system cells lock and bootstrap lock.
+const ALWAYS_SUCCESS_TYPE_HASH: &str =
+    "0x6283a479a3cf5d4276cd93594de9f1827ab9b55c7b05b3d28e4c2e0a696cfefd";
+
+/// The activation epochs of the hardfork features.
+#[derive(Debug, Clone, Copy)]
+pub struct HardForkEpochs {
+    pub rfc_0028: EpochNumber,
+    pub rfc_0029: EpochNumber,
+    pub rfc_0030: EpochNumber,
+    pub rfc_0031: EpochNumber,
+    pub rfc_0032: EpochNumber,
+    pub rfc_0036: EpochNumber,
+}
+
+impl HardForkEpochs {
+    /// Activate all the hardfork features at `epoch_number`.
+    pub fn all(epoch_number: EpochNumber) -> Self {
+        Self {
+            rfc_0028: epoch_number,
+            rfc_0029: epoch_number,
+            rfc_0030: epoch_number,
+            rfc_0031: epoch_number,
+            rfc_0032: epoch_number,
+            rfc_0036: epoch_number,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SystemCell {
+    path: PathBuf,
+    create_type_id: bool,
+}
+
+/// Emit a chain spec directory, which contains "integration.toml" and its "cells", from typed
+/// parameters.
+///
+/// The default is identical to "testdata/spec/ckb2021", so the generated spec has the same
+/// genesis block and can be used together with the initial databases generated by it, as long as
+/// no issued cells or extra system cells are added.
+///
+/// The genesis always-success cell is always the first user-defined system cell, i.e.
+/// `SYSTEM_CELL_ALWAYS_SUCCESS_INDEX`, the extra system cells follow it.
+#[derive(Debug, Clone)]
+pub struct ChainSpecBuilder {
+    genesis_epoch_length: u64,
+    cellbase_maturity: EpochNumberWithFraction,
+    permanent_difficulty_in_dummy: bool,
+    hardfork: Option<HardForkEpochs>,
+    issued_cells: Vec<(Capacity, Script)>,
+    extra_system_cells: Vec<SystemCell>,
+}
+
+impl Default for ChainSpecBuilder {
+    fn default() -> Self {
+        Self {
+            genesis_epoch_length: 1000,
+            cellbase_maturity: EpochNumberWithFraction::from_full_value(0),
+            permanent_difficulty_in_dummy: true,
+            hardfork: Some(HardForkEpochs::all(3)),
+            issued_cells: Vec::new(),
+            extra_system_cells: Vec::new(),
+        }
+    }
+}
+
+impl ChainSpecBuilder {
+    /// The same as "testdata/spec/ckb2021".
+    pub fn ckb2021() -> Self {
+        Default::default()
+    }
+
+    /// The same as "testdata/spec/ckb2019", without `[params.hardfork]`, which is unknown for
+    /// ckb v0.43.
+    pub fn ckb2019() -> Self {
+        Self::default().hardfork(None)
+    }
+
+    pub fn genesis_epoch_length(mut self, genesis_epoch_length: u64) -> Self {
+        self.genesis_epoch_length = genesis_epoch_length;
+        self
+    }
+
+    pub fn cellbase_maturity(mut self, cellbase_maturity: EpochNumberWithFraction) -> Self {
+        self.cellbase_maturity = cellbase_maturity;
+        self
+    }
+
+    pub fn permanent_difficulty_in_dummy(mut self, permanent_difficulty_in_dummy: bool) -> Self {
+        self.permanent_difficulty_in_dummy = permanent_difficulty_in_dummy;
+        self
+    }
+
+    /// Set the activation epochs of the hardfork features, `None` means omitting
+    /// `[params.hardfork]`.
+    pub fn hardfork(mut self, hardfork: Option<HardForkEpochs>) -> Self {
+        self.hardfork = hardfork;
+        self
+    }
+
+    /// Issue a genesis cell with `capacity` to `lock`. Note that it changes the genesis block.
+    pub fn issued_cell(mut self, capacity: Capacity, lock: Script) -> Self {
+        self.issued_cells.push((capacity, lock));
+        self
+    }
+
+    /// Add the file at `path` as a system cell. Note that it changes the genesis block.
+    pub fn extra_system_cell<P: Into<PathBuf>>(mut self, path: P, create_type_id: bool) -> Self {
+        self.extra_system_cells.push(SystemCell {
+            path: path.into(),
+            create_type_id,
+        });
+        self
+    }
+
+    /// Write "integration.toml" and its cells into `spec_dir`.
+    pub fn build(&self, spec_dir: &Path) {
+        let cells_dir = spec_dir.join("cells");
+        fs::create_dir_all(&cells_dir).unwrap_or_else(|err| {
+            panic!(
+                "failed to create dir \"{}\", error: {}",
+                cells_dir.display(),
+                err
+            )
+        });
+        let always_success = cells_dir.join("always_success");
+        fs::write(&always_success, ALWAYS_SUCCESS).unwrap_or_else(|err| {
+            panic!(
+                "failed to write {}, error: {}",
+                always_success.display(),
+                err
+            )
+        });
+        for cell in self.extra_system_cells.iter() {
+            let target = cells_dir.join(cell.file_name());
+            fs::copy(&cell.path, &target).unwrap_or_else(|err| {
+                panic!(
+                    "failed to copy {} to {}, error: {}",
+                    cell.path.display(),
+                    target.display(),
+                    err
+                )
+            });
+        }
+
+        let spec = spec_dir.join("integration.toml");
+        fs::write(&spec, self.render())
+            .unwrap_or_else(|err| panic!("failed to write {}, error: {}", spec.display(), err));
+    }
+
+    fn render(&self) -> String {
+        let mut issued_cells = String::new();
+        if self.issued_cells.is_empty() {
+            issued_cells.push_str("issued_cells = []\n");
+        }
+        let mut issued_cells_tables = String::new();
+        for (capacity, lock) in self.issued_cells.iter() {
+            let lock: ckb_jsonrpc_types::Script = lock.clone().into();
+            let lock = serde_json::to_value(&lock).expect("serialize lock script");
+            writeln!(
+                issued_cells_tables,
+                "[[genesis.issued_cells]]\ncapacity = {}\nlock.code_hash = {}\nlock.args = {}\nlock.hash_type = {}\n",
+                capacity.as_u64(),
+                lock["code_hash"],
+                lock["args"],
+                lock["hash_type"],
+            )
+            .unwrap();
+        }
+
+        let mut extra_system_cells = String::new();
+        for cell in self.extra_system_cells.iter() {
+            writeln!(
+                extra_system_cells,
+                "[[genesis.system_cells]]\nfile = {{ file = \"cells/{}\" }}\ncreate_type_id = {}",
+                cell.file_name(),
+                cell.create_type_id
+            )
+            .unwrap();
+        }
+
+        let hardfork = match self.hardfork {
+            Some(epochs) => format!(
+                "\n[params.hardfork]\nrfc_0028 = {}\nrfc_0029 = {}\nrfc_0030 = {}\nrfc_0031 = {}\nrfc_0032 = {}\nrfc_0036 = {}\n",
+                epochs.rfc_0028,
+                epochs.rfc_0029,
+                epochs.rfc_0030,
+                epochs.rfc_0031,
+                epochs.rfc_0032,
+                epochs.rfc_0036,
+            ),
+            None => String::new(),
+        };
+
+        format!(
+            r#"name = "ckb_integration_test"
+
+[genesis]
+version = 0
+parent_hash = "0x0000000000000000000000000000000000000000000000000000000000000000"
+timestamp = 0
+compact_target = 0x20010000
+uncles_hash = "0x0000000000000000000000000000000000000000000000000000000000000000"
+{issued_cells}nonce = "0x0"
+
+[genesis.genesis_cell]
+message = ""
+
+[genesis.genesis_cell.lock]
+code_hash = "{always_success_type_hash}"
+args = "0x"
+hash_type = "type"
+
+# An array list paths to system cell files, which is absolute or relative to
+# the directory containing this config file.
+[[genesis.system_cells]]
+file = {{ bundled = "specs/cells/secp256k1_blake160_sighash_all" }}
+create_type_id = true
+[[genesis.system_cells]]
+file = {{ bundled = "specs/cells/dao" }}
+create_type_id = true
+[[genesis.system_cells]]
+file = {{ bundled = "specs/cells/secp256k1_data" }}
+create_type_id = false
+[[genesis.system_cells]]
+file = {{ bundled = "specs/cells/secp256k1_blake160_multisig_all" }}
+create_type_id = true
+[[genesis.system_cells]]
+file = {{ file = "cells/always_success" }}
+create_type_id = true
+{extra_system_cells}
+[genesis.system_cells_lock]
+code_hash = "{always_success_type_hash}"
+args = "0x"
+hash_type = "type"
+
+# Dep group cells
+[[genesis.dep_groups]]
+name = "secp256k1_blake160_sighash_all"
+files = [
+  {{ bundled = "specs/cells/secp256k1_data" }},
+  {{ bundled = "specs/cells/secp256k1_blake160_sighash_all" }}
+]
+[[genesis.dep_groups]]
+name = "secp256k1_blake160_multisig_all"
+files = [
+  {{ bundled = "specs/cells/secp256k1_data" }},
+  {{ bundled = "specs/cells/secp256k1_blake160_multisig_all" }}
+]
+
+[genesis.bootstrap_lock]
+code_hash = "{always_success_type_hash}"
+args = "0x"
+hash_type = "type"
+
+{issued_cells_tables}[params]
+initial_primary_epoch_reward = 1_917_808_21917808
+secondary_epoch_reward = 613_698_63013698
+max_block_cycles = 10_000_000_000
+cellbase_maturity = {cellbase_maturity}
+primary_epoch_reward_halving_interval = 8760
+epoch_duration_target = 14400
+genesis_epoch_length = {genesis_epoch_length}
+permanent_difficulty_in_dummy = {permanent_difficulty_in_dummy}
+{hardfork}
+[pow]
+func = "Dummy"
+"#,
+            issued_cells = issued_cells,
+            always_success_type_hash = ALWAYS_SUCCESS_TYPE_HASH,
+            extra_system_cells = extra_system_cells,
+            issued_cells_tables = issued_cells_tables,
+            cellbase_maturity = self.cellbase_maturity.full_value(),
+            genesis_epoch_length = self.genesis_epoch_length,
+            permanent_difficulty_in_dummy = self.permanent_difficulty_in_dummy,
+            hardfork = hardfork,
+        )
+    }
+}
+
+impl SystemCell {
+    fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .unwrap_or_else(|| panic!("invalid system cell path {}", self.path.display()))
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChainSpecBuilder;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn testdata_spec_dir(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../ckb-integration-test/testdata/spec")
+            .join(name)
+    }
+
+    fn read(path: PathBuf) -> Vec<u8> {
+        fs::read(&path)
+            .unwrap_or_else(|err| panic!("failed to read {}, error: {}", path.display(), err))
+    }
+
+    #[test]
+    fn test_default_matches_testdata() {
+        for (name, builder) in [
+            ("ckb2021", ChainSpecBuilder::default()),
+            ("ckb2019", ChainSpecBuilder::ckb2019()),
+        ] {
+            let expected_dir = testdata_spec_dir(name);
+            let expected = String::from_utf8(read(expected_dir.join("integration.toml"))).unwrap();
+            assert_eq!(builder.render(), expected, "spec {}", name);
+
+            let dir = tempfile::tempdir().unwrap();
+            builder.build(dir.path());
+            assert_eq!(
+                read(dir.path().join("integration.toml")),
+                expected.as_bytes(),
+                "spec {}",
+                name
+            );
+            assert_eq!(
+                read(dir.path().join("cells/always_success")),
+                read(expected_dir.join("cells/always_success")),
+                "spec {}",
+                name
+            );
+        }
+    }
+}
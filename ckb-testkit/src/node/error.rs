@@ -0,0 +1,53 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::time::Duration;
+
+/// The reason why a node failed to start, returned by [`Node::try_start`].
+///
+/// [`Node::try_start`]: crate::Node::try_start
+#[derive(Debug)]
+pub enum NodeStartError {
+    /// The ckb binary cannot be found.
+    BinaryMissing(PathBuf),
+    /// Failed to spawn the ckb process, or failed to wait for it.
+    Process(io::Error),
+    /// The ckb process exited before its RPC service came up.
+    Crashed {
+        status: ExitStatus,
+        log_tail: String,
+    },
+    /// The ckb process exited because its RPC or P2P port was already in use.
+    PortClash { log_tail: String },
+    /// The RPC service did not come up within the startup timeout.
+    RpcTimeout { timeout: Duration, log_tail: String },
+}
+
+impl fmt::Display for NodeStartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NodeStartError::BinaryMissing(binary) => {
+                write!(f, "ckb binary \"{}\" not found", binary.display())
+            }
+            NodeStartError::Process(err) => write!(f, "ckb process error: {}", err),
+            NodeStartError::Crashed { status, log_tail } => {
+                write!(
+                    f,
+                    "ckb process crashed, {}, run.log tail:\n{}",
+                    status, log_tail
+                )
+            }
+            NodeStartError::PortClash { log_tail } => {
+                write!(f, "ckb port already in use, run.log tail:\n{}", log_tail)
+            }
+            NodeStartError::RpcTimeout { timeout, log_tail } => write!(
+                f,
+                "timeout to wait for RPC up after {:?}, run.log tail:\n{}",
+                timeout, log_tail
+            ),
+        }
+    }
+}
+
+impl ::std::error::Error for NodeStartError {}
@@ -1,5 +1,7 @@
 mod always_success;
 mod builder;
+mod chain_spec;
+mod error;
 mod genesis_block_info;
 mod get_transaction;
 mod get_transaction_cycles;
@@ -9,9 +11,12 @@ mod node;
 mod node_options;
 mod p2p;
 mod rpc;
-#[cfg(feature = "with_subscribe")]
+mod snapshot;
 mod subscribe;
 
 pub use builder::BuildInstruction;
+pub use chain_spec::{ChainSpecBuilder, HardForkEpochs};
+pub use error::NodeStartError;
 pub use node::Node;
-pub use node_options::NodeOptions;
+pub use node_options::{NodeOptions, NodeOptionsBuilder};
+pub use snapshot::{Snapshot, SnapshotKey};
@@ -1,6 +1,8 @@
 use crate::error;
-use crate::rpc::RpcClient;
+use crate::node::NodeStartError;
+use crate::rpc::{AsyncRpcClient, RpcClient};
 use crate::util::{find_available_port, temp_path};
+use crate::LogWatcher;
 use crate::NodeOptions;
 use ckb_indexer::{
     indexer::Indexer,
@@ -11,15 +13,57 @@ use ckb_types::core::BlockView;
 use fs_extra::dir::CopyOptions;
 use reqwest::Url;
 use std::fs;
-use std::path::PathBuf;
-use std::process::{self, Child, Command, Stdio};
-use std::thread::sleep;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread::{self, sleep};
 use std::time::{Duration, Instant};
 
+const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+const LOG_TAIL_LINES: usize = 20;
+
 struct ProcessGuard(pub Child);
 
+impl ProcessGuard {
+    /// Send SIGTERM and wait for the process to exit within `timeout`. Return whether the
+    /// process exited by itself.
+    #[cfg(unix)]
+    fn terminate(&mut self, timeout: Duration) -> bool {
+        // A paused process cannot handle SIGTERM until it is continued
+        send_signal(&self.0, libc::SIGCONT);
+        send_signal(&self.0, libc::SIGTERM);
+        let start_time = Instant::now();
+        while start_time.elapsed() <= timeout {
+            match self.0.try_wait() {
+                Ok(Some(_)) => return true,
+                Ok(None) => sleep(Duration::from_millis(100)),
+                Err(err) => {
+                    error!("failed to wait ckb process, error: {}", err);
+                    return false;
+                }
+            }
+        }
+        false
+    }
+
+    #[cfg(not(unix))]
+    fn terminate(&mut self, _timeout: Duration) -> bool {
+        false
+    }
+}
+
 impl Drop for ProcessGuard {
     fn drop(&mut self) {
+        let pid = self.0.id();
+        // Left running for `CaseArtifacts` to dump its state and kill it
+        if thread::panicking() && crate::artifacts::keeps_on_panic(pid) {
+            return;
+        }
+        crate::artifacts::unset_pid(pid);
+        // The process may have exited already, e.g. stopped gracefully
+        if let Ok(Some(_)) = self.0.try_wait() {
+            return;
+        }
         let _x = self
             .0
             .kill()
@@ -28,36 +72,26 @@ impl Drop for ProcessGuard {
     }
 }
 
+#[cfg(unix)]
+fn send_signal(child: &Child, signal: libc::c_int) {
+    let ret = unsafe { libc::kill(child.id() as libc::pid_t, signal) };
+    if ret != 0 {
+        error!(
+            "failed to send signal {} to ckb process {}, error: {}",
+            signal,
+            child.id(),
+            io::Error::last_os_error()
+        );
+    }
+}
+
 pub struct Node {
+    pub(super) case_name: String,
     pub(super) node_options: NodeOptions,
 
     pub(super) working_dir: PathBuf,
     pub(super) rpc_client: RpcClient,
-
-    #[cfg(feature = "with_subscribe")]
-    pub(super) new_tip_block_subscriber:
-        Option<crate::subscribe::Handle<tokio::net::TcpStream, ckb_jsonrpc_types::BlockView>>,
-    #[cfg(feature = "with_subscribe")]
-    pub(super) new_tip_header_subscriber:
-        Option<crate::subscribe::Handle<tokio::net::TcpStream, ckb_jsonrpc_types::HeaderView>>,
-    #[cfg(feature = "with_subscribe")]
-    pub(super) new_transaction_subscriber: Option<
-        crate::subscribe::Handle<tokio::net::TcpStream, ckb_jsonrpc_types::PoolTransactionEntry>,
-    >,
-    #[cfg(feature = "with_subscribe")]
-    pub(super) proposed_transaction_subscriber: Option<
-        crate::subscribe::Handle<tokio::net::TcpStream, ckb_jsonrpc_types::PoolTransactionEntry>,
-    >,
-    #[cfg(feature = "with_subscribe")]
-    pub(super) rejected_transaction_subscriber: Option<
-        crate::subscribe::Handle<
-            tokio::net::TcpStream,
-            (
-                ckb_jsonrpc_types::PoolTransactionEntry,
-                ckb_jsonrpc_types::PoolTransactionReject,
-            ),
-        >,
-    >,
+    pub(super) startup_timeout: Duration,
 
     pub(super) p2p_address: Option<String>, // initialize when node start
     pub(super) consensus: Option<Consensus>, // initialize when node start
@@ -70,6 +104,7 @@ pub struct Node {
 impl Clone for Node {
     fn clone(&self) -> Node {
         Self {
+            case_name: self.case_name.clone(),
             node_options: self.node_options.clone(),
             working_dir: self.working_dir().clone(),
             rpc_client: self.rpc_client.clone(),
@@ -78,17 +113,8 @@ impl Clone for Node {
             genesis_block: self.genesis_block.clone(),
             node_id: self.node_id.clone(),
             indexer: self.indexer.clone(),
+            startup_timeout: self.startup_timeout,
             _guard: None,
-            #[cfg(feature = "with_subscribe")]
-            new_tip_block_subscriber: None,
-            #[cfg(feature = "with_subscribe")]
-            new_tip_header_subscriber: None,
-            #[cfg(feature = "with_subscribe")]
-            new_transaction_subscriber: None,
-            #[cfg(feature = "with_subscribe")]
-            proposed_transaction_subscriber: None,
-            #[cfg(feature = "with_subscribe")]
-            rejected_transaction_subscriber: None,
         }
     }
 }
@@ -99,43 +125,28 @@ impl Node {
         let rpc_port = find_available_port();
         let p2p_port = find_available_port();
         let working_dir = prepare_working_dir(&case_name, &node_options, rpc_port, p2p_port);
+        let rpc_url = format!("http://127.0.0.1:{}/", rpc_port);
+        crate::artifacts::register(&case_name, &node_options.node_name, &working_dir, &rpc_url);
+        crate::rpc::recorder::register_node(&rpc_url, &case_name, &node_options.node_name);
         Self {
+            case_name,
             node_options,
             working_dir,
-            rpc_client: RpcClient::new(&format!("http://127.0.0.1:{}/", rpc_port), is_ckb2021),
+            rpc_client: RpcClient::new(&rpc_url, is_ckb2021),
             p2p_address: None,
             consensus: None,
             genesis_block: None,
             node_id: None,
             indexer: None,
+            startup_timeout: DEFAULT_STARTUP_TIMEOUT,
             _guard: None,
-            #[cfg(feature = "with_subscribe")]
-            new_tip_block_subscriber: None,
-            #[cfg(feature = "with_subscribe")]
-            new_tip_header_subscriber: None,
-            #[cfg(feature = "with_subscribe")]
-            new_transaction_subscriber: None,
-            #[cfg(feature = "with_subscribe")]
-            proposed_transaction_subscriber: None,
-            #[cfg(feature = "with_subscribe")]
-            rejected_transaction_subscriber: None,
         }
     }
 
     pub fn init_from_url(rpc_url: &str, working_dir: PathBuf) -> Self {
         let mut rpc_client = RpcClient::new(rpc_url, true);
         let local_node_info = rpc_client.local_node_info();
-        let is_ckb2021 = {
-            let node_version = &local_node_info.version;
-            let minimal_2021_version = "0.44.0";
-            let is_ckb2021 = version_compare::VersionCompare::compare_to(
-                node_version,
-                minimal_2021_version,
-                &version_compare::CompOp::Ge,
-            )
-            .unwrap_or(true);
-            is_ckb2021
-        };
+        let is_ckb2021 = is_ckb2021_version(&local_node_info.version);
         if !is_ckb2021 {
             rpc_client = RpcClient::new(&rpc_url, is_ckb2021)
         }
@@ -174,6 +185,7 @@ impl Node {
         );
         Self {
             // TODO get p2p listen address via RPC
+            case_name: String::new(),
             node_options,
             working_dir,
             rpc_client,
@@ -182,24 +194,31 @@ impl Node {
             genesis_block: Some(genesis_block.into()),
             node_id: Some(node_id),
             indexer,
+            startup_timeout: DEFAULT_STARTUP_TIMEOUT,
             _guard: None,
-            #[cfg(feature = "with_subscribe")]
-            new_tip_block_subscriber: None,
-            #[cfg(feature = "with_subscribe")]
-            new_tip_header_subscriber: None,
-            #[cfg(feature = "with_subscribe")]
-            new_transaction_subscriber: None,
-            #[cfg(feature = "with_subscribe")]
-            proposed_transaction_subscriber: None,
-            #[cfg(feature = "with_subscribe")]
-            rejected_transaction_subscriber: None,
         }
     }
 
+    /// Start the node process, panic if it fails to start.
     pub fn start(&mut self) {
+        self.try_start().unwrap_or_else(|err| {
+            panic!(
+                "[Node {}] failed to start, log_path: {}, error: {}",
+                self.node_name(),
+                self.log_path().display(),
+                err
+            )
+        })
+    }
+
+    /// Start the node process, return the error if the node fails to come up within
+    /// `startup_timeout`. It is useful for cases which deliberately start a node with bad
+    /// chain spec or corrupted database.
+    pub fn try_start(&mut self) -> Result<(), NodeStartError> {
         let binary = &self.node_options.ckb_binary;
-        let mut child_process = Command::new(&binary)
+        let child_process = Command::new(&binary)
             .env("RUST_BACKTRACE", "full")
+            .envs(self.node_options.envs.iter().cloned())
             .args(&[
                 "-C",
                 &self.working_dir().to_string_lossy().to_string(),
@@ -207,18 +226,36 @@ impl Node {
                 "--ba-advanced",
                 "--overwrite-spec",
             ])
+            .args(&self.node_options.extra_args)
             .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::inherit())
+            .stdout(self.open_output_file(&self.stdout_path()))
+            .stderr(self.open_output_file(&self.stderr_path()))
             .spawn()
-            .unwrap_or_else(|err| {
-                panic!(
-                    "failed to start ckb process, binary: {}, error: {}",
-                    binary.display(),
-                    err
-                )
-            });
-        let local_node_info = self.wait_for_node_up(&mut child_process);
+            .map_err(|err| {
+                if err.kind() == io::ErrorKind::NotFound {
+                    NodeStartError::BinaryMissing(binary.clone())
+                } else {
+                    NodeStartError::Process(err)
+                }
+            })?;
+        crate::artifacts::set_pid(&self.working_dir, Some(child_process.id()));
+        // Kill the child process if it does not come up
+        let mut guard = ProcessGuard(child_process);
+        let local_node_info = match self.wait_for_node_up(&mut guard.0) {
+            Ok(local_node_info) => local_node_info,
+            Err(err) => {
+                crate::artifacts::set_pid(&self.working_dir, None);
+                return Err(err);
+            }
+        };
+
+        // The binary may have been swapped via `restart_with_binary`, the reported version
+        // decides which RPC representation to use.
+        let is_ckb2021 = is_ckb2021_version(&local_node_info.version);
+        if is_ckb2021 != self.rpc_client.ckb2021 {
+            self.rpc_client = RpcClient::new(self.rpc_client.url(), is_ckb2021);
+        }
+
         let consensus = self.rpc_client().get_consensus();
         let genesis_block = self.get_block_by_number(0);
         let indexer = {
@@ -229,7 +266,7 @@ impl Node {
 
         self.consensus = Some(consensus);
         self.genesis_block = Some(genesis_block);
-        self._guard = Some(ProcessGuard(child_process));
+        self._guard = Some(guard);
         self.node_id = Some(local_node_info.node_id);
         self.p2p_address = Some(local_node_info.addresses[0].address.clone());
         self.indexer = Some(indexer);
@@ -240,6 +277,56 @@ impl Node {
             self.p2p_address.as_ref().expect("checked"),
             self.log_path().display()
         );
+        Ok(())
+    }
+
+    /// Stop the node process and start it again on the same working directory.
+    ///
+    /// The database, ckb.toml and chain spec under `working_dir` are kept, so the restarted
+    /// node listens on the same RPC and P2P ports.
+    pub fn restart(&mut self) {
+        self.stop();
+        self.start();
+    }
+
+    /// Stop the node process and start it again on the same working directory, with another
+    /// ckb binary. It is useful for rolling-upgrade tests, e.g. opening a v0.43 database with a
+    /// v0.100 binary and back.
+    pub fn restart_with_binary<P: Into<PathBuf>>(&mut self, ckb_binary: P) {
+        self.stop();
+        self.node_options.ckb_binary = ckb_binary.into();
+        self.start();
+    }
+
+    /// Return the process id of the running ckb process.
+    pub fn pid(&self) -> Option<u32> {
+        self._guard.as_ref().map(|guard| guard.0.id())
+    }
+
+    /// Merge `overrides` into ckb.toml of the working directory, then restart the node if it is
+    /// running. `overrides` are in the form of `(dotted_key, value)`, e.g.
+    /// `("network.max_peers", 2.into())`.
+    pub fn update_config<I, K, V>(&mut self, overrides: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<toml_edit::Value>,
+    {
+        let overrides = overrides
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect::<Vec<_>>();
+        apply_app_config_overrides(&self.working_dir().join("ckb.toml"), &overrides);
+        self.node_options.app_config_overrides.extend(overrides);
+        if self._guard.is_some() {
+            self.restart();
+        }
+    }
+
+    /// The case which initialized the node via `Node::init`, empty for the nodes initialized
+    /// via `Node::init_from_url`.
+    pub fn case_name(&self) -> &str {
+        &self.case_name
     }
 
     pub fn node_name(&self) -> &str {
@@ -258,10 +345,59 @@ impl Node {
         self.working_dir().join("data/logs/run.log")
     }
 
+    /// The stdout of the ckb process, appended across restarts.
+    pub fn stdout_path(&self) -> PathBuf {
+        self.working_dir().join("stdout.log")
+    }
+
+    /// The stderr of the ckb process, appended across restarts. Panics during startup go here.
+    pub fn stderr_path(&self) -> PathBuf {
+        self.working_dir().join("stderr.log")
+    }
+
+    /// Return a `LogWatcher` watching the lines appended to run.log from now on.
+    pub fn log_watcher(&self) -> LogWatcher {
+        LogWatcher::from_end(self.log_path())
+    }
+
+    /// Panic if any `ERROR` or panic line was emitted into run.log or stderr since the node was
+    /// initialized.
+    pub fn assert_no_error_logs(&self) {
+        LogWatcher::new(self.log_path()).assert_no_errors();
+        let stderr = fs::read_to_string(self.stderr_path()).unwrap_or_default();
+        assert!(
+            !stderr.contains("panicked at"),
+            "[Node {}] panicked, stderr: {}",
+            self.node_name(),
+            stderr
+        );
+    }
+
+    fn open_output_file(&self, path: &Path) -> Stdio {
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map(Stdio::from)
+            .unwrap_or_else(|err| panic!("failed to open {}, error: {}", path.display(), err))
+    }
+
+    /// Set the timeout to wait for the RPC service to come up when starting the node.
+    /// The default is 60 seconds.
+    pub fn set_startup_timeout(&mut self, timeout: Duration) {
+        self.startup_timeout = timeout;
+    }
+
     pub fn rpc_client(&self) -> &RpcClient {
         &self.rpc_client
     }
 
+    /// Build an async rpc client of the node. Build it once and clone it to share the
+    /// connection pool, e.g. among the tasks sending transactions concurrently.
+    pub fn async_rpc_client(&self) -> AsyncRpcClient {
+        AsyncRpcClient::new(self.rpc_client.url(), self.rpc_client.ckb2021)
+    }
+
     /// P2p listen address, without node_id. E.g. "/ip4/0.0.0.0/tcp/9003"
     pub fn p2p_address(&self) -> String {
         self.p2p_address.as_ref().unwrap().clone()
@@ -303,43 +439,116 @@ impl Node {
             self.log_path().display(),
         );
         if self._guard.is_some() {
-            drop(self._guard.take())
+            drop(self._guard.take());
+            crate::artifacts::set_pid(&self.working_dir, None);
+        }
+
+        // Reset the states initialized when node start, so that callers won't see stale
+        // states after restarting. Dropping the indexer also releases its rocksdb lock.
+        self.p2p_address = None;
+        self.consensus = None;
+        self.genesis_block = None;
+        self.node_id = None;
+        self.indexer = None;
+    }
+
+    /// Stop the node process gracefully. Send SIGTERM and wait for the process to exit within
+    /// `timeout`, fall back to kill it if it doesn't. Return whether the node exited gracefully.
+    ///
+    /// On non-unix platforms the process is always killed. A node not started is regarded as
+    /// stopped gracefully.
+    pub fn stop_gracefully(&mut self, timeout: Duration) -> bool {
+        crate::info!(
+            "[Node {}] STOP GRACEFULLY log_path: {}",
+            self.node_name(),
+            self.log_path().display(),
+        );
+        let graceful = match self._guard.as_mut() {
+            Some(guard) => guard.terminate(timeout),
+            None => return true,
+        };
+        if !graceful {
+            error!(
+                "[Node {}] failed to stop gracefully within {:?}, kill it",
+                self.node_name(),
+                timeout
+            );
         }
+        self.stop();
+        graceful
     }
 
-    fn wait_for_node_up(&self, child_process: &mut Child) -> LocalNode {
+    /// Freeze the node process via SIGSTOP. The frozen node keeps its connections open but
+    /// doesn't respond to anything, which simulates a stalled peer.
+    #[cfg(unix)]
+    pub fn pause(&self) {
+        crate::info!("[Node {}] PAUSE", self.node_name());
+        let guard = self
+            ._guard
+            .as_ref()
+            .unwrap_or_else(|| panic!("[Node {}] is not started", self.node_name()));
+        send_signal(&guard.0, libc::SIGSTOP);
+    }
+
+    /// Continue the node process frozen by [`Node::pause`] via SIGCONT.
+    #[cfg(unix)]
+    pub fn resume(&self) {
+        crate::info!("[Node {}] RESUME", self.node_name());
+        let guard = self
+            ._guard
+            .as_ref()
+            .unwrap_or_else(|| panic!("[Node {}] is not started", self.node_name()));
+        send_signal(&guard.0, libc::SIGCONT);
+    }
+
+    fn wait_for_node_up(&self, child_process: &mut Child) -> Result<LocalNode, NodeStartError> {
         let start_time = Instant::now();
-        while start_time.elapsed() <= Duration::from_secs(60) {
+        while start_time.elapsed() <= self.startup_timeout {
             if let Ok(local_node_info) = self.rpc_client().inner().local_node_info() {
                 let _x = self.rpc_client().tx_pool_info();
-                return local_node_info;
+                return Ok(local_node_info);
             }
             match child_process.try_wait() {
                 Ok(None) => sleep(std::time::Duration::from_secs(1)),
                 Ok(Some(status)) => {
-                    error!(
-                        "{} node crashed, {}, log_path: {}",
-                        self.node_name(),
-                        status,
-                        self.log_path().display()
+                    let log_tail = format!(
+                        "{}\nstderr:\n{}",
+                        tail_of_file(&self.log_path(), LOG_TAIL_LINES),
+                        tail_of_file(&self.stderr_path(), LOG_TAIL_LINES)
                     );
-                    process::exit(status.code().unwrap());
-                }
-                Err(error) => {
-                    error!(
-                        "{} node crashed with reason: {}, log_path: {}",
-                        self.node_name(),
-                        error,
-                        self.log_path().display()
-                    );
-                    process::exit(255);
+                    if log_tail.contains("AddrInUse") || log_tail.contains("Address already in use")
+                    {
+                        return Err(NodeStartError::PortClash { log_tail });
+                    }
+                    return Err(NodeStartError::Crashed { status, log_tail });
                 }
+                Err(err) => return Err(NodeStartError::Process(err)),
             }
         }
-        panic!("timeout to start node process")
+        Err(NodeStartError::RpcTimeout {
+            timeout: self.startup_timeout,
+            log_tail: tail_of_file(&self.log_path(), LOG_TAIL_LINES),
+        })
     }
 }
 
+fn tail_of_file(path: &Path, n_lines: usize) -> String {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    let lines = content.lines().collect::<Vec<_>>();
+    let skip = lines.len().saturating_sub(n_lines);
+    lines[skip..].join("\n")
+}
+
+fn is_ckb2021_version(node_version: &str) -> bool {
+    let minimal_2021_version = "0.44.0";
+    version_compare::VersionCompare::compare_to(
+        node_version,
+        minimal_2021_version,
+        &version_compare::CompOp::Ge,
+    )
+    .unwrap_or(true)
+}
+
 fn prepare_working_dir(
     case_name: &str,
     node_options: &NodeOptions,
@@ -348,9 +557,9 @@ fn prepare_working_dir(
 ) -> PathBuf {
     let working_dir: PathBuf = temp_path(&case_name, &node_options.node_name);
     let target_database = &working_dir.join("data/db");
-    let source_database = node_options.initial_database;
-    let source_chain_spec = node_options.chain_spec;
-    let source_app_config = node_options.app_config;
+    let source_database = &node_options.initial_database;
+    let source_chain_spec = &node_options.chain_spec;
+    let source_app_config = &node_options.app_config;
 
     fs::create_dir_all(target_database).unwrap_or_else(|err| {
         panic!(
@@ -370,27 +579,31 @@ fn prepare_working_dir(
     .unwrap_or_else(|err| {
         panic!(
             "failed to copy {} to {}, error: {}",
-            source_database,
+            source_database.display(),
             target_database.display(),
             err
         )
     });
-    fs_extra::dir::copy(
-        source_chain_spec,
-        &working_dir,
-        &CopyOptions {
-            content_only: true,
-            ..Default::default()
-        },
-    )
-    .unwrap_or_else(|err| {
-        panic!(
-            "failed to copy {} to {}, error: {}",
+    if let Some(chain_spec_builder) = node_options.chain_spec_builder.as_ref() {
+        chain_spec_builder.build(&working_dir);
+    } else {
+        fs_extra::dir::copy(
             source_chain_spec,
-            working_dir.display(),
-            err
+            &working_dir,
+            &CopyOptions {
+                content_only: true,
+                ..Default::default()
+            },
         )
-    });
+        .unwrap_or_else(|err| {
+            panic!(
+                "failed to copy {} to {}, error: {}",
+                source_chain_spec.display(),
+                working_dir.display(),
+                err
+            )
+        });
+    }
     fs_extra::dir::copy(
         source_app_config,
         &working_dir,
@@ -402,7 +615,7 @@ fn prepare_working_dir(
     .unwrap_or_else(|err| {
         panic!(
             "failed to copy {} to {}, error: {}",
-            source_app_config,
+            source_app_config.display(),
             working_dir.display(),
             err
         )
@@ -417,6 +630,42 @@ fn prepare_working_dir(
         .replace("__P2P_PORT__", &p2p_port.to_string());
     fs::write(&app_config, content)
         .unwrap_or_else(|err| panic!("failed to write {}, error: {}", app_config.display(), err));
+    apply_app_config_overrides(&app_config, &node_options.app_config_overrides);
 
     working_dir
 }
+
+fn apply_app_config_overrides(app_config: &Path, overrides: &[(String, toml_edit::Value)]) {
+    if overrides.is_empty() {
+        return;
+    }
+    let content = fs::read_to_string(app_config)
+        .unwrap_or_else(|err| panic!("failed to read {}, error: {}", app_config.display(), err));
+    let mut document = content
+        .parse::<toml_edit::Document>()
+        .unwrap_or_else(|err| panic!("failed to parse {}, error: {}", app_config.display(), err));
+    for (key, value) in overrides {
+        let segments = key.split('.').collect::<Vec<_>>();
+        let (last, tables) = segments.split_last().expect("non-empty key");
+        let mut item = document.as_item_mut();
+        for segment in tables {
+            let table = item.as_table_like_mut().unwrap_or_else(|| {
+                panic!(
+                    "failed to override \"{}\", \"{}\" is not a table",
+                    key, segment
+                )
+            });
+            if table.get(segment).is_none() {
+                let mut new_table = toml_edit::Table::new();
+                new_table.set_implicit(true);
+                table.insert(segment, toml_edit::Item::Table(new_table));
+            }
+            item = table.get_mut(segment).expect("inserted above");
+        }
+        item.as_table_like_mut()
+            .unwrap_or_else(|| panic!("failed to override \"{}\", parent is not a table", key))
+            .insert(last, toml_edit::value(value.clone()));
+    }
+    fs::write(app_config, document.to_string())
+        .unwrap_or_else(|err| panic!("failed to write {}, error: {}", app_config.display(), err));
+}
@@ -1,10 +1,123 @@
+use crate::{ChainSpecBuilder, Snapshot};
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Default)]
 pub struct NodeOptions {
     pub node_name: String,
     pub ckb_binary: PathBuf,
-    pub initial_database: &'static str,
-    pub chain_spec: &'static str,
-    pub app_config: &'static str,
+    pub initial_database: PathBuf,
+    pub chain_spec: PathBuf,
+    /// Generate the chain spec via the builder instead of copying `chain_spec`.
+    pub chain_spec_builder: Option<ChainSpecBuilder>,
+    pub app_config: PathBuf,
+    /// Overrides merged into the copied ckb.toml, in the form of `(dotted_key, value)`, e.g.
+    /// `("network.max_peers", 2.into())`. Missing tables are created.
+    pub app_config_overrides: Vec<(String, toml_edit::Value)>,
+    /// Environment variables of the ckb process
+    pub envs: Vec<(String, String)>,
+    /// Additional arguments passed to `ckb run`
+    pub extra_args: Vec<String>,
+}
+
+impl NodeOptions {
+    pub fn builder() -> NodeOptionsBuilder {
+        Default::default()
+    }
+
+    /// Preset of a ckb2021 node, starting from an empty database with
+    /// "testdata/spec/ckb2021" and "testdata/config/ckb2021". `ckb_binary` is left to the caller.
+    pub fn ckb2021<S: Into<String>>(node_name: S) -> NodeOptionsBuilder {
+        Self::builder()
+            .node_name(node_name)
+            .initial_database("testdata/db/empty")
+            .chain_spec("testdata/spec/ckb2021")
+            .app_config("testdata/config/ckb2021")
+    }
+
+    /// Preset of a ckb2019 node, starting from an empty database with
+    /// "testdata/spec/ckb2019" and "testdata/config/ckb2019". `ckb_binary` is left to the caller.
+    pub fn ckb2019<S: Into<String>>(node_name: S) -> NodeOptionsBuilder {
+        Self::builder()
+            .node_name(node_name)
+            .initial_database("testdata/db/empty")
+            .chain_spec("testdata/spec/ckb2019")
+            .app_config("testdata/config/ckb2019")
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NodeOptionsBuilder {
+    inner: NodeOptions,
+}
+
+impl NodeOptionsBuilder {
+    pub fn node_name<S: Into<String>>(mut self, node_name: S) -> Self {
+        self.inner.node_name = node_name.into();
+        self
+    }
+
+    pub fn ckb_binary<P: Into<PathBuf>>(mut self, ckb_binary: P) -> Self {
+        self.inner.ckb_binary = ckb_binary.into();
+        self
+    }
+
+    pub fn initial_database<P: Into<PathBuf>>(mut self, initial_database: P) -> Self {
+        self.inner.initial_database = initial_database.into();
+        self
+    }
+
+    /// Start from the database snapshot, see `Node::snapshot`.
+    pub fn initial_snapshot(self, snapshot: &Snapshot) -> Self {
+        self.initial_database(snapshot.path())
+    }
+
+    pub fn chain_spec<P: Into<PathBuf>>(mut self, chain_spec: P) -> Self {
+        self.inner.chain_spec = chain_spec.into();
+        self
+    }
+
+    pub fn chain_spec_builder(mut self, chain_spec_builder: ChainSpecBuilder) -> Self {
+        self.inner.chain_spec_builder = Some(chain_spec_builder);
+        self
+    }
+
+    pub fn app_config<P: Into<PathBuf>>(mut self, app_config: P) -> Self {
+        self.inner.app_config = app_config.into();
+        self
+    }
+
+    /// Add an override of ckb.toml, see `NodeOptions::app_config_overrides`.
+    pub fn app_config_override<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<toml_edit::Value>,
+    {
+        self.inner
+            .app_config_overrides
+            .push((key.into(), value.into()));
+        self
+    }
+
+    pub fn env<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.inner.envs.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn extra_arg<S: Into<String>>(mut self, arg: S) -> Self {
+        self.inner.extra_args.push(arg.into());
+        self
+    }
+
+    pub fn build(self) -> NodeOptions {
+        assert!(
+            !self.inner.node_name.is_empty(),
+            "NodeOptions.node_name is required"
+        );
+        assert!(
+            self.inner.ckb_binary != PathBuf::new(),
+            "NodeOptions.ckb_binary is required, node_name: {}",
+            self.inner.node_name
+        );
+        self.inner
+    }
 }
@@ -1,7 +1,34 @@
 use crate::util::wait_until;
 use crate::Node;
+use ckb_jsonrpc_types::{RemoteNode, SyncState};
 
 impl Node {
+    /// The details of the connected peer `other`, e.g. its sync state and protocols
+    pub fn get_peer(&self, other: &Node) -> Option<RemoteNode> {
+        self.rpc_client()
+            .get_peers()
+            .into_iter()
+            .find(|peer| &peer.node_id == other.node_id())
+    }
+
+    pub fn sync_state(&self) -> SyncState {
+        self.rpc_client().sync_state()
+    }
+
+    /// Disable or enable all the p2p network activity
+    pub fn set_network_active(&self, state: bool) {
+        self.rpc_client().set_network_active(state)
+    }
+
+    pub fn clear_banned_addresses(&self) {
+        self.rpc_client().clear_banned_addresses()
+    }
+
+    /// Send ping messages to all the connected peers, to refresh `RemoteNode::last_ping_duration`
+    pub fn ping_peers(&self) {
+        self.rpc_client().ping_peers()
+    }
+
     pub fn is_p2p_connected(&self, other: &Node) -> bool {
         self.rpc_client()
             .get_peers()
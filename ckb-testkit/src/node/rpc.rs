@@ -1,12 +1,19 @@
-use crate::Node;
-use ckb_jsonrpc_types::TxPoolInfo;
+use crate::rpc::types::{
+    EntryCompleted, FeeRateStatistics, IndexerCell, IndexerOrder, IndexerSearchKey, IndexerTx,
+};
+use crate::{Node, RpcError};
+use ckb_jsonrpc_types::{BlockEconomicState, BlockReward, TransactionProof, TxPoolInfo};
 use ckb_types::{
-    core::{BlockNumber, BlockView, HeaderView, TransactionView},
-    packed::Byte32,
+    core::{BlockNumber, BlockView, Capacity, Cycle, HeaderView, TransactionView},
+    packed::{Byte32, Script},
 };
+use std::ops::RangeInclusive;
 use std::thread::sleep;
 use std::time::{Duration, Instant};
 
+// The page size of the indexer RPCs
+const INDEXER_PAGE_SIZE: u32 = 100;
+
 impl Node {
     pub fn submit_block(&self, block: &BlockView) -> Byte32 {
         let hash = self
@@ -67,6 +74,137 @@ impl Node {
             .into()
     }
 
+    /// Get the blocks of `numbers` via batch requests, which is much faster than calling
+    /// `get_block_by_number` one by one over a long range.
+    pub fn get_blocks_by_number(&self, numbers: RangeInclusive<BlockNumber>) -> Vec<BlockView> {
+        self.rpc_client()
+            .get_blocks_by_number(numbers)
+            .into_iter()
+            .map(|block| block.expect("block exists").into())
+            .collect()
+    }
+
+    /// Get the headers of `numbers` via batch requests.
+    pub fn get_headers_by_number(&self, numbers: RangeInclusive<BlockNumber>) -> Vec<HeaderView> {
+        self.rpc_client()
+            .get_headers_by_number(numbers)
+            .into_iter()
+            .map(|header| header.expect("header exists").into())
+            .collect()
+    }
+
+    pub fn get_transaction_proof(
+        &self,
+        tx_hashes: Vec<Byte32>,
+        block_hash: Option<Byte32>,
+    ) -> TransactionProof {
+        self.rpc_client()
+            .get_transaction_proof(tx_hashes, block_hash)
+    }
+
+    /// Return the hashes of the transactions proved by `tx_proof`
+    pub fn verify_transaction_proof(&self, tx_proof: TransactionProof) -> Vec<Byte32> {
+        self.rpc_client().verify_transaction_proof(tx_proof)
+    }
+
+    pub fn get_block_economic_state(&self, block_hash: Byte32) -> Option<BlockEconomicState> {
+        self.rpc_client().get_block_economic_state(block_hash)
+    }
+
+    pub fn get_cellbase_output_capacity_details(&self, block_hash: Byte32) -> Option<BlockReward> {
+        self.rpc_client()
+            .get_cellbase_output_capacity_details(block_hash)
+    }
+
+    /// Check whether the tx-pool accepts the transaction, without adding it into the tx-pool
+    pub fn test_tx_pool_accept(&self, transaction: &TransactionView) -> EntryCompleted {
+        self.rpc_client()
+            .test_tx_pool_accept(transaction.data().into())
+    }
+
+    pub fn try_test_tx_pool_accept(
+        &self,
+        transaction: &TransactionView,
+    ) -> Result<EntryCompleted, RpcError> {
+        self.rpc_client()
+            .try_test_tx_pool_accept(transaction.data().into())
+    }
+
+    pub fn estimate_cycles(&self, transaction: &TransactionView) -> Cycle {
+        self.rpc_client().estimate_cycles(transaction.data().into())
+    }
+
+    pub fn try_estimate_cycles(&self, transaction: &TransactionView) -> Result<Cycle, RpcError> {
+        self.rpc_client()
+            .try_estimate_cycles(transaction.data().into())
+    }
+
+    /// Remove the transaction and its descendants from the tx-pool, return whether it was in
+    /// the tx-pool
+    pub fn remove_transaction(&self, tx_hash: Byte32) -> bool {
+        self.rpc_client().remove_transaction(tx_hash)
+    }
+
+    pub fn clear_tx_pool(&self) {
+        self.rpc_client().clear_tx_pool()
+    }
+
+    pub fn get_fee_rate_statistics(&self, target: Option<u64>) -> Option<FeeRateStatistics> {
+        self.rpc_client().get_fee_rate_statistics(target)
+    }
+
+    /// All the live cells of the lock script, via the built-in indexer RPC `get_cells`
+    pub fn get_cells_by_lock(&self, lock: &Script) -> Vec<IndexerCell> {
+        let search_key = IndexerSearchKey::lock(lock.clone().into());
+        let mut cells = Vec::new();
+        let mut after = None;
+        loop {
+            let page = self.rpc_client().get_cells(
+                search_key.clone(),
+                IndexerOrder::Asc,
+                INDEXER_PAGE_SIZE,
+                after,
+            );
+            let is_last_page = page.objects.len() < INDEXER_PAGE_SIZE as usize;
+            cells.extend(page.objects);
+            if is_last_page {
+                return cells;
+            }
+            after = Some(page.last_cursor);
+        }
+    }
+
+    /// All the transactions of the lock script, via the built-in indexer RPC `get_transactions`
+    pub fn get_transactions_by_lock(&self, lock: &Script) -> Vec<IndexerTx> {
+        let search_key = IndexerSearchKey::lock(lock.clone().into());
+        let mut txs = Vec::new();
+        let mut after = None;
+        loop {
+            let page = self.rpc_client().get_transactions(
+                search_key.clone(),
+                IndexerOrder::Asc,
+                INDEXER_PAGE_SIZE,
+                after,
+            );
+            let is_last_page = page.objects.len() < INDEXER_PAGE_SIZE as usize;
+            txs.extend(page.objects);
+            if is_last_page {
+                return txs;
+            }
+            after = Some(page.last_cursor);
+        }
+    }
+
+    /// The total capacity of the live cells of the lock script, via the built-in indexer RPC
+    /// `get_cells_capacity`
+    pub fn get_cells_capacity_by_lock(&self, lock: &Script) -> Capacity {
+        let search_key = IndexerSearchKey::lock(lock.clone().into());
+        self.rpc_client()
+            .get_cells_capacity(search_key)
+            .map(|cells_capacity| cells_capacity.capacity.into())
+            .unwrap_or_else(Capacity::zero)
+    }
+
     /// The states of chain and txpool are updated asynchronously. Which means that the chain has
     /// updated to the newest tip but txpool not.
     /// get_tip_tx_pool_info wait to ensure the txpool update to the newest tip as well.
@@ -0,0 +1,168 @@
+use crate::Node;
+use ckb_types::core::BlockNumber;
+#[cfg(unix)]
+use fs_extra::dir::CopyOptions;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Identify a database snapshot. A snapshot can only be reused by nodes running the same binary
+/// version on the same chain spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotKey {
+    pub version: String,
+    pub spec_hash: String,
+    pub height: BlockNumber,
+}
+
+impl SnapshotKey {
+    /// `version` is the output of `ckb --version` or `local_node_info.version`, only the first
+    /// word, e.g. "0.100.0", is used.
+    pub fn new(version: &str, spec_file: &Path, height: BlockNumber) -> Self {
+        let spec = fs::read(spec_file)
+            .unwrap_or_else(|err| panic!("failed to read {}, error: {}", spec_file.display(), err));
+        let spec_hash = ckb_hash::blake2b_256(&spec)
+            .iter()
+            .take(8)
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        let version = version
+            .trim_start_matches("ckb ")
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        Self {
+            version,
+            spec_hash,
+            height,
+        }
+    }
+}
+
+/// A copy of `data/db` stored in the snapshot directory, which is specified by env
+/// `CKB_INTEGRATION_TEST_SNAPSHOTS`, defaulting to "ckb-it-snapshots" under the temp dir.
+///
+/// Pass `Snapshot::path` as `NodeOptions.initial_database` to start a node from the snapshot.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub name: String,
+    pub key: SnapshotKey,
+    path: PathBuf,
+}
+
+impl Snapshot {
+    /// Return the snapshot if it exists.
+    pub fn load(name: &str, key: &SnapshotKey) -> Option<Self> {
+        let snapshot = Self::new(name, key);
+        if snapshot.path.exists() {
+            Some(snapshot)
+        } else {
+            None
+        }
+    }
+
+    /// The path of the copied database.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn new(name: &str, key: &SnapshotKey) -> Self {
+        let root = env::var("CKB_INTEGRATION_TEST_SNAPSHOTS")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| env::temp_dir().join("ckb-it-snapshots"));
+        let dirname = format!("{}-{}-{}-{}", name, key.version, key.spec_hash, key.height);
+        Self {
+            name: name.to_string(),
+            key: key.clone(),
+            path: root.join(dirname).join("db"),
+        }
+    }
+}
+
+impl Node {
+    /// Copy `data/db` of the running node into the snapshot directory, keyed by the binary
+    /// version, the chain spec and the tip number. Return the existing one if any.
+    ///
+    /// The node is paused during copying, so the snapshot is as consistent as a database after
+    /// the process is killed, which ckb recovers from on start.
+    #[cfg(unix)]
+    pub fn snapshot(&self, name: &str) -> Snapshot {
+        let key = SnapshotKey::new(
+            &self.rpc_client().local_node_info().version,
+            &self.working_dir().join("integration.toml"),
+            self.get_tip_block_number(),
+        );
+        if let Some(snapshot) = Snapshot::load(name, &key) {
+            return snapshot;
+        }
+
+        let snapshot = Snapshot::new(name, &key);
+        // Copy into a temporary directory and then rename it, so that others never see a
+        // partial snapshot
+        let parent = snapshot.path.parent().expect("snapshot has parent");
+        fs::create_dir_all(parent).unwrap_or_else(|err| {
+            panic!(
+                "failed to create dir \"{}\", error: {}",
+                parent.display(),
+                err
+            )
+        });
+        let partial = tempfile::Builder::new()
+            .prefix("db.partial")
+            .tempdir_in(parent)
+            .expect("create tempdir failed")
+            .into_path();
+        self.copy_database(&partial);
+        if let Err(err) = fs::rename(&partial, &snapshot.path) {
+            // Created by others in the meantime
+            if !snapshot.path.exists() {
+                panic!(
+                    "failed to rename {} to {}, error: {}",
+                    partial.display(),
+                    snapshot.path.display(),
+                    err
+                );
+            }
+            let _ = fs::remove_dir_all(&partial);
+        }
+        crate::info!(
+            "[Node {}] SNAPSHOT {} at {}",
+            self.node_name(),
+            snapshot.path.display(),
+            key.height
+        );
+        snapshot
+    }
+
+    /// Copy `data/db` into `target` while the node is paused.
+    #[cfg(unix)]
+    pub(super) fn copy_database(&self, target: &Path) {
+        let source = self.working_dir().join("data/db");
+        fs::create_dir_all(target).unwrap_or_else(|err| {
+            panic!(
+                "failed to create dir \"{}\", error: {}",
+                target.display(),
+                err
+            )
+        });
+        self.pause();
+        let result = fs_extra::dir::copy(
+            &source,
+            target,
+            &CopyOptions {
+                content_only: true,
+                ..Default::default()
+            },
+        );
+        self.resume();
+        result.unwrap_or_else(|err| {
+            panic!(
+                "failed to copy {} to {}, error: {}",
+                source.display(),
+                target.display(),
+                err
+            )
+        });
+    }
+}
@@ -1,74 +1,39 @@
 use crate::subscribe::{
-    subscribe_new_tip_block, subscribe_new_tip_header, subscribe_new_transaction,
-    subscribe_proposed_transaction, subscribe_rejected_transaction, Handle as SubscribeHandle,
+    SubscriptionAddress, SubscriptionTopic, Subscriptions, SUBSCRIPTION_QUEUE_CAPACITY,
 };
 use crate::Node;
-use std::net::SocketAddr;
-use tokio::net::ToSocketAddrs;
+use std::time::Duration;
 
-impl Node {
-    pub async fn subscribe_new_tip_block<A: ToSocketAddrs>(&mut self, subscription_addr: A) {
-        let handle = subscribe_new_tip_block(subscription_addr).await.unwrap();
-        self.new_tip_block_subscriber = Some(handle);
-    }
-
-    pub fn new_tip_block_subscriber(
-        &mut self,
-    ) -> &mut SubscribeHandle<tokio::net::TcpStream, ckb_jsonrpc_types::BlockView> {
-        self.new_tip_block_subscriber.as_mut().unwrap()
-    }
-
-    pub async fn subscribe_new_tip_header<A: ToSocketAddrs>(&mut self, subscription_addr: A) {
-        let handle = subscribe_new_tip_header(subscription_addr).await.unwrap();
-        self.new_tip_header_subscriber = Some(handle);
-    }
-
-    pub fn new_tip_header_subscriber(
-        &mut self,
-    ) -> &mut SubscribeHandle<tokio::net::TcpStream, ckb_jsonrpc_types::HeaderView> {
-        self.new_tip_header_subscriber.as_mut().unwrap()
-    }
-
-    pub async fn subscribe_new_transaction<A: ToSocketAddrs>(&mut self, subscription_addr: A) {
-        let handle = subscribe_new_transaction(subscription_addr).await.unwrap();
-        self.new_transaction_subscriber = Some(handle);
-    }
+const SUBSCRIBE_TIMEOUT: Duration = Duration::from_secs(20);
 
-    pub fn new_transaction_subscriber(
-        &mut self,
-    ) -> &mut SubscribeHandle<tokio::net::TcpStream, ckb_jsonrpc_types::PoolTransactionEntry> {
-        self.new_transaction_subscriber.as_mut().unwrap()
-    }
-
-    pub async fn subscribe_proposed_transaction<A: ToSocketAddrs>(&mut self, subscription_addr: A) {
-        let handle = subscribe_proposed_transaction(subscription_addr)
-            .await
-            .unwrap();
-        self.proposed_transaction_subscriber = Some(handle);
-    }
-
-    pub fn proposed_transaction_subscriber(
-        &mut self,
-    ) -> &mut SubscribeHandle<tokio::net::TcpStream, ckb_jsonrpc_types::PoolTransactionEntry> {
-        self.proposed_transaction_subscriber.as_mut().unwrap()
-    }
-
-    pub async fn subscribe_rejected_transaction<A: ToSocketAddrs>(&mut self, subscription_addr: A) {
-        let handle = subscribe_rejected_transaction(subscription_addr)
-            .await
-            .unwrap();
-        self.rejected_transaction_subscriber = Some(handle);
-    }
-
-    pub fn rejected_transaction_subscriber(
-        &mut self,
-    ) -> &mut SubscribeHandle<
-        tokio::net::TcpStream,
-        (
-            ckb_jsonrpc_types::PoolTransactionEntry,
-            ckb_jsonrpc_types::PoolTransactionReject,
-        ),
-    > {
-        self.rejected_transaction_subscriber.as_mut().unwrap()
+impl Node {
+    /// Subscribe all the topics via the subscription service configured in ckb.toml, i.e.
+    /// `rpc.tcp_listen_address` or `rpc.ws_listen_address`. Only the events after subscribed are
+    /// received.
+    ///
+    /// Panic if neither is configured, configure one via `Node::update_config` or
+    /// `NodeOptions::app_config_overrides`.
+    pub fn subscriptions(&self) -> Subscriptions {
+        let app_config = self.working_dir().join("ckb.toml");
+        let address = SubscriptionAddress::from_app_config(&app_config).unwrap_or_else(|| {
+            panic!(
+                "[Node {}] neither rpc.tcp_listen_address nor rpc.ws_listen_address is set in {}",
+                self.node_name(),
+                app_config.display()
+            )
+        });
+        let subscriptions = Subscriptions::start(
+            address.clone(),
+            &SubscriptionTopic::ALL,
+            SUBSCRIPTION_QUEUE_CAPACITY,
+        );
+        if !subscriptions.wait_for_connected(SUBSCRIBE_TIMEOUT) {
+            panic!(
+                "[Node {}] timeout to subscribe {:?}",
+                self.node_name(),
+                address
+            );
+        }
+        subscriptions
     }
 }
@@ -6,6 +6,9 @@ use ckb_types::{
 };
 use std::collections::HashSet;
 
+// The number of headers fetched in a batch request when searching the fixed header
+const FIXED_HEADER_BATCH_SIZE: BlockNumber = 100;
+
 impl Nodes {
     pub fn waiting_for_sync(&self) -> Result<(), Vec<(&str, BlockNumber, Byte32)>> {
         crate::trace!("Nodes::waiting_for_sync start");
@@ -50,15 +53,27 @@ impl Nodes {
             .map(|node| node.get_tip_block_number())
             .min()
             .expect("at least 1 node");
-        for number in (0..=maximal_number).rev() {
-            let headers = self
+        // Search backward a batch of headers at a time
+        let mut end = maximal_number;
+        loop {
+            let start = end.saturating_sub(FIXED_HEADER_BATCH_SIZE - 1);
+            let nodes_headers = self
                 .nodes()
-                .map(|node| node.get_header_by_number(number))
-                .collect::<HashSet<_>>();
-            if headers.len() == 1 {
-                return headers.into_iter().collect::<Vec<_>>()[0].to_owned();
+                .map(|node| node.get_headers_by_number(start..=end))
+                .collect::<Vec<_>>();
+            for index in (0..=(end - start) as usize).rev() {
+                let headers = nodes_headers
+                    .iter()
+                    .map(|node_headers| node_headers[index].clone())
+                    .collect::<HashSet<_>>();
+                if headers.len() == 1 {
+                    return headers.into_iter().collect::<Vec<_>>()[0].to_owned();
+                }
+            }
+            if start == 0 {
+                unreachable!()
             }
+            end = start - 1;
         }
-        unreachable!()
     }
 }
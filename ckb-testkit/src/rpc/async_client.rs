@@ -0,0 +1,372 @@
+use super::v2019::AsyncInner2019;
+use super::v2021::AsyncInner2021;
+use super::{new_async_http_client, Call, RpcError};
+use ckb_jsonrpc_types::{
+    Alert, BannedAddr, Block, BlockTemplate, BlockView, CellWithStatus, ChainInfo, Consensus,
+    DryRunResult, EpochView, HeaderView, LocalNode, OutPoint, RawTxPool, RemoteNode, Timestamp,
+    Transaction, TransactionWithStatus, TxPoolInfo,
+};
+use ckb_types::core::{
+    BlockNumber as CoreBlockNumber, Capacity as CoreCapacity, EpochNumber as CoreEpochNumber,
+    Version as CoreVersion,
+};
+use ckb_types::{packed::Byte32, prelude::*};
+use std::time::{Duration, Instant};
+
+/// The async version of `RpcClient`, with the same ckb2019/ckb2021 compatibility conversions.
+///
+/// Requests are issued on the current tokio runtime without blocking it, so many requests can be
+/// in flight at the same time. Clones share the same connection pool.
+///
+/// ```ignore
+/// let rpc_client = node.async_rpc_client();
+/// let (tip, pool) = futures::join!(rpc_client.get_tip_header(), rpc_client.tx_pool_info());
+/// ```
+pub struct AsyncRpcClient {
+    pub ckb2021: bool,
+    inner2019: AsyncInner2019,
+    inner2021: AsyncInner2021,
+}
+
+impl Clone for AsyncRpcClient {
+    fn clone(&self) -> AsyncRpcClient {
+        AsyncRpcClient::with_client(
+            self.inner2021.url.as_str(),
+            self.ckb2021,
+            self.inner2021.client.clone(),
+        )
+    }
+}
+
+impl AsyncRpcClient {
+    pub fn new(uri: &str, ckb2021: bool) -> Self {
+        Self::with_client(uri, ckb2021, new_async_http_client())
+    }
+
+    pub fn with_client(uri: &str, ckb2021: bool, client: reqwest::Client) -> Self {
+        Self {
+            inner2019: AsyncInner2019::with_client(uri, client.clone()),
+            inner2021: AsyncInner2021::with_client(uri, client),
+            ckb2021,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        self.inner2021.url.as_ref()
+    }
+
+    pub fn inner(&self) -> &AsyncInner2021 {
+        &self.inner2021
+    }
+
+    pub async fn get_block(&self, hash: Byte32) -> Result<Option<BlockView>, RpcError> {
+        if self.ckb2021 {
+            Ok(self.inner2021.get_block(hash.unpack()).await?)
+        } else {
+            let block = self.inner2019.get_block(hash.unpack()).await?;
+            Ok(item2019_to_item2021!(block))
+        }
+    }
+
+    pub async fn get_fork_block(&self, hash: Byte32) -> Result<Option<BlockView>, RpcError> {
+        if self.ckb2021 {
+            Ok(self.inner2021.get_fork_block(hash.unpack()).await?)
+        } else {
+            let block = self.inner2019.get_fork_block(hash.unpack()).await?;
+            Ok(item2019_to_item2021!(block))
+        }
+    }
+
+    pub async fn get_block_by_number(
+        &self,
+        number: CoreBlockNumber,
+    ) -> Result<Option<BlockView>, RpcError> {
+        if self.ckb2021 {
+            Ok(self.inner2021.get_block_by_number(number.into()).await?)
+        } else {
+            let block = self.inner2019.get_block_by_number(number.into()).await?;
+            Ok(item2019_to_item2021!(block))
+        }
+    }
+
+    pub async fn get_header(&self, hash: Byte32) -> Result<Option<HeaderView>, RpcError> {
+        if self.ckb2021 {
+            Ok(self.inner2021.get_header(hash.unpack()).await?)
+        } else {
+            let header = self.inner2019.get_header(hash.unpack()).await?;
+            Ok(item2019_to_item2021!(header))
+        }
+    }
+
+    pub async fn get_header_by_number(
+        &self,
+        number: CoreBlockNumber,
+    ) -> Result<Option<HeaderView>, RpcError> {
+        if self.ckb2021 {
+            Ok(self.inner2021.get_header_by_number(number.into()).await?)
+        } else {
+            let header = self.inner2019.get_header_by_number(number.into()).await?;
+            Ok(item2019_to_item2021!(header))
+        }
+    }
+
+    pub async fn get_transaction(
+        &self,
+        hash: Byte32,
+    ) -> Result<Option<TransactionWithStatus>, RpcError> {
+        if self.ckb2021 {
+            Ok(self.inner2021.get_transaction(hash.unpack()).await?)
+        } else {
+            let transaction = self.inner2019.get_transaction(hash.unpack()).await?;
+            Ok(item2019_to_item2021!(transaction))
+        }
+    }
+
+    pub async fn get_block_hash(
+        &self,
+        number: CoreBlockNumber,
+    ) -> Result<Option<Byte32>, RpcError> {
+        let hash = self.inner().get_block_hash(number.into()).await?;
+        Ok(hash.map(|x| x.pack()))
+    }
+
+    pub async fn get_tip_header(&self) -> Result<HeaderView, RpcError> {
+        if self.ckb2021 {
+            Ok(self.inner2021.get_tip_header().await?)
+        } else {
+            let header = self.inner2019.get_tip_header().await?;
+            Ok(item2019_to_item2021!(header))
+        }
+    }
+
+    pub async fn get_live_cell(
+        &self,
+        out_point: OutPoint,
+        with_data: bool,
+    ) -> Result<CellWithStatus, RpcError> {
+        if self.ckb2021 {
+            Ok(self.inner2021.get_live_cell(out_point, with_data).await?)
+        } else {
+            let out_point = item2019_to_item2021!(out_point);
+            let cell = self.inner2019.get_live_cell(out_point, with_data).await?;
+            Ok(item2019_to_item2021!(cell))
+        }
+    }
+
+    pub async fn get_tip_block_number(&self) -> Result<CoreBlockNumber, RpcError> {
+        Ok(self.inner().get_tip_block_number().await?.into())
+    }
+
+    pub async fn get_current_epoch(&self) -> Result<EpochView, RpcError> {
+        Ok(self.inner().get_current_epoch().await?)
+    }
+
+    pub async fn get_epoch_by_number(
+        &self,
+        number: CoreEpochNumber,
+    ) -> Result<Option<EpochView>, RpcError> {
+        Ok(self.inner().get_epoch_by_number(number.into()).await?)
+    }
+
+    pub async fn get_consensus(&self) -> Result<Consensus, RpcError> {
+        if self.ckb2021 {
+            Ok(self.inner2021.get_consensus().await?)
+        } else {
+            let consensus = self.inner2019.get_consensus().await?;
+            Ok(item2019_to_item2021!(consensus))
+        }
+    }
+
+    pub async fn local_node_info(&self) -> Result<LocalNode, RpcError> {
+        Ok(self.inner().local_node_info().await?)
+    }
+
+    pub async fn get_peers(&self) -> Result<Vec<RemoteNode>, RpcError> {
+        Ok(self.inner().get_peers().await?)
+    }
+
+    pub async fn get_banned_addresses(&self) -> Result<Vec<BannedAddr>, RpcError> {
+        Ok(self.inner().get_banned_addresses().await?)
+    }
+
+    pub async fn set_ban(
+        &self,
+        address: String,
+        command: String,
+        ban_time: Option<Timestamp>,
+        absolute: Option<bool>,
+        reason: Option<String>,
+    ) -> Result<(), RpcError> {
+        Ok(self
+            .inner()
+            .set_ban(address, command, ban_time, absolute, reason)
+            .await?)
+    }
+
+    pub async fn get_block_template(
+        &self,
+        bytes_limit: Option<u64>,
+        proposals_limit: Option<u64>,
+        max_version: Option<CoreVersion>,
+    ) -> Result<BlockTemplate, RpcError> {
+        if self.ckb2021 {
+            let bytes_limit = bytes_limit.map(Into::into);
+            let proposals_limit = proposals_limit.map(Into::into);
+            let max_version = max_version.map(Into::into);
+            Ok(self
+                .inner2021
+                .get_block_template(bytes_limit, proposals_limit, max_version)
+                .await?)
+        } else {
+            let bytes_limit = bytes_limit.map(Into::into);
+            let proposals_limit = proposals_limit.map(Into::into);
+            let max_version = max_version.map(Into::into);
+            let template = self
+                .inner2019
+                .get_block_template(bytes_limit, proposals_limit, max_version)
+                .await?;
+            Ok(item2019_to_item2021!(template))
+        }
+    }
+
+    pub async fn submit_block(&self, work_id: String, block: Block) -> Result<Byte32, RpcError> {
+        let hash = if self.ckb2021 {
+            self.inner2021.submit_block(work_id, block).await?
+        } else {
+            let block2019 = item2021_to_item2019!(&block);
+            self.inner2019.submit_block(work_id, block2019).await?
+        };
+        Ok(hash.pack())
+    }
+
+    pub async fn get_blockchain_info(&self) -> Result<ChainInfo, RpcError> {
+        Ok(self.inner().get_blockchain_info().await?)
+    }
+
+    pub async fn get_block_median_time(
+        &self,
+        block_hash: Byte32,
+    ) -> Result<Option<Timestamp>, RpcError> {
+        Ok(self
+            .inner()
+            .get_block_median_time(block_hash.unpack())
+            .await?)
+    }
+
+    /// See `RpcClient::send_transaction_result` for why it waits for the transaction status on
+    /// ckb2021.
+    pub async fn send_transaction(&self, tx: Transaction) -> Result<Byte32, RpcError> {
+        if self.ckb2021 {
+            let hash = self
+                .inner2021
+                .send_transaction(tx, Some("passthrough".to_string()))
+                .await?;
+            let start_time = Instant::now();
+            while start_time.elapsed() <= Duration::from_secs(20) {
+                if let Some(txstatus) = self.inner2021.get_transaction(hash.clone()).await? {
+                    if txstatus.tx_status.status != ckb_jsonrpc_types::Status::Unknown {
+                        break;
+                    }
+                }
+            }
+            Ok(hash.pack())
+        } else {
+            let tx = item2021_to_item2019!(tx);
+            let hash = self
+                .inner2019
+                .send_transaction(tx, Some("passthrough".to_string()))
+                .await?;
+            Ok(hash.pack())
+        }
+    }
+
+    pub async fn dry_run_transaction(&self, tx: Transaction) -> Result<DryRunResult, RpcError> {
+        if self.ckb2021 {
+            Ok(self.inner2021.dry_run_transaction(tx).await?)
+        } else {
+            let tx = item2019_to_item2021!(tx);
+            let result = self.inner2019.dry_run_transaction(tx).await?;
+            Ok(item2019_to_item2021!(result))
+        }
+    }
+
+    pub async fn send_alert(&self, alert: Alert) -> Result<(), RpcError> {
+        Ok(self.inner().send_alert(alert).await?)
+    }
+
+    pub async fn tx_pool_info(&self) -> Result<TxPoolInfo, RpcError> {
+        Ok(self.inner().tx_pool_info().await?)
+    }
+
+    pub async fn add_node(&self, peer_id: String, address: String) -> Result<(), RpcError> {
+        Ok(self.inner().add_node(peer_id, address).await?)
+    }
+
+    pub async fn remove_node(&self, peer_id: String) -> Result<(), RpcError> {
+        Ok(self.inner().remove_node(peer_id).await?)
+    }
+
+    pub async fn truncate(&self, target_tip_hash: Byte32) -> Result<(), RpcError> {
+        Ok(self.inner().truncate(target_tip_hash.unpack()).await?)
+    }
+
+    pub async fn calculate_dao_maximum_withdraw(
+        &self,
+        out_point: OutPoint,
+        hash: Byte32,
+    ) -> Result<CoreCapacity, RpcError> {
+        let capacity = self
+            .inner()
+            .calculate_dao_maximum_withdraw(out_point, hash.unpack())
+            .await?;
+        Ok(capacity.into())
+    }
+
+    pub async fn process_block_without_verify(
+        &self,
+        block: Block,
+        should_broadcast: bool,
+    ) -> Result<Option<Byte32>, RpcError> {
+        let hash = if self.ckb2021 {
+            self.inner2021
+                .process_block_without_verify(block, should_broadcast)
+                .await?
+        } else {
+            let block = item2021_to_item2019!(block);
+            self.inner2019
+                .process_block_without_verify(block, should_broadcast)
+                .await?
+        };
+        Ok(hash.map(|h256| h256.pack()))
+    }
+
+    pub async fn calculate_dao_field(
+        &self,
+        block_template: BlockTemplate,
+    ) -> Result<Byte32, RpcError> {
+        if !self.ckb2021 {
+            return Err(RpcError::Unsupported("calculate_dao_field"));
+        }
+        let dao = self.inner2021.calculate_dao_field(block_template).await?;
+        Ok(dao.into())
+    }
+
+    pub async fn get_raw_tx_pool(&self, verbose: Option<bool>) -> Result<RawTxPool, RpcError> {
+        if !self.ckb2021 {
+            return Err(RpcError::Unsupported("get_raw_tx_pool"));
+        }
+        Ok(self.inner2021.get_raw_tx_pool(verbose).await?)
+    }
+
+    /// Send `calls` in one batch request, see `RpcClient::batch`.
+    pub async fn batch(
+        &self,
+        calls: Vec<Call>,
+    ) -> Result<Vec<Result<serde_json::Value, RpcError>>, RpcError> {
+        let results = self.inner().batch(calls).await?;
+        Ok(results
+            .into_iter()
+            .map(|result| result.map_err(Into::into))
+            .collect())
+    }
+}
@@ -0,0 +1,101 @@
+use super::error::Error;
+use super::id_generator::IdGenerator;
+use ckb_error::AnyError;
+use jsonrpc_core::response::{Output, Response};
+use std::collections::HashMap;
+
+/// The maximal number of calls sent in one batch request by the `*_by_number` batch helpers.
+pub const MAX_BATCH_SIZE: usize = 100;
+
+/// A call of a JSON-RPC batch request.
+///
+/// ```ignore
+/// let calls = vec![
+///     Call::new("get_tip_header", serde_json::Value::Null),
+///     Call::new("get_block_hash", serde_json::json!(["0x1"])),
+/// ];
+/// let results = node.rpc_client().batch(calls);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    pub method: String,
+    /// `Null` or the array of the positional params
+    pub params: serde_json::Value,
+}
+
+impl Call {
+    pub fn new(method: &str, params: serde_json::Value) -> Self {
+        Self {
+            method: method.to_string(),
+            params,
+        }
+    }
+}
+
+/// Build the batch request, return the ids of the calls along with the request.
+#[doc(hidden)]
+pub fn batch_request(
+    id_generator: &IdGenerator,
+    calls: Vec<Call>,
+) -> (Vec<u64>, serde_json::Value) {
+    let mut ids = Vec::with_capacity(calls.len());
+    let mut req_json = Vec::with_capacity(calls.len());
+    for call in calls {
+        let id = id_generator.next();
+        ids.push(id);
+        req_json.push(serde_json::json!({
+            "id": id,
+            "jsonrpc": "2.0",
+            "method": call.method,
+            "params": call.params,
+        }));
+    }
+    (ids, serde_json::Value::Array(req_json))
+}
+
+/// Match the outputs of the batch response to the calls by id, as the server is free to respond
+/// in any order.
+#[doc(hidden)]
+pub fn batch_results(
+    ids: Vec<u64>,
+    response: Response,
+) -> Result<Vec<Result<serde_json::Value, AnyError>>, AnyError> {
+    let outputs = match response {
+        Response::Batch(outputs) => outputs,
+        // The whole batch request is rejected, e.g. parse error
+        Response::Single(Output::Failure(failure)) => {
+            return Err(Error {
+                inner: failure.error,
+            }
+            .into())
+        }
+        Response::Single(output) => vec![output],
+    };
+    let mut outputs = outputs
+        .into_iter()
+        .filter_map(|output| {
+            let id = match output.id() {
+                jsonrpc_core::Id::Num(id) => *id,
+                _ => return None,
+            };
+            Some((id, output))
+        })
+        .collect::<HashMap<_, _>>();
+    ids.into_iter()
+        .map(|id| {
+            let output = outputs.remove(&id).ok_or_else(|| {
+                AnyError::from(ckb_error::OtherError::new(format!(
+                    "missing the output of batch call id {}",
+                    id
+                )))
+            })?;
+            match output {
+                Output::Success(success) => Ok(Ok(success.result)),
+                Output::Failure(failure) => Ok(Err(Error {
+                    inner: failure.error,
+                }
+                .into())),
+            }
+        })
+        .collect()
+}
@@ -1,4 +1,7 @@
+use ckb_error::AnyError;
 use jsonrpc_core::error::Error as JsonRpcError;
+use lazy_static::lazy_static;
+use regex::Regex;
 use std::fmt;
 
 #[derive(Debug, PartialEq, Clone)]
@@ -17,3 +20,287 @@ impl fmt::Display for Error {
 }
 
 impl ::std::error::Error for Error {}
+
+lazy_static! {
+    // e.g. "ValidationFailure: see the error code 5 in the page https://..."
+    static ref SCRIPT_ERROR_CODE: Regex = Regex::new(r"error code (-?\d+)").unwrap();
+}
+
+/// The error of `RpcClient::try_*` methods.
+///
+/// The errors returned by ckb are decoded by their JSON-RPC error codes, which are the same in
+/// ckb2019 and ckb2021. The payloads are the raw JSON-RPC errors, the same as their `Display`,
+/// e.g. `{"code":-1106,"message":"PoolIsFull: ...","data":"..."}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpcError {
+    /// Failed to send the request or to receive the response, e.g. the node is down
+    Transport(String),
+    /// The response is not a JSON-RPC response of the expected type
+    InvalidResponse(String),
+    /// The method is not supported by the ckb2019 node, e.g. "get_transaction_proof"
+    Unsupported(&'static str),
+    /// -3, the params are invalid, e.g. an invalid block submitted via `submit_block`
+    Invalid(String),
+    /// -301
+    TransactionFailedToResolve(String),
+    /// -302, with the exit code of the failed script if any
+    TransactionFailedToVerify {
+        script_error_code: Option<i8>,
+        raw: String,
+    },
+    /// -1102
+    PoolRejectedTransactionByOutputsValidator(String),
+    /// -1103
+    PoolRejectedTransactionByIllTransactionChecker(String),
+    /// -1104
+    PoolRejectedTransactionByMinFeeRate(String),
+    /// -1105
+    PoolRejectedTransactionByMaxAncestorsCountLimit(String),
+    /// -1106
+    PoolIsFull(String),
+    /// -1107
+    PoolRejectedDuplicatedTransaction(String),
+    /// -1108
+    PoolRejectedMalformedTransaction(String),
+    /// The other JSON-RPC errors
+    Other { code: i64, raw: String },
+}
+
+impl RpcError {
+    fn from_json_rpc_error(error: &JsonRpcError) -> Self {
+        let raw = Error {
+            inner: error.clone(),
+        }
+        .to_string();
+        match error.code.code() {
+            -3 => RpcError::Invalid(raw),
+            -301 => RpcError::TransactionFailedToResolve(raw),
+            -302 => {
+                let script_error_code = SCRIPT_ERROR_CODE
+                    .captures(&raw)
+                    .and_then(|captures| captures[1].parse::<i8>().ok());
+                RpcError::TransactionFailedToVerify {
+                    script_error_code,
+                    raw,
+                }
+            }
+            -1102 => RpcError::PoolRejectedTransactionByOutputsValidator(raw),
+            -1103 => RpcError::PoolRejectedTransactionByIllTransactionChecker(raw),
+            -1104 => RpcError::PoolRejectedTransactionByMinFeeRate(raw),
+            -1105 => RpcError::PoolRejectedTransactionByMaxAncestorsCountLimit(raw),
+            -1106 => RpcError::PoolIsFull(raw),
+            -1107 => RpcError::PoolRejectedDuplicatedTransaction(raw),
+            -1108 => RpcError::PoolRejectedMalformedTransaction(raw),
+            code => RpcError::Other { code, raw },
+        }
+    }
+
+    /// The JSON-RPC error code, `None` for `Transport`, `InvalidResponse` and `Unsupported`.
+    pub fn code(&self) -> Option<i64> {
+        let code = match self {
+            RpcError::Transport(_) | RpcError::InvalidResponse(_) | RpcError::Unsupported(_) => {
+                return None
+            }
+            RpcError::Invalid(_) => -3,
+            RpcError::TransactionFailedToResolve(_) => -301,
+            RpcError::TransactionFailedToVerify { .. } => -302,
+            RpcError::PoolRejectedTransactionByOutputsValidator(_) => -1102,
+            RpcError::PoolRejectedTransactionByIllTransactionChecker(_) => -1103,
+            RpcError::PoolRejectedTransactionByMinFeeRate(_) => -1104,
+            RpcError::PoolRejectedTransactionByMaxAncestorsCountLimit(_) => -1105,
+            RpcError::PoolIsFull(_) => -1106,
+            RpcError::PoolRejectedDuplicatedTransaction(_) => -1107,
+            RpcError::PoolRejectedMalformedTransaction(_) => -1108,
+            RpcError::Other { code, .. } => *code,
+        };
+        Some(code)
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RpcError::Transport(raw)
+            | RpcError::InvalidResponse(raw)
+            | RpcError::Invalid(raw)
+            | RpcError::TransactionFailedToResolve(raw)
+            | RpcError::TransactionFailedToVerify { raw, .. }
+            | RpcError::PoolRejectedTransactionByOutputsValidator(raw)
+            | RpcError::PoolRejectedTransactionByIllTransactionChecker(raw)
+            | RpcError::PoolRejectedTransactionByMinFeeRate(raw)
+            | RpcError::PoolRejectedTransactionByMaxAncestorsCountLimit(raw)
+            | RpcError::PoolIsFull(raw)
+            | RpcError::PoolRejectedDuplicatedTransaction(raw)
+            | RpcError::PoolRejectedMalformedTransaction(raw)
+            | RpcError::Other { raw, .. } => write!(f, "{}", raw),
+            RpcError::Unsupported(method) => write!(f, "{} is not supported by ckb2019", method),
+        }
+    }
+}
+
+impl ::std::error::Error for RpcError {}
+
+impl From<AnyError> for RpcError {
+    fn from(err: AnyError) -> Self {
+        if let Some(error) = err.downcast_ref::<Error>() {
+            RpcError::from_json_rpc_error(&error.inner)
+        } else if let Some(error) = err.downcast_ref::<reqwest::Error>() {
+            if error.is_decode() {
+                RpcError::InvalidResponse(err.to_string())
+            } else {
+                RpcError::Transport(err.to_string())
+            }
+        } else {
+            RpcError::InvalidResponse(err.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RpcError, SCRIPT_ERROR_CODE};
+    use jsonrpc_core::error::Error as JsonRpcError;
+
+    const TX_HASH: &str = "0xa0ef4eb5f4ceeb08a4c8524d84c5da95dce2f608e0ca2ec8091191b0f330c6e3";
+
+    fn parse(response_error: &str) -> RpcError {
+        let error: JsonRpcError = serde_json::from_str(response_error)
+            .unwrap_or_else(|err| panic!("invalid error {}, error: {}", response_error, err));
+        RpcError::from_json_rpc_error(&error)
+    }
+
+    // The error of a failed script returned by ckb v0.100
+    fn script_error(code: i8) -> String {
+        format!(
+            r#"{{"code":-302,"message":"TransactionFailedToVerify: Verification failed Script(TransactionScriptError {{ source: Inputs[0].Lock, cause: ValidationFailure: see the error code {} in the page https://nervosnetwork.github.io/ckb-script-error-codes/by-type-hash/9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8.html#{} }})","data":"Verification(Error {{ kind: Script, inner: TransactionScriptError {{ source: Inputs[0].Lock, cause: ValidationFailure: see the error code {} in the page https://nervosnetwork.github.io/ckb-script-error-codes/by-type-hash/9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8.html#{} }} }})"}}"#,
+            code, code, code, code
+        )
+    }
+
+    #[test]
+    fn test_from_json_rpc_error() {
+        // (the error returned by ckb v0.100, the expected variant)
+        let cases = vec![
+            (
+                r#"{"code":-3,"message":"Invalid: Header(Version(BlockVersionError { expected: 0, actual: 1 }))","data":"Header(Version(BlockVersionError { expected: 0, actual: 1 }))"}"#.to_string(),
+                "Invalid",
+            ),
+            (
+                format!(
+                    r#"{{"code":-301,"message":"TransactionFailedToResolve: Resolve failed Dead(OutPoint({}00000000))","data":"Resolve(Dead(OutPoint({}00000000)))"}}"#,
+                    TX_HASH, TX_HASH
+                ),
+                "TransactionFailedToResolve",
+            ),
+            (script_error(5), "TransactionFailedToVerify"),
+            (script_error(-1), "TransactionFailedToVerify"),
+            (
+                r#"{"code":-302,"message":"TransactionFailedToVerify: Verification failed Transaction(InsufficientCellCapacity(Outputs[0]: occupied capacity 6100000000 > capacity 100))","data":"Verification(Error { kind: Transaction, inner: InsufficientCellCapacity(Outputs[0]: occupied capacity 6100000000 > capacity 100) })"}"#.to_string(),
+                "TransactionFailedToVerify",
+            ),
+            (
+                r#"{"code":-1102,"message":"PoolRejectedTransactionByOutputsValidator: The transaction is rejected by OutputsValidator set in params[1]: well_known_scripts_only. Please check the related information in https://github.com/nervosnetwork/ckb/wiki/Transaction-%C2%BB-Default-Outputs-Validator"}"#.to_string(),
+                "PoolRejectedTransactionByOutputsValidator",
+            ),
+            (
+                r#"{"code":-1103,"message":"PoolRejectedTransactionByIllTransactionChecker: The transaction is ill-formed, the output 0 contains the code of the deprecated ckb-vm","data":"Reject(Malformed(\"the output 0 contains the code of the deprecated ckb-vm\"))"}"#.to_string(),
+                "PoolRejectedTransactionByIllTransactionChecker",
+            ),
+            (
+                r#"{"code":-1104,"message":"PoolRejectedTransactionByMinFeeRate: The min fee rate is 1000 shannons/KB, so the transaction fee should be 242 shannons at least, but only got 0","data":"Reject(LowFeeRate(FeeRate(1000), 242, 0))"}"#.to_string(),
+                "PoolRejectedTransactionByMinFeeRate",
+            ),
+            (
+                r#"{"code":-1105,"message":"PoolRejectedTransactionByMaxAncestorsCountLimit: Transaction exceeded maximum ancestors count limit, try send it later","data":"Reject(ExceededMaximumAncestorsCount)"}"#.to_string(),
+                "PoolRejectedTransactionByMaxAncestorsCountLimit",
+            ),
+            (
+                r#"{"code":-1106,"message":"PoolIsFull: Transaction pool exceeded maximum size limit(180000000), try send it later","data":"Reject(Full(\"size\", 180000000))"}"#.to_string(),
+                "PoolIsFull",
+            ),
+            (
+                format!(
+                    r#"{{"code":-1107,"message":"PoolRejectedDuplicatedTransaction: Transaction(Byte32({})) already exist in transaction_pool","data":"Reject(Duplicated(Byte32({})))"}}"#,
+                    TX_HASH, TX_HASH
+                ),
+                "PoolRejectedDuplicatedTransaction",
+            ),
+            (
+                r#"{"code":-1108,"message":"PoolRejectedMalformedTransaction: Malformed cellbase like transaction","data":"Reject(Malformed(\"cellbase like\"))"}"#.to_string(),
+                "PoolRejectedMalformedTransaction",
+            ),
+            (
+                r#"{"code":-32601,"message":"Method not found"}"#.to_string(),
+                "Other",
+            ),
+        ];
+        for (response_error, variant) in cases {
+            let err = parse(&response_error);
+            let debug = format!("{:?}", err);
+            assert!(
+                debug.starts_with(&format!("{}(", variant))
+                    || debug.starts_with(&format!("{} {{", variant)),
+                "{} => {}",
+                response_error,
+                debug
+            );
+
+            // The code and the raw error are kept
+            let value: serde_json::Value = serde_json::from_str(&response_error).unwrap();
+            assert_eq!(err.code(), value["code"].as_i64());
+            let displayed: serde_json::Value = serde_json::from_str(&err.to_string()).unwrap();
+            assert_eq!(displayed, value);
+        }
+
+        for (code, expected) in [(5, Some(5)), (-1, Some(-1))] {
+            match parse(&script_error(code)) {
+                RpcError::TransactionFailedToVerify {
+                    script_error_code, ..
+                } => assert_eq!(script_error_code, expected),
+                err => panic!("unexpected {:?}", err),
+            }
+        }
+    }
+
+    #[test]
+    fn test_script_error_code() {
+        let script_error_code = |raw: &str| {
+            SCRIPT_ERROR_CODE
+                .captures(raw)
+                .and_then(|captures| captures[1].parse::<i8>().ok())
+        };
+        for (raw, expected) in [
+            (
+                "ValidationFailure: see the error code 5 in the page https://...",
+                Some(5),
+            ),
+            (
+                "ValidationFailure: see the error code -1 in the page https://...",
+                Some(-1),
+            ),
+            (
+                "ValidationFailure: see the error code 127 in the page",
+                Some(127),
+            ),
+            (
+                "ValidationFailure: see the error code -128 in the page",
+                Some(-128),
+            ),
+            // Out of the range of the script exit code
+            (
+                "ValidationFailure: see the error code 128 in the page",
+                None,
+            ),
+            ("ValidationFailure: see the error code in the page", None),
+            ("ExceededMaximumCycles: expect cycles <= 70000000", None),
+            ("", None),
+        ] {
+            assert_eq!(script_error_code(raw), expected, "{}", raw);
+        }
+        assert_eq!(
+            script_error_code(&script_error(42)),
+            Some(42),
+            "the first match is taken"
+        );
+    }
+}
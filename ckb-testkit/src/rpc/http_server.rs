@@ -0,0 +1,133 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+/// Answer a JSON-RPC request object with a JSON-RPC response object
+pub(crate) type Handler = dyn Fn(&serde_json::Value) -> serde_json::Value + Send + Sync;
+
+/// A minimal HTTP/1.1 JSON-RPC server on "127.0.0.1:<random port>", standing in for a ckb node.
+/// It handles both single and batch requests, and stops when dropped.
+pub(crate) struct HttpServer {
+    url: String,
+    stopped: Arc<AtomicBool>,
+    local_addr: std::net::SocketAddr,
+}
+
+impl HttpServer {
+    pub(crate) fn start(handler: Arc<Handler>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .unwrap_or_else(|err| panic!("failed to bind the http server, error: {}", err));
+        let local_addr = listener
+            .local_addr()
+            .unwrap_or_else(|err| panic!("failed to get the local address, error: {}", err));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let stopped_clone = Arc::clone(&stopped);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped_clone.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let handler = Arc::clone(&handler);
+                    thread::spawn(move || {
+                        let _ = serve_connection(stream, &*handler);
+                    });
+                }
+            }
+        });
+        Self {
+            url: format!("http://{}/", local_addr),
+            stopped,
+            local_addr,
+        }
+    }
+
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl Drop for HttpServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wake up the accepting thread
+        if let Ok(stream) = TcpStream::connect(self.local_addr) {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+// Serve the requests of a keep-alive connection one by one
+fn serve_connection(stream: TcpStream, handler: &Handler) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut content_length = 0;
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line)? == 0 {
+            return Ok(());
+        }
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap_or(0);
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+
+        let response = match serde_json::from_slice::<serde_json::Value>(&body) {
+            Ok(serde_json::Value::Array(calls)) => {
+                serde_json::Value::Array(calls.iter().map(|call| handler(call)).collect())
+            }
+            Ok(request) => handler(&request),
+            Err(err) => failure(
+                &serde_json::Value::Null,
+                jsonrpc_core::Error::parse_error(),
+                &err.to_string(),
+            ),
+        };
+        let response = serde_json::to_vec(&response).expect("serialize response");
+        write!(
+            writer,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+            response.len()
+        )?;
+        writer.write_all(&response)?;
+        writer.flush()?;
+    }
+}
+
+/// The success response of `request`
+pub(crate) fn success(request: &serde_json::Value, result: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": request.get("id").cloned().unwrap_or(serde_json::Value::Null),
+        "result": result,
+    })
+}
+
+/// The failure response of `request`, with `data` as the error data
+pub(crate) fn failure(
+    request: &serde_json::Value,
+    mut error: jsonrpc_core::Error,
+    data: &str,
+) -> serde_json::Value {
+    error.data = Some(serde_json::Value::String(data.to_string()));
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": request.get("id").cloned().unwrap_or(serde_json::Value::Null),
+        "error": error,
+    })
+}
@@ -1,5 +1,29 @@
 #[macro_export]
 macro_rules! jsonrpc {
+    // Generate both the blocking client `$struct_name` and the async client `$async_struct_name`
+    (
+        $(#[$struct_attr:meta])*
+        pub struct $struct_name:ident, $async_struct_name:ident {$(
+            $(#[$attr:meta])*
+            pub fn $method:ident(&$selff:ident $(, $arg_name:ident: $arg_ty:ty)*)
+                -> $return_ty:ty;
+        )*}
+    ) => (
+        jsonrpc!(
+            $(#[$struct_attr])*
+            pub struct $struct_name {$(
+                $(#[$attr])*
+                pub fn $method(&$selff $(, $arg_name: $arg_ty)*) -> $return_ty;
+            )*}
+        );
+        jsonrpc_async!(
+            $(#[$struct_attr])*
+            pub struct $async_struct_name {$(
+                $(#[$attr])*
+                pub fn $method(&$selff $(, $arg_name: $arg_ty)*) -> $return_ty;
+            )*}
+        );
+    );
     (
         $(#[$struct_attr:meta])*
         pub struct $struct_name:ident {$(
@@ -22,6 +46,21 @@ macro_rules! jsonrpc {
                 $struct_name { url, id_generator, client: &$crate::rpc::HTTP_CLIENT, }
             }
 
+            /// Send `calls` in one batch request, return the results in the order of `calls`.
+            #[allow(dead_code)]
+            pub fn batch(&self, calls: Vec<$crate::rpc::Call>)
+                -> Result<Vec<Result<serde_json::Value, ckb_error::AnyError>>, ckb_error::AnyError> {
+                if calls.is_empty() {
+                    return Ok(Vec::new());
+                }
+                let (ids, req_json) = $crate::rpc::batch::batch_request(&self.id_generator, calls);
+                let start_time = ::std::time::Instant::now();
+                let resp = self.client.post(self.url.clone()).json(&req_json).send()?;
+                let response = resp.json::<jsonrpc_core::response::Response>()?;
+                $crate::rpc::recorder::record_batch(&self.url, &req_json, &response, start_time.elapsed());
+                $crate::rpc::batch::batch_results(ids, response)
+            }
+
             $(
                 #[allow(dead_code)]
                 $(#[$attr])*
@@ -40,8 +79,92 @@ macro_rules! jsonrpc {
                     req_json.insert("method".to_owned(), serde_json::json!(method));
                     req_json.insert("params".to_owned(), params);
 
+                    let start_time = ::std::time::Instant::now();
                     let resp = $selff.client.post($selff.url.clone()).json(&req_json).send()?;
                     let output = resp.json::<jsonrpc_core::response::Output>()?;
+                    $crate::rpc::recorder::record(&$selff.url, &req_json, &output, start_time.elapsed());
+                    match output {
+                        jsonrpc_core::response::Output::Success(success) => {
+                            serde_json::from_value(success.result).map_err(Into::into)
+                        },
+                        jsonrpc_core::response::Output::Failure(failure) => {
+                            Err($crate::rpc::error::Error{ inner: failure.error }.into())
+                        }
+                    }
+                }
+            )*
+        }
+    )
+}
+
+/// The async sibling of `jsonrpc!`, generating the same methods as `async fn`s over a
+/// `reqwest::Client`, so that requests are issued concurrently instead of blocking the runtime.
+#[macro_export]
+macro_rules! jsonrpc_async {
+    (
+        $(#[$struct_attr:meta])*
+        pub struct $struct_name:ident {$(
+            $(#[$attr:meta])*
+            pub fn $method:ident(&$selff:ident $(, $arg_name:ident: $arg_ty:ty)*)
+                -> $return_ty:ty;
+        )*}
+    ) => (
+        $(#[$struct_attr])*
+        pub struct $struct_name {
+            pub client: reqwest::Client,
+            pub url: reqwest::Url,
+            pub id_generator: $crate::rpc::id_generator::IdGenerator,
+        }
+
+        impl $struct_name {
+            pub fn new(uri: &str) -> Self {
+                Self::with_client(uri, $crate::rpc::new_async_http_client())
+            }
+
+            /// Share the connection pool of `client`
+            pub fn with_client(uri: &str, client: reqwest::Client) -> Self {
+                let url = reqwest::Url::parse(uri).expect("ckb uri, e.g. \"http://127.0.0.1:8114\"");
+                let id_generator = $crate::rpc::id_generator::IdGenerator::new();
+                $struct_name { url, id_generator, client }
+            }
+
+            /// Send `calls` in one batch request, return the results in the order of `calls`.
+            #[allow(dead_code)]
+            pub async fn batch(&self, calls: Vec<$crate::rpc::Call>)
+                -> Result<Vec<Result<serde_json::Value, ckb_error::AnyError>>, ckb_error::AnyError> {
+                if calls.is_empty() {
+                    return Ok(Vec::new());
+                }
+                let (ids, req_json) = $crate::rpc::batch::batch_request(&self.id_generator, calls);
+                let start_time = ::std::time::Instant::now();
+                let resp = self.client.post(self.url.clone()).json(&req_json).send().await?;
+                let response = resp.json::<jsonrpc_core::response::Response>().await?;
+                $crate::rpc::recorder::record_batch(&self.url, &req_json, &response, start_time.elapsed());
+                $crate::rpc::batch::batch_results(ids, response)
+            }
+
+            $(
+                #[allow(dead_code)]
+                $(#[$attr])*
+                pub async fn $method(&$selff $(, $arg_name: $arg_ty)*) -> Result<$return_ty, ckb_error::AnyError> {
+                    let method =
+                        String::from(stringify!($method))
+                            .replace("2019", "")
+                            .replace("2021", "");
+
+                    let params = serialize_parameters!($($arg_name,)*);
+                    let id = $selff.id_generator.next();
+
+                    let mut req_json = serde_json::Map::new();
+                    req_json.insert("id".to_owned(), serde_json::json!(id));
+                    req_json.insert("jsonrpc".to_owned(), serde_json::json!("2.0"));
+                    req_json.insert("method".to_owned(), serde_json::json!(method));
+                    req_json.insert("params".to_owned(), params);
+
+                    let start_time = ::std::time::Instant::now();
+                    let resp = $selff.client.post($selff.url.clone()).json(&req_json).send().await?;
+                    let output = resp.json::<jsonrpc_core::response::Output>().await?;
+                    $crate::rpc::recorder::record(&$selff.url, &req_json, &output, start_time.elapsed());
                     match output {
                         jsonrpc_core::response::Output::Success(success) => {
                             serde_json::from_value(success.result).map_err(Into::into)
@@ -62,3 +185,26 @@ macro_rules! serialize_parameters {
     () => ( serde_json::Value::Null );
     ($($arg_name:ident,)+) => ( serde_json::to_value(($($arg_name,)+))?)
 }
+
+macro_rules! item2019_to_item2021 {
+    ($item2019:expr) => {{
+        let raw2019 = serde_json::to_string(&$item2019).unwrap();
+        let raw2021 = raw2019
+            // interfaces that includes block header
+            .replace("uncles_hash", "extra_hash")
+            // get_consensus
+            .replace(
+                "\"permanent_difficulty_in_dummy\":",
+                "\"hardfork_features\":[],\"permanent_difficulty_in_dummy\":",
+            );
+        serde_json::from_str(&raw2021).unwrap()
+    }};
+}
+
+macro_rules! item2021_to_item2019 {
+    ($item2021:expr) => {{
+        let raw2021 = serde_json::to_string(&$item2021).unwrap();
+        let raw2019 = raw2021.replace("extra_hash", "uncles_hash");
+        serde_json::from_str(&raw2019).unwrap()
+    }};
+}
@@ -1,24 +1,42 @@
 mod id_generator;
 #[macro_use]
 mod macros;
+mod async_client;
+pub(crate) mod batch;
 mod error;
+pub(crate) mod http_server;
+pub(crate) mod recorder;
+mod replayer;
+pub mod types;
 mod v2019;
 mod v2021;
 
+pub use async_client::AsyncRpcClient;
+pub use batch::Call;
+pub use error::RpcError;
+pub use recorder::{RpcRecord, RpcRecorder};
+pub use replayer::RpcReplayer;
+
 use ckb_error::AnyError;
 // TODO replace json types with core types
 use ckb_jsonrpc_types::{
-    Alert, BannedAddr, Block, BlockTemplate, BlockView, CellWithStatus, ChainInfo, Consensus,
-    DryRunResult, EpochView, HeaderView, LocalNode, OutPoint, RawTxPool, RemoteNode, Timestamp,
-    Transaction, TransactionWithStatus, TxPoolInfo,
+    Alert, BannedAddr, Block, BlockEconomicState, BlockNumber, BlockReward, BlockTemplate,
+    BlockView, CellWithStatus, ChainInfo, Consensus, DryRunResult, EpochView, HeaderView,
+    JsonBytes, LocalNode, OutPoint, RawTxPool, RemoteNode, SyncState, Timestamp, Transaction,
+    TransactionProof, TransactionWithStatus, TxPoolInfo,
 };
 use ckb_types::core::{
-    BlockNumber as CoreBlockNumber, Capacity as CoreCapacity, EpochNumber as CoreEpochNumber,
-    Version as CoreVersion,
+    BlockNumber as CoreBlockNumber, Capacity as CoreCapacity, Cycle as CoreCycle,
+    EpochNumber as CoreEpochNumber, Version as CoreVersion,
 };
 use ckb_types::{packed::Byte32, prelude::*};
 use lazy_static::lazy_static;
+use std::ops::RangeInclusive;
 use std::time::{Duration, Instant};
+use types::{
+    EntryCompleted, FeeRateStatistics, IndexerCell, IndexerCellsCapacity, IndexerOrder,
+    IndexerPagination, IndexerSearchKey, IndexerTx,
+};
 use v2019::Inner2019;
 use v2021::Inner2021;
 
@@ -29,27 +47,13 @@ lazy_static! {
         .expect("reqwest Client build");
 }
 
-macro_rules! item2019_to_item2021 {
-    ($item2019:expr) => {{
-        let raw2019 = serde_json::to_string(&$item2019).unwrap();
-        let raw2021 = raw2019
-            // interfaces that includes block header
-            .replace("uncles_hash", "extra_hash")
-            // get_consensus
-            .replace(
-                "\"permanent_difficulty_in_dummy\":",
-                "\"hardfork_features\":[],\"permanent_difficulty_in_dummy\":",
-            );
-        serde_json::from_str(&raw2021).unwrap()
-    }};
-}
-
-macro_rules! item2021_to_item2019 {
-    ($item2021:expr) => {{
-        let raw2021 = serde_json::to_string(&$item2021).unwrap();
-        let raw2019 = raw2021.replace("extra_hash", "uncles_hash");
-        serde_json::from_str(&raw2019).unwrap()
-    }};
+/// Build the http client of the async rpc clients. Unlike `HTTP_CLIENT`, it is not shared
+/// globally, as its connections are bound to the tokio runtime they were created on.
+pub fn new_async_http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(::std::time::Duration::from_secs(30))
+        .build()
+        .expect("reqwest Client build")
 }
 
 pub struct RpcClient {
@@ -81,166 +85,217 @@ impl RpcClient {
         &self.inner2021
     }
 
+    // The ckb2021-only methods are unsupported by ckb2019 nodes
+    fn ensure_ckb2021(&self, method: &'static str) -> Result<(), RpcError> {
+        if self.ckb2021 {
+            Ok(())
+        } else {
+            Err(RpcError::Unsupported(method))
+        }
+    }
+
     pub fn get_block(&self, hash: Byte32) -> Option<BlockView> {
+        self.try_get_block(hash).expect("rpc call get_block")
+    }
+
+    pub fn try_get_block(&self, hash: Byte32) -> Result<Option<BlockView>, RpcError> {
         if self.ckb2021 {
-            self.inner2021
-                .get_block(hash.unpack())
-                .expect("rpc call get_block")
+            Ok(self.inner2021.get_block(hash.unpack())?)
         } else {
-            item2019_to_item2021!(self
-                .inner2019
-                .get_block(hash.unpack())
-                .expect("rpc call get_block"))
+            let block = self.inner2019.get_block(hash.unpack())?;
+            Ok(item2019_to_item2021!(block))
         }
     }
 
     pub fn get_fork_block(&self, hash: Byte32) -> Option<BlockView> {
+        self.try_get_fork_block(hash)
+            .expect("rpc call get_fork_block")
+    }
+
+    pub fn try_get_fork_block(&self, hash: Byte32) -> Result<Option<BlockView>, RpcError> {
         if self.ckb2021 {
-            self.inner2021
-                .get_fork_block(hash.unpack())
-                .expect("rpc call get_fork_block")
+            Ok(self.inner2021.get_fork_block(hash.unpack())?)
         } else {
-            item2019_to_item2021!(self
-                .inner2019
-                .get_fork_block(hash.unpack())
-                .expect("rpc call get_fork_block"))
+            let block = self.inner2019.get_fork_block(hash.unpack())?;
+            Ok(item2019_to_item2021!(block))
         }
     }
 
     pub fn get_block_by_number(&self, number: CoreBlockNumber) -> Option<BlockView> {
+        self.try_get_block_by_number(number)
+            .expect("rpc call get_block_by_number")
+    }
+
+    pub fn try_get_block_by_number(
+        &self,
+        number: CoreBlockNumber,
+    ) -> Result<Option<BlockView>, RpcError> {
         if self.ckb2021 {
-            self.inner2021
-                .get_block_by_number(number.into())
-                .expect("rpc call get_block_by_number")
+            Ok(self.inner2021.get_block_by_number(number.into())?)
         } else {
-            item2019_to_item2021!(self
-                .inner2019
-                .get_block_by_number(number.into())
-                .expect("rpc call get_block_by_number"))
+            let block = self.inner2019.get_block_by_number(number.into())?;
+            Ok(item2019_to_item2021!(block))
         }
     }
 
     pub fn get_header(&self, hash: Byte32) -> Option<HeaderView> {
+        self.try_get_header(hash).expect("rpc call get_header")
+    }
+
+    pub fn try_get_header(&self, hash: Byte32) -> Result<Option<HeaderView>, RpcError> {
         if self.ckb2021 {
-            self.inner2021
-                .get_header(hash.unpack())
-                .expect("rpc call get_header")
+            Ok(self.inner2021.get_header(hash.unpack())?)
         } else {
-            item2019_to_item2021!(self
-                .inner2019
-                .get_header(hash.unpack())
-                .expect("rpc call get_header"))
+            let header = self.inner2019.get_header(hash.unpack())?;
+            Ok(item2019_to_item2021!(header))
         }
     }
 
     pub fn get_header_by_number(&self, number: CoreBlockNumber) -> Option<HeaderView> {
+        self.try_get_header_by_number(number)
+            .expect("rpc call get_header_by_number")
+    }
+
+    pub fn try_get_header_by_number(
+        &self,
+        number: CoreBlockNumber,
+    ) -> Result<Option<HeaderView>, RpcError> {
         if self.ckb2021 {
-            self.inner2021
-                .get_header_by_number(number.into())
-                .expect("rpc call get_header_by_number")
+            Ok(self.inner2021.get_header_by_number(number.into())?)
         } else {
-            item2019_to_item2021!(self
-                .inner2019
-                .get_header_by_number(number.into())
-                .expect("rpc call get_header_by_number"))
+            let header = self.inner2019.get_header_by_number(number.into())?;
+            Ok(item2019_to_item2021!(header))
         }
     }
 
     pub fn get_transaction(&self, hash: Byte32) -> Option<TransactionWithStatus> {
+        self.try_get_transaction(hash)
+            .expect("rpc call get_transaction")
+    }
+
+    pub fn try_get_transaction(
+        &self,
+        hash: Byte32,
+    ) -> Result<Option<TransactionWithStatus>, RpcError> {
         if self.ckb2021 {
-            self.inner2021
-                .get_transaction(hash.unpack())
-                .expect("rpc call get_transaction")
+            Ok(self.inner2021.get_transaction(hash.unpack())?)
         } else {
-            item2019_to_item2021!(self
-                .inner2019
-                .get_transaction(hash.unpack())
-                .expect("rpc call get_transaction"))
+            let transaction = self.inner2019.get_transaction(hash.unpack())?;
+            Ok(item2019_to_item2021!(transaction))
         }
     }
 
     pub fn get_block_hash(&self, number: CoreBlockNumber) -> Option<Byte32> {
-        self.inner()
-            .get_block_hash(number.into())
+        self.try_get_block_hash(number)
             .expect("rpc call get_block_hash")
-            .map(|x| x.pack())
+    }
+
+    pub fn try_get_block_hash(&self, number: CoreBlockNumber) -> Result<Option<Byte32>, RpcError> {
+        let hash = self.inner().get_block_hash(number.into())?;
+        Ok(hash.map(|x| x.pack()))
     }
 
     pub fn get_tip_header(&self) -> HeaderView {
+        self.try_get_tip_header().expect("rpc call get_tip_header")
+    }
+
+    pub fn try_get_tip_header(&self) -> Result<HeaderView, RpcError> {
         if self.ckb2021 {
-            self.inner2021
-                .get_tip_header()
-                .expect("rpc call get_block_hash")
+            Ok(self.inner2021.get_tip_header()?)
         } else {
-            item2019_to_item2021!(self
-                .inner2019
-                .get_tip_header()
-                .expect("rpc call get_block_hash"))
+            let header = self.inner2019.get_tip_header()?;
+            Ok(item2019_to_item2021!(header))
         }
     }
 
     pub fn get_live_cell(&self, out_point: OutPoint, with_data: bool) -> CellWithStatus {
+        self.try_get_live_cell(out_point, with_data)
+            .expect("rpc call get_live_cell")
+    }
+
+    pub fn try_get_live_cell(
+        &self,
+        out_point: OutPoint,
+        with_data: bool,
+    ) -> Result<CellWithStatus, RpcError> {
         if self.ckb2021 {
-            self.inner2021
-                .get_live_cell(out_point, with_data)
-                .expect("rpc call get_live_cell")
+            Ok(self.inner2021.get_live_cell(out_point, with_data)?)
         } else {
             let out_point = item2019_to_item2021!(out_point);
-            item2019_to_item2021!(self
-                .inner2019
-                .get_live_cell(out_point, with_data)
-                .expect("rpc call get_live_cell"))
+            let cell = self.inner2019.get_live_cell(out_point, with_data)?;
+            Ok(item2019_to_item2021!(cell))
         }
     }
 
     pub fn get_tip_block_number(&self) -> CoreBlockNumber {
-        self.inner()
-            .get_tip_block_number()
+        self.try_get_tip_block_number()
             .expect("rpc call get_tip_block_number")
-            .into()
+    }
+
+    pub fn try_get_tip_block_number(&self) -> Result<CoreBlockNumber, RpcError> {
+        Ok(self.inner().get_tip_block_number()?.into())
     }
 
     pub fn get_current_epoch(&self) -> EpochView {
-        self.inner()
-            .get_current_epoch()
+        self.try_get_current_epoch()
             .expect("rpc call get_current_epoch")
     }
 
+    pub fn try_get_current_epoch(&self) -> Result<EpochView, RpcError> {
+        Ok(self.inner().get_current_epoch()?)
+    }
+
     pub fn get_epoch_by_number(&self, number: CoreEpochNumber) -> Option<EpochView> {
-        self.inner()
-            .get_epoch_by_number(number.into())
+        self.try_get_epoch_by_number(number)
             .expect("rpc call get_epoch_by_number")
     }
 
+    pub fn try_get_epoch_by_number(
+        &self,
+        number: CoreEpochNumber,
+    ) -> Result<Option<EpochView>, RpcError> {
+        Ok(self.inner().get_epoch_by_number(number.into())?)
+    }
+
     pub fn get_consensus(&self) -> Consensus {
+        self.try_get_consensus().expect("rpc call get_consensus")
+    }
+
+    pub fn try_get_consensus(&self) -> Result<Consensus, RpcError> {
         if self.ckb2021 {
-            self.inner2021
-                .get_consensus()
-                .expect("rpc call get_consensus")
+            Ok(self.inner2021.get_consensus()?)
         } else {
-            item2019_to_item2021!(self
-                .inner2019
-                .get_consensus()
-                .expect("rpc call get_consensus"))
+            let consensus = self.inner2019.get_consensus()?;
+            Ok(item2019_to_item2021!(consensus))
         }
     }
 
     pub fn local_node_info(&self) -> LocalNode {
-        self.inner()
-            .local_node_info()
+        self.try_local_node_info()
             .expect("rpc call local_node_info")
     }
 
+    pub fn try_local_node_info(&self) -> Result<LocalNode, RpcError> {
+        Ok(self.inner().local_node_info()?)
+    }
+
     pub fn get_peers(&self) -> Vec<RemoteNode> {
-        self.inner().get_peers().expect("rpc call get_peers")
+        self.try_get_peers().expect("rpc call get_peers")
+    }
+
+    pub fn try_get_peers(&self) -> Result<Vec<RemoteNode>, RpcError> {
+        Ok(self.inner().get_peers()?)
     }
 
     pub fn get_banned_addresses(&self) -> Vec<BannedAddr> {
-        self.inner()
-            .get_banned_addresses()
+        self.try_get_banned_addresses()
             .expect("rpc call get_banned_addresses")
     }
 
+    pub fn try_get_banned_addresses(&self) -> Result<Vec<BannedAddr>, RpcError> {
+        Ok(self.inner().get_banned_addresses()?)
+    }
+
     pub fn set_ban(
         &self,
         address: String,
@@ -249,32 +304,54 @@ impl RpcClient {
         absolute: Option<bool>,
         reason: Option<String>,
     ) {
-        self.inner()
-            .set_ban(address, command, ban_time, absolute, reason)
+        self.try_set_ban(address, command, ban_time, absolute, reason)
             .expect("rpc call set_ban")
     }
 
+    pub fn try_set_ban(
+        &self,
+        address: String,
+        command: String,
+        ban_time: Option<Timestamp>,
+        absolute: Option<bool>,
+        reason: Option<String>,
+    ) -> Result<(), RpcError> {
+        Ok(self
+            .inner()
+            .set_ban(address, command, ban_time, absolute, reason)?)
+    }
+
     pub fn get_block_template(
         &self,
         bytes_limit: Option<u64>,
         proposals_limit: Option<u64>,
         max_version: Option<CoreVersion>,
     ) -> BlockTemplate {
+        self.try_get_block_template(bytes_limit, proposals_limit, max_version)
+            .expect("rpc call get_block_template")
+    }
+
+    pub fn try_get_block_template(
+        &self,
+        bytes_limit: Option<u64>,
+        proposals_limit: Option<u64>,
+        max_version: Option<CoreVersion>,
+    ) -> Result<BlockTemplate, RpcError> {
         if self.ckb2021 {
             let bytes_limit = bytes_limit.map(Into::into);
             let proposals_limit = proposals_limit.map(Into::into);
             let max_version = max_version.map(Into::into);
-            self.inner2021
-                .get_block_template(bytes_limit, proposals_limit, max_version)
-                .expect("rpc call get_block_template2021")
+            Ok(self
+                .inner2021
+                .get_block_template(bytes_limit, proposals_limit, max_version)?)
         } else {
             let bytes_limit = bytes_limit.map(Into::into);
             let proposals_limit = proposals_limit.map(Into::into);
             let max_version = max_version.map(Into::into);
-            item2019_to_item2021!(self
-                .inner2019
-                .get_block_template(bytes_limit, proposals_limit, max_version)
-                .expect("rpc call get_block_template2019"))
+            let template =
+                self.inner2019
+                    .get_block_template(bytes_limit, proposals_limit, max_version)?;
+            Ok(item2019_to_item2021!(template))
         }
     }
 
@@ -291,18 +368,31 @@ impl RpcClient {
         }
     }
 
+    pub fn try_submit_block(&self, work_id: String, block: Block) -> Result<Byte32, RpcError> {
+        Ok(self.submit_block(work_id, block)?)
+    }
+
     pub fn get_blockchain_info(&self) -> ChainInfo {
-        self.inner()
-            .get_blockchain_info()
+        self.try_get_blockchain_info()
             .expect("rpc call get_blockchain_info")
     }
 
+    pub fn try_get_blockchain_info(&self) -> Result<ChainInfo, RpcError> {
+        Ok(self.inner().get_blockchain_info()?)
+    }
+
     pub fn get_block_median_time(&self, block_hash: Byte32) -> Option<Timestamp> {
-        self.inner()
-            .get_block_median_time(block_hash.unpack())
+        self.try_get_block_median_time(block_hash)
             .expect("rpc call get_block_median_time")
     }
 
+    pub fn try_get_block_median_time(
+        &self,
+        block_hash: Byte32,
+    ) -> Result<Option<Timestamp>, RpcError> {
+        Ok(self.inner().get_block_median_time(block_hash.unpack())?)
+    }
+
     pub fn send_transaction(&self, tx: Transaction) -> Byte32 {
         self.send_transaction_result(tx)
             .expect("rpc call send_transaction")
@@ -343,55 +433,85 @@ impl RpcClient {
         }
     }
 
+    pub fn try_send_transaction(&self, tx: Transaction) -> Result<Byte32, RpcError> {
+        Ok(self.send_transaction_result(tx)?)
+    }
+
     pub fn dry_run_transaction(&self, tx: Transaction) -> DryRunResult {
+        self.try_dry_run_transaction(tx)
+            .expect("rpc call dry_run_transaction")
+    }
+
+    pub fn try_dry_run_transaction(&self, tx: Transaction) -> Result<DryRunResult, RpcError> {
         if self.ckb2021 {
-            self.inner2021
-                .dry_run_transaction(tx)
-                .expect("rpc call dry_run_transaction")
+            Ok(self.inner2021.dry_run_transaction(tx)?)
         } else {
             let tx = item2019_to_item2021!(tx);
-            item2019_to_item2021!(self
-                .inner2019
-                .dry_run_transaction(tx)
-                .expect("rpc call dry_run_transaction"))
+            let result = self.inner2019.dry_run_transaction(tx)?;
+            Ok(item2019_to_item2021!(result))
         }
     }
 
     pub fn send_alert(&self, alert: Alert) {
-        self.inner().send_alert(alert).expect("rpc call send_alert")
+        self.try_send_alert(alert).expect("rpc call send_alert")
+    }
+
+    pub fn try_send_alert(&self, alert: Alert) -> Result<(), RpcError> {
+        Ok(self.inner().send_alert(alert)?)
     }
 
     pub fn tx_pool_info(&self) -> TxPoolInfo {
-        self.inner().tx_pool_info().expect("rpc call tx_pool_info")
+        self.try_tx_pool_info().expect("rpc call tx_pool_info")
+    }
+
+    pub fn try_tx_pool_info(&self) -> Result<TxPoolInfo, RpcError> {
+        Ok(self.inner().tx_pool_info()?)
     }
 
     pub fn add_node(&self, peer_id: String, address: String) {
-        self.inner()
-            .add_node(peer_id, address)
+        self.try_add_node(peer_id, address)
             .expect("rpc call add_node");
     }
 
+    pub fn try_add_node(&self, peer_id: String, address: String) -> Result<(), RpcError> {
+        Ok(self.inner().add_node(peer_id, address)?)
+    }
+
     pub fn remove_node(&self, peer_id: String) {
-        self.inner()
-            .remove_node(peer_id)
-            .expect("rpc call remove_node")
+        self.try_remove_node(peer_id).expect("rpc call remove_node")
+    }
+
+    pub fn try_remove_node(&self, peer_id: String) -> Result<(), RpcError> {
+        Ok(self.inner().remove_node(peer_id)?)
     }
 
     pub fn truncate(&self, target_tip_hash: Byte32) {
-        self.inner()
-            .truncate(target_tip_hash.unpack())
+        self.try_truncate(target_tip_hash)
             .expect("rpc call truncate")
     }
 
+    pub fn try_truncate(&self, target_tip_hash: Byte32) -> Result<(), RpcError> {
+        Ok(self.inner().truncate(target_tip_hash.unpack())?)
+    }
+
     pub fn calculate_dao_maximum_withdraw(
         &self,
         out_point: OutPoint,
         hash: Byte32,
     ) -> CoreCapacity {
-        self.inner()
-            .calculate_dao_maximum_withdraw(out_point, hash.unpack())
+        self.try_calculate_dao_maximum_withdraw(out_point, hash)
             .expect("rpc call calculate_dao_maximum_withdraw")
-            .into()
+    }
+
+    pub fn try_calculate_dao_maximum_withdraw(
+        &self,
+        out_point: OutPoint,
+        hash: Byte32,
+    ) -> Result<CoreCapacity, RpcError> {
+        let capacity = self
+            .inner()
+            .calculate_dao_maximum_withdraw(out_point, hash.unpack())?;
+        Ok(capacity.into())
     }
 
     pub fn process_block_without_verify(
@@ -399,29 +519,416 @@ impl RpcClient {
         block: Block,
         should_broadcast: bool,
     ) -> Option<Byte32> {
-        if self.ckb2021 {
+        self.try_process_block_without_verify(block, should_broadcast)
+            .expect("rpc call process_block_without_verify")
+    }
+
+    pub fn try_process_block_without_verify(
+        &self,
+        block: Block,
+        should_broadcast: bool,
+    ) -> Result<Option<Byte32>, RpcError> {
+        let hash = if self.ckb2021 {
             self.inner2021
-                .process_block_without_verify(block, should_broadcast)
-                .expect("rpc call process_block_without_verify")
-                .map(|h256| h256.pack())
+                .process_block_without_verify(block, should_broadcast)?
         } else {
             let block = item2021_to_item2019!(block);
             self.inner2019
-                .process_block_without_verify(block, should_broadcast)
-                .expect("rpc call process_block_without_verify")
-                .map(|h256| h256.pack())
-        }
+                .process_block_without_verify(block, should_broadcast)?
+        };
+        Ok(hash.map(|h256| h256.pack()))
+    }
+
+    pub fn calculate_dao_field(&self, block_template: BlockTemplate) -> Byte32 {
+        self.try_calculate_dao_field(block_template)
+            .expect("rpc call calculate_dao_field")
     }
 
-    pub fn calculate_dao_field(&self, block_template: BlockTemplate) -> Result<Byte32, AnyError> {
-        assert!(self.ckb2021);
-        self.inner2021
+    pub fn try_calculate_dao_field(
+        &self,
+        block_template: BlockTemplate,
+    ) -> Result<Byte32, RpcError> {
+        self.ensure_ckb2021("calculate_dao_field")?;
+        Ok(self
+            .inner2021
             .calculate_dao_field(block_template)
-            .map(Into::into)
+            .map(Into::into)?)
+    }
+
+    pub fn get_raw_tx_pool(&self, verbose: Option<bool>) -> RawTxPool {
+        self.try_get_raw_tx_pool(verbose)
+            .expect("rpc call get_raw_tx_pool")
+    }
+
+    pub fn try_get_raw_tx_pool(&self, verbose: Option<bool>) -> Result<RawTxPool, RpcError> {
+        self.ensure_ckb2021("get_raw_tx_pool")?;
+        Ok(self.inner2021.get_raw_tx_pool(verbose)?)
+    }
+
+    pub fn get_transaction_proof(
+        &self,
+        tx_hashes: Vec<Byte32>,
+        block_hash: Option<Byte32>,
+    ) -> TransactionProof {
+        self.try_get_transaction_proof(tx_hashes, block_hash)
+            .expect("rpc call get_transaction_proof")
+    }
+
+    pub fn try_get_transaction_proof(
+        &self,
+        tx_hashes: Vec<Byte32>,
+        block_hash: Option<Byte32>,
+    ) -> Result<TransactionProof, RpcError> {
+        self.ensure_ckb2021("get_transaction_proof")?;
+        let tx_hashes = tx_hashes.into_iter().map(|hash| hash.unpack()).collect();
+        let block_hash = block_hash.map(|hash| hash.unpack());
+        Ok(self
+            .inner2021
+            .get_transaction_proof(tx_hashes, block_hash)?)
+    }
+
+    /// Return the hashes of the transactions proved by `tx_proof`
+    pub fn verify_transaction_proof(&self, tx_proof: TransactionProof) -> Vec<Byte32> {
+        self.try_verify_transaction_proof(tx_proof)
+            .expect("rpc call verify_transaction_proof")
+    }
+
+    pub fn try_verify_transaction_proof(
+        &self,
+        tx_proof: TransactionProof,
+    ) -> Result<Vec<Byte32>, RpcError> {
+        self.ensure_ckb2021("verify_transaction_proof")?;
+        let tx_hashes = self.inner2021.verify_transaction_proof(tx_proof)?;
+        Ok(tx_hashes.into_iter().map(|hash| hash.pack()).collect())
+    }
+
+    pub fn get_block_economic_state(&self, block_hash: Byte32) -> Option<BlockEconomicState> {
+        self.try_get_block_economic_state(block_hash)
+            .expect("rpc call get_block_economic_state")
+    }
+
+    pub fn try_get_block_economic_state(
+        &self,
+        block_hash: Byte32,
+    ) -> Result<Option<BlockEconomicState>, RpcError> {
+        self.ensure_ckb2021("get_block_economic_state")?;
+        Ok(self
+            .inner2021
+            .get_block_economic_state(block_hash.unpack())?)
     }
 
-    pub fn get_raw_tx_pool(&self, verbose: Option<bool>) -> Result<RawTxPool, AnyError> {
-        assert!(self.ckb2021);
-        self.inner2021.get_raw_tx_pool(verbose)
+    pub fn get_cellbase_output_capacity_details(&self, block_hash: Byte32) -> Option<BlockReward> {
+        self.try_get_cellbase_output_capacity_details(block_hash)
+            .expect("rpc call get_cellbase_output_capacity_details")
+    }
+
+    pub fn try_get_cellbase_output_capacity_details(
+        &self,
+        block_hash: Byte32,
+    ) -> Result<Option<BlockReward>, RpcError> {
+        self.ensure_ckb2021("get_cellbase_output_capacity_details")?;
+        Ok(self
+            .inner2021
+            .get_cellbase_output_capacity_details(block_hash.unpack())?)
+    }
+
+    pub fn clear_tx_pool(&self) {
+        self.try_clear_tx_pool().expect("rpc call clear_tx_pool")
+    }
+
+    pub fn try_clear_tx_pool(&self) -> Result<(), RpcError> {
+        self.ensure_ckb2021("clear_tx_pool")?;
+        Ok(self.inner2021.clear_tx_pool()?)
+    }
+
+    /// Remove the transaction and its descendants from the tx-pool, return whether it was in
+    /// the tx-pool
+    pub fn remove_transaction(&self, tx_hash: Byte32) -> bool {
+        self.try_remove_transaction(tx_hash)
+            .expect("rpc call remove_transaction")
+    }
+
+    pub fn try_remove_transaction(&self, tx_hash: Byte32) -> Result<bool, RpcError> {
+        self.ensure_ckb2021("remove_transaction")?;
+        Ok(self.inner2021.remove_transaction(tx_hash.unpack())?)
+    }
+
+    pub fn sync_state(&self) -> SyncState {
+        self.try_sync_state().expect("rpc call sync_state")
+    }
+
+    pub fn try_sync_state(&self) -> Result<SyncState, RpcError> {
+        self.ensure_ckb2021("sync_state")?;
+        Ok(self.inner2021.sync_state()?)
+    }
+
+    pub fn set_network_active(&self, state: bool) {
+        self.try_set_network_active(state)
+            .expect("rpc call set_network_active")
+    }
+
+    pub fn try_set_network_active(&self, state: bool) -> Result<(), RpcError> {
+        self.ensure_ckb2021("set_network_active")?;
+        Ok(self.inner2021.set_network_active(state)?)
+    }
+
+    pub fn clear_banned_addresses(&self) {
+        self.try_clear_banned_addresses()
+            .expect("rpc call clear_banned_addresses")
+    }
+
+    pub fn try_clear_banned_addresses(&self) -> Result<(), RpcError> {
+        self.ensure_ckb2021("clear_banned_addresses")?;
+        Ok(self.inner2021.clear_banned_addresses()?)
+    }
+
+    pub fn ping_peers(&self) {
+        self.try_ping_peers().expect("rpc call ping_peers")
+    }
+
+    pub fn try_ping_peers(&self) -> Result<(), RpcError> {
+        self.ensure_ckb2021("ping_peers")?;
+        Ok(self.inner2021.ping_peers()?)
+    }
+
+    /// Check whether the tx-pool accepts `tx` without adding it into the tx-pool.
+    pub fn test_tx_pool_accept(&self, tx: Transaction) -> EntryCompleted {
+        self.try_test_tx_pool_accept(tx)
+            .expect("rpc call test_tx_pool_accept")
+    }
+
+    /// The rejection is returned as error, as it is usually what the cases assert on.
+    pub fn try_test_tx_pool_accept(&self, tx: Transaction) -> Result<EntryCompleted, RpcError> {
+        self.ensure_ckb2021("test_tx_pool_accept")?;
+        Ok(self
+            .inner2021
+            .test_tx_pool_accept(tx, Some("passthrough".to_string()))?)
+    }
+
+    /// Return the cycles of `tx`.
+    pub fn estimate_cycles(&self, tx: Transaction) -> CoreCycle {
+        self.try_estimate_cycles(tx)
+            .expect("rpc call estimate_cycles")
+    }
+
+    /// Return the cycles of `tx`, or the error if its scripts fail to run.
+    pub fn try_estimate_cycles(&self, tx: Transaction) -> Result<CoreCycle, RpcError> {
+        self.ensure_ckb2021("estimate_cycles")?;
+        Ok(self.inner2021.estimate_cycles(tx)?.cycles.into())
+    }
+
+    /// The statistics of the fee rates of the recent `target` blocks, `None` if no transactions
+    /// in them
+    pub fn get_fee_rate_statistics(&self, target: Option<u64>) -> Option<FeeRateStatistics> {
+        self.try_get_fee_rate_statistics(target)
+            .expect("rpc call get_fee_rate_statistics")
+    }
+
+    pub fn try_get_fee_rate_statistics(
+        &self,
+        target: Option<u64>,
+    ) -> Result<Option<FeeRateStatistics>, RpcError> {
+        self.ensure_ckb2021("get_fee_rate_statistics")?;
+        Ok(self
+            .inner2021
+            .get_fee_rate_statistics(target.map(Into::into))?)
+    }
+
+    /// Built-in indexer RPC, requires the node enabling the `Indexer` RPC module
+    pub fn get_cells(
+        &self,
+        search_key: IndexerSearchKey,
+        order: IndexerOrder,
+        limit: u32,
+        after: Option<JsonBytes>,
+    ) -> IndexerPagination<IndexerCell> {
+        self.try_get_cells(search_key, order, limit, after)
+            .expect("rpc call get_cells")
+    }
+
+    pub fn try_get_cells(
+        &self,
+        search_key: IndexerSearchKey,
+        order: IndexerOrder,
+        limit: u32,
+        after: Option<JsonBytes>,
+    ) -> Result<IndexerPagination<IndexerCell>, RpcError> {
+        self.ensure_ckb2021("get_cells")?;
+        Ok(self
+            .inner2021
+            .get_cells(search_key, order, limit.into(), after)?)
+    }
+
+    /// Built-in indexer RPC, requires the node enabling the `Indexer` RPC module
+    pub fn get_transactions(
+        &self,
+        search_key: IndexerSearchKey,
+        order: IndexerOrder,
+        limit: u32,
+        after: Option<JsonBytes>,
+    ) -> IndexerPagination<IndexerTx> {
+        self.try_get_transactions(search_key, order, limit, after)
+            .expect("rpc call get_transactions")
+    }
+
+    pub fn try_get_transactions(
+        &self,
+        search_key: IndexerSearchKey,
+        order: IndexerOrder,
+        limit: u32,
+        after: Option<JsonBytes>,
+    ) -> Result<IndexerPagination<IndexerTx>, RpcError> {
+        self.ensure_ckb2021("get_transactions")?;
+        Ok(self
+            .inner2021
+            .get_transactions(search_key, order, limit.into(), after)?)
+    }
+
+    /// Built-in indexer RPC, requires the node enabling the `Indexer` RPC module
+    pub fn get_cells_capacity(&self, search_key: IndexerSearchKey) -> Option<IndexerCellsCapacity> {
+        self.try_get_cells_capacity(search_key)
+            .expect("rpc call get_cells_capacity")
+    }
+
+    pub fn try_get_cells_capacity(
+        &self,
+        search_key: IndexerSearchKey,
+    ) -> Result<Option<IndexerCellsCapacity>, RpcError> {
+        self.ensure_ckb2021("get_cells_capacity")?;
+        Ok(self.inner2021.get_cells_capacity(search_key)?)
+    }
+
+    /// Send `calls` in one batch request, return the results in the order of `calls`.
+    ///
+    /// The results are the raw JSON values, without the ckb2019 compatibility conversions.
+    pub fn batch(&self, calls: Vec<Call>) -> Vec<Result<serde_json::Value, RpcError>> {
+        self.try_batch(calls).expect("rpc call batch")
+    }
+
+    pub fn try_batch(
+        &self,
+        calls: Vec<Call>,
+    ) -> Result<Vec<Result<serde_json::Value, RpcError>>, RpcError> {
+        let results = self.inner().batch(calls)?;
+        Ok(results
+            .into_iter()
+            .map(|result| result.map_err(Into::into))
+            .collect())
+    }
+
+    /// Get the blocks of `numbers` via batch requests, in the order of `numbers`.
+    pub fn get_blocks_by_number(
+        &self,
+        numbers: RangeInclusive<CoreBlockNumber>,
+    ) -> Vec<Option<BlockView>> {
+        self.try_get_blocks_by_number(numbers)
+            .expect("rpc call get_blocks_by_number")
+    }
+
+    pub fn try_get_blocks_by_number(
+        &self,
+        numbers: RangeInclusive<CoreBlockNumber>,
+    ) -> Result<Vec<Option<BlockView>>, RpcError> {
+        self.try_batch_by_number("get_block_by_number", numbers)?
+            .into_iter()
+            .map(|value| {
+                if self.ckb2021 {
+                    serde_json::from_value::<Option<BlockView>>(value).map_err(invalid_response)
+                } else {
+                    let block =
+                        serde_json::from_value::<Option<ckb2019_jsonrpc_types::BlockView>>(value)
+                            .map_err(invalid_response)?;
+                    Ok(item2019_to_item2021!(block))
+                }
+            })
+            .collect()
+    }
+
+    /// Get the headers of `numbers` via batch requests, in the order of `numbers`.
+    pub fn get_headers_by_number(
+        &self,
+        numbers: RangeInclusive<CoreBlockNumber>,
+    ) -> Vec<Option<HeaderView>> {
+        self.try_get_headers_by_number(numbers)
+            .expect("rpc call get_headers_by_number")
+    }
+
+    pub fn try_get_headers_by_number(
+        &self,
+        numbers: RangeInclusive<CoreBlockNumber>,
+    ) -> Result<Vec<Option<HeaderView>>, RpcError> {
+        self.try_batch_by_number("get_header_by_number", numbers)?
+            .into_iter()
+            .map(|value| {
+                if self.ckb2021 {
+                    serde_json::from_value::<Option<HeaderView>>(value).map_err(invalid_response)
+                } else {
+                    let header =
+                        serde_json::from_value::<Option<ckb2019_jsonrpc_types::HeaderView>>(value)
+                            .map_err(invalid_response)?;
+                    Ok(item2019_to_item2021!(header))
+                }
+            })
+            .collect()
+    }
+
+    // Call `method(number)` for every number of `numbers`, at most `MAX_BATCH_SIZE` calls in a
+    // batch request
+    fn try_batch_by_number(
+        &self,
+        method: &str,
+        numbers: RangeInclusive<CoreBlockNumber>,
+    ) -> Result<Vec<serde_json::Value>, RpcError> {
+        let numbers = numbers.collect::<Vec<_>>();
+        let mut values = Vec::with_capacity(numbers.len());
+        for chunk in numbers.chunks(batch::MAX_BATCH_SIZE) {
+            let calls = chunk
+                .iter()
+                .map(|number| Call::new(method, serde_json::json!([BlockNumber::from(*number)])))
+                .collect();
+            for result in self.try_batch(calls)? {
+                values.push(result?);
+            }
+        }
+        Ok(values)
+    }
+}
+
+fn invalid_response(err: serde_json::Error) -> RpcError {
+    RpcError::InvalidResponse(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RpcClient, RpcError};
+    use crate::MockCkb;
+    use ckb_types::packed::Byte32;
+
+    #[test]
+    fn test_ckb2021_methods_unsupported_by_ckb2019() {
+        let mock = MockCkb::start();
+        let rpc_client = RpcClient::new(mock.url(), false);
+        assert_eq!(
+            rpc_client.try_sync_state().unwrap_err(),
+            RpcError::Unsupported("sync_state")
+        );
+        assert_eq!(
+            rpc_client.try_get_raw_tx_pool(None).unwrap_err(),
+            RpcError::Unsupported("get_raw_tx_pool")
+        );
+        assert_eq!(
+            rpc_client
+                .try_get_transaction_proof(vec![Byte32::zero()], None)
+                .unwrap_err(),
+            RpcError::Unsupported("get_transaction_proof")
+        );
+        let err = rpc_client.try_clear_tx_pool().unwrap_err();
+        assert_eq!(err.code(), None);
+        assert_eq!(err.to_string(), "clear_tx_pool is not supported by ckb2019");
+
+        // The same methods of a ckb2021 client reach the node
+        let rpc_client = RpcClient::new(mock.url(), true);
+        assert!(rpc_client.try_get_raw_tx_pool(None).is_ok());
+        assert!(rpc_client.try_clear_tx_pool().is_ok());
     }
 }
@@ -0,0 +1,219 @@
+use jsonrpc_core::response::{Output, Response};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+lazy_static! {
+    // The file being recorded into
+    static ref RECORDING: Mutex<Option<File>> = Mutex::new(None);
+    // rpc_url => (case_name, node_name), registered by `Node::init`
+    static ref NODE_NAMES: RwLock<HashMap<String, (String, String)>> = Default::default();
+}
+
+// Avoid locking `RECORDING` for every request when not recording
+static IS_RECORDING: AtomicBool = AtomicBool::new(false);
+
+/// A JSON-RPC request and its response, a line of the JSONL file written by `RpcRecorder`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcRecord {
+    /// The case which initialized the node, empty for the nodes not initialized via `Node::init`
+    pub case_name: String,
+    /// The node name, or the rpc url for the nodes not initialized via `Node::init`
+    pub node_name: String,
+    pub url: String,
+    pub method: String,
+    pub params: serde_json::Value,
+    /// The result of the success response
+    pub result: Option<serde_json::Value>,
+    /// The error of the failure response
+    pub error: Option<serde_json::Value>,
+    /// The latency of the whole HTTP request, shared by the calls of a batch request
+    pub latency_ms: f64,
+}
+
+impl RpcRecord {
+    /// Load all the records from the JSONL file written by `RpcRecorder`.
+    pub fn load_all(path: &Path) -> Vec<RpcRecord> {
+        let content = fs::read_to_string(path)
+            .unwrap_or_else(|err| panic!("failed to read {}, error: {}", path.display(), err));
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+            .map(|(index, line)| {
+                serde_json::from_str(line).unwrap_or_else(|err| {
+                    panic!(
+                        "failed to parse line {} of {}, error: {}",
+                        index + 1,
+                        path.display(),
+                        err
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+/// Record every JSON-RPC request sent by `RpcClient` and `AsyncRpcClient` of this process,
+/// along with its response, into a JSONL file of `RpcRecord`s, until the recorder is dropped.
+///
+/// Recording is opt-in and process-wide. Records are appended, so that several processes can
+/// record into the same file, e.g. the workers of `ckb-integration-test run --jobs`. Replay the
+/// records with `RpcReplayer`.
+///
+/// ```ignore
+/// let _recorder = RpcRecorder::start(Path::new("rpc.jsonl"));
+/// ```
+pub struct RpcRecorder {
+    path: PathBuf,
+}
+
+impl RpcRecorder {
+    pub fn start(path: &Path) -> Self {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).unwrap_or_else(|err| {
+                    panic!(
+                        "failed to create dir \"{}\", error: {}",
+                        parent.display(),
+                        err
+                    )
+                });
+            }
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap_or_else(|err| panic!("failed to open {}, error: {}", path.display(), err));
+        let mut recording = RECORDING.lock().unwrap();
+        assert!(recording.is_none(), "RpcRecorder is already started");
+        *recording = Some(file);
+        IS_RECORDING.store(true, Ordering::SeqCst);
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for RpcRecorder {
+    fn drop(&mut self) {
+        IS_RECORDING.store(false, Ordering::SeqCst);
+        RECORDING.lock().unwrap().take();
+    }
+}
+
+pub(crate) fn register_node(rpc_url: &str, case_name: &str, node_name: &str) {
+    NODE_NAMES.write().unwrap().insert(
+        rpc_url.to_string(),
+        (case_name.to_string(), node_name.to_string()),
+    );
+}
+
+/// Record a single request and its response, if recording.
+#[doc(hidden)]
+pub fn record(
+    url: &reqwest::Url,
+    request: &serde_json::Map<String, serde_json::Value>,
+    output: &Output,
+    latency: Duration,
+) {
+    if !IS_RECORDING.load(Ordering::SeqCst) {
+        return;
+    }
+    let record = new_record(url, request, output, latency);
+    write_records(&[record]);
+}
+
+/// Record every call of a batch request and its output, if recording.
+#[doc(hidden)]
+pub fn record_batch(
+    url: &reqwest::Url,
+    request: &serde_json::Value,
+    response: &Response,
+    latency: Duration,
+) {
+    if !IS_RECORDING.load(Ordering::SeqCst) {
+        return;
+    }
+    let outputs = match response {
+        Response::Batch(outputs) => outputs.iter().collect::<Vec<_>>(),
+        Response::Single(output) => vec![output],
+    };
+    let records = request
+        .as_array()
+        .map(|calls| calls.iter().filter_map(|call| call.as_object()))
+        .into_iter()
+        .flatten()
+        .filter_map(|call| {
+            let output = outputs
+                .iter()
+                .find(|output| serde_json::to_value(output.id()).ok().as_ref() == call.get("id"))?;
+            Some(new_record(url, call, output, latency))
+        })
+        .collect::<Vec<_>>();
+    write_records(&records);
+}
+
+fn new_record(
+    url: &reqwest::Url,
+    request: &serde_json::Map<String, serde_json::Value>,
+    output: &Output,
+    latency: Duration,
+) -> RpcRecord {
+    let (case_name, node_name) = NODE_NAMES
+        .read()
+        .unwrap()
+        .get(url.as_str())
+        .cloned()
+        .unwrap_or_else(|| (String::new(), url.to_string()));
+    let (result, error) = match output {
+        Output::Success(success) => (Some(success.result.clone()), None),
+        Output::Failure(failure) => (None, serde_json::to_value(&failure.error).ok()),
+    };
+    RpcRecord {
+        case_name,
+        node_name,
+        url: url.to_string(),
+        method: request
+            .get("method")
+            .and_then(|method| method.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        params: request
+            .get("params")
+            .cloned()
+            .unwrap_or(serde_json::Value::Null),
+        result,
+        error,
+        latency_ms: latency.as_secs_f64() * 1000.0,
+    }
+}
+
+fn write_records(records: &[RpcRecord]) {
+    let mut content = String::new();
+    for record in records {
+        content.push_str(&serde_json::to_string(record).expect("serialize RpcRecord"));
+        content.push('\n');
+    }
+    let mut recording = RECORDING.lock().unwrap();
+    if let Some(file) = recording.as_mut() {
+        // Write the lines at once, as other processes may append into the same file
+        if let Err(err) = file
+            .write_all(content.as_bytes())
+            .and_then(|_| file.flush())
+        {
+            crate::error!("failed to write the rpc records, error: {}", err);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use super::http_server::{self, HttpServer};
+use super::recorder::RpcRecord;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// Serve the responses recorded by `RpcRecorder` from a local HTTP server, standing in for the
+/// recorded node, so that a case or a helper can be debugged offline without spawning ckb.
+///
+/// The requests are matched by method and params. The same request is answered with its
+/// recorded responses in the recorded order, and then always the last one, e.g. the recorded
+/// `get_tip_block_number` responses are replayed as the chain grows and then stops at the last
+/// tip. The requests never recorded are answered with an error.
+///
+/// ```ignore
+/// let records = RpcRecord::load_all(Path::new("rpc.jsonl"))
+///     .into_iter()
+///     .filter(|record| record.node_name == "node2021")
+///     .collect();
+/// let replayer = RpcReplayer::start(records);
+/// let rpc_client = RpcClient::new(replayer.url(), true);
+/// ```
+pub struct RpcReplayer {
+    server: HttpServer,
+}
+
+// (method, params) => the recorded responses, `Ok(result)` or `Err(error)`
+type Responses = HashMap<(String, String), VecDeque<Result<serde_json::Value, serde_json::Value>>>;
+
+impl RpcReplayer {
+    pub fn start(records: Vec<RpcRecord>) -> Self {
+        let mut responses: Responses = HashMap::new();
+        for record in records {
+            let key = (record.method, normalize_params(&record.params));
+            let response = match (record.result, record.error) {
+                (_, Some(error)) => Err(error),
+                (result, None) => Ok(result.unwrap_or(serde_json::Value::Null)),
+            };
+            responses.entry(key).or_default().push_back(response);
+        }
+        let responses = Mutex::new(responses);
+        let handler = move |request: &serde_json::Value| {
+            let method = request
+                .get("method")
+                .and_then(|method| method.as_str())
+                .unwrap_or_default()
+                .to_string();
+            let params = request
+                .get("params")
+                .map(normalize_params)
+                .unwrap_or_else(|| normalize_params(&serde_json::Value::Null));
+            let mut responses = responses.lock().unwrap();
+            let recorded = responses
+                .get_mut(&(method.clone(), params.clone()))
+                .and_then(|queue| {
+                    if queue.len() > 1 {
+                        queue.pop_front()
+                    } else {
+                        queue.front().cloned()
+                    }
+                });
+            match recorded {
+                Some(Ok(result)) => http_server::success(request, result),
+                Some(Err(error)) => serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": request.get("id").cloned().unwrap_or(serde_json::Value::Null),
+                    "error": error,
+                }),
+                None => http_server::failure(
+                    request,
+                    jsonrpc_core::Error::method_not_found(),
+                    &format!("no recorded response of {}({})", method, params),
+                ),
+            }
+        };
+        Self {
+            server: HttpServer::start(Arc::new(handler)),
+        }
+    }
+
+    /// The url of the stand-in node, e.g. "http://127.0.0.1:34567/"
+    pub fn url(&self) -> &str {
+        self.server.url()
+    }
+}
+
+// `null` and `[]` are the same empty params
+fn normalize_params(params: &serde_json::Value) -> String {
+    match params {
+        serde_json::Value::Null => "[]".to_string(),
+        params => params.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RpcReplayer;
+    use crate::{Call, MockCkb, RpcClient, RpcRecord, RpcRecorder};
+
+    #[test]
+    fn test_replay_recorded_responses() {
+        let mock = MockCkb::start();
+        let dir = tempfile::tempdir()
+            .unwrap_or_else(|err| panic!("failed to create tempdir, error: {}", err));
+        let path = dir.path().join("rpc.jsonl");
+        let batch_calls = vec![
+            Call::new("get_block_hash", serde_json::json!(["0x1"])),
+            Call::new("get_unknown", serde_json::Value::Null),
+        ];
+        let (recorded_hash, recorded_batch) = {
+            let _recorder = RpcRecorder::start(&path);
+            let rpc_client = mock.rpc_client();
+            assert_eq!(rpc_client.get_tip_block_number(), 0);
+            mock.chain().mine(2);
+            assert_eq!(rpc_client.get_tip_block_number(), 2);
+            let hash = rpc_client.get_block_hash(1);
+            assert!(hash.is_some());
+            (hash, rpc_client.batch(batch_calls.clone()))
+        };
+
+        // The recorder is process-wide, keep only the requests sent to this mock
+        let records: Vec<RpcRecord> = RpcRecord::load_all(&path)
+            .into_iter()
+            .filter(|record| record.url == mock.url())
+            .collect();
+        let methods: Vec<&str> = records
+            .iter()
+            .map(|record| record.method.as_str())
+            .collect();
+        assert_eq!(
+            methods,
+            vec![
+                "get_tip_block_number",
+                "get_tip_block_number",
+                "get_block_hash",
+                "get_block_hash",
+                "get_unknown",
+            ]
+        );
+        assert!(records[4].result.is_none());
+        assert!(records[4].error.is_some());
+
+        let replayer = RpcReplayer::start(records);
+        let rpc_client = RpcClient::new(replayer.url(), true);
+        assert_eq!(rpc_client.get_tip_block_number(), 0);
+        assert_eq!(rpc_client.get_tip_block_number(), 2);
+        // The last recorded response is repeated
+        assert_eq!(rpc_client.get_tip_block_number(), 2);
+        assert_eq!(rpc_client.get_block_hash(1), recorded_hash);
+        assert_eq!(rpc_client.get_block_hash(1), recorded_hash);
+
+        let replayed_batch = rpc_client.batch(batch_calls);
+        assert_eq!(replayed_batch.len(), 2);
+        assert_eq!(
+            replayed_batch[0].as_ref().ok(),
+            recorded_batch[0].as_ref().ok()
+        );
+        assert!(replayed_batch[0].is_ok());
+        assert!(replayed_batch[1].is_err());
+
+        // The requests never recorded are answered with an error
+        assert!(rpc_client.try_get_block_hash(2).is_err());
+        let results = rpc_client.batch(vec![Call::new(
+            "get_block_hash",
+            serde_json::json!(["0x2"]),
+        )]);
+        assert!(results[0].is_err());
+    }
+}
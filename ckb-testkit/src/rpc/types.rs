@@ -0,0 +1,159 @@
+//! The RPC types which are not in ckb-jsonrpc-types 0.101, as the methods were introduced by the
+//! later ckb versions. They follow the JSON of the ckb RPC.
+
+use ckb_jsonrpc_types::{
+    BlockNumber, Capacity, CellOutput, JsonBytes, OutPoint, Script, Uint32, Uint64,
+};
+use ckb_types::H256;
+use serde::{Deserialize, Serialize};
+
+/// The result of `estimate_cycles`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EstimateCycles {
+    pub cycles: Uint64,
+}
+
+/// The result of `test_tx_pool_accept`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryCompleted {
+    pub cycles: Uint64,
+    pub fee: Capacity,
+}
+
+/// The result of `get_fee_rate_statistics`, in shannons per kilo-weight
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeRateStatistics {
+    pub mean: Uint64,
+    pub median: Uint64,
+}
+
+/// The search key of the indexer RPCs `get_cells`, `get_transactions` and `get_cells_capacity`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexerSearchKey {
+    pub script: Script,
+    pub script_type: IndexerScriptType,
+    pub script_search_mode: Option<IndexerSearchMode>,
+    pub filter: Option<IndexerSearchKeyFilter>,
+    pub with_data: Option<bool>,
+    pub group_by_transaction: Option<bool>,
+}
+
+impl IndexerSearchKey {
+    /// Search the cells or transactions of the lock script, with the default options
+    pub fn lock(script: Script) -> Self {
+        Self::new(script, IndexerScriptType::Lock)
+    }
+
+    /// Search the cells or transactions of the type script, with the default options
+    pub fn type_(script: Script) -> Self {
+        Self::new(script, IndexerScriptType::Type)
+    }
+
+    fn new(script: Script, script_type: IndexerScriptType) -> Self {
+        Self {
+            script,
+            script_type,
+            script_search_mode: None,
+            filter: None,
+            with_data: None,
+            group_by_transaction: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexerScriptType {
+    Lock,
+    Type,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexerSearchMode {
+    Prefix,
+    Exact,
+    Partial,
+}
+
+/// The ranges are `[start, end)`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexerSearchKeyFilter {
+    pub script: Option<Script>,
+    pub script_len_range: Option<[Uint64; 2]>,
+    pub output_data_len_range: Option<[Uint64; 2]>,
+    pub output_capacity_range: Option<[Uint64; 2]>,
+    pub block_range: Option<[BlockNumber; 2]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexerOrder {
+    Desc,
+    Asc,
+}
+
+/// A page of the indexer results, pass `last_cursor` as `after` to get the next page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexerPagination<T> {
+    pub objects: Vec<T>,
+    pub last_cursor: JsonBytes,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexerCell {
+    pub output: CellOutput,
+    pub output_data: Option<JsonBytes>,
+    pub out_point: OutPoint,
+    pub block_number: BlockNumber,
+    pub tx_index: Uint32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexerCellType {
+    Input,
+    Output,
+}
+
+/// A transaction of `get_transactions`, grouped if `group_by_transaction` is set
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum IndexerTx {
+    Ungrouped(IndexerTxWithCell),
+    Grouped(IndexerTxWithCells),
+}
+
+impl IndexerTx {
+    pub fn tx_hash(&self) -> &H256 {
+        match self {
+            IndexerTx::Ungrouped(tx) => &tx.tx_hash,
+            IndexerTx::Grouped(tx) => &tx.tx_hash,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexerTxWithCell {
+    pub tx_hash: H256,
+    pub block_number: BlockNumber,
+    pub tx_index: Uint32,
+    pub io_index: Uint32,
+    pub io_type: IndexerCellType,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexerTxWithCells {
+    pub tx_hash: H256,
+    pub block_number: BlockNumber,
+    pub tx_index: Uint32,
+    pub cells: Vec<(IndexerCellType, Uint32)>,
+}
+
+/// The result of `get_cells_capacity`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexerCellsCapacity {
+    pub capacity: Capacity,
+    pub block_hash: H256,
+    pub block_number: BlockNumber,
+}
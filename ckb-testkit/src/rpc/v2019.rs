@@ -6,7 +6,7 @@ use ckb2019_jsonrpc_types::{
 };
 use ckb_types::H256;
 
-jsonrpc!(pub struct Inner2019 {
+jsonrpc!(pub struct Inner2019, AsyncInner2019 {
     pub fn get_block(&self, _hash: H256) -> Option<BlockView>;
     pub fn get_fork_block(&self, _hash: H256) -> Option<BlockView>;
     pub fn get_block_by_number(&self, _number: BlockNumber) -> Option<BlockView>;
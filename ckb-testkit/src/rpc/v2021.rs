@@ -1,12 +1,17 @@
+use super::types::{
+    EntryCompleted, EstimateCycles, FeeRateStatistics, IndexerCell, IndexerCellsCapacity,
+    IndexerOrder, IndexerPagination, IndexerSearchKey, IndexerTx,
+};
 use ckb_jsonrpc_types::{
-    Alert, BannedAddr, Block, BlockNumber, BlockTemplate, BlockView, Byte32, Capacity,
-    CellWithStatus, ChainInfo, Consensus, DryRunResult, EpochNumber, EpochView, HeaderView,
-    JsonBytes, LocalNode, OutPoint, RawTxPool, RemoteNode, Script, Timestamp, Transaction,
-    TransactionWithStatus, TxPoolInfo, Uint64, Version,
+    Alert, BannedAddr, Block, BlockEconomicState, BlockNumber, BlockReward, BlockTemplate,
+    BlockView, Byte32, Capacity, CellWithStatus, ChainInfo, Consensus, DryRunResult, EpochNumber,
+    EpochView, HeaderView, JsonBytes, LocalNode, OutPoint, RawTxPool, RemoteNode, Script,
+    SyncState, Timestamp, Transaction, TransactionProof, TransactionWithStatus, TxPoolInfo, Uint32,
+    Uint64, Version,
 };
 use ckb_types::H256;
 
-jsonrpc!(pub struct Inner2021 {
+jsonrpc!(pub struct Inner2021, AsyncInner2021 {
     pub fn get_block(&self, _hash: H256) -> Option<BlockView>;
     pub fn get_fork_block(&self, _hash: H256) -> Option<BlockView>;
     pub fn get_block_by_number(&self, _number: BlockNumber) -> Option<BlockView>;
@@ -20,10 +25,18 @@ jsonrpc!(pub struct Inner2021 {
     pub fn get_current_epoch(&self) -> EpochView;
     pub fn get_epoch_by_number(&self, number: EpochNumber) -> Option<EpochView>;
     pub fn get_consensus(&self) -> Consensus;
+    pub fn get_transaction_proof(&self, tx_hashes: Vec<H256>, block_hash: Option<H256>) -> TransactionProof;
+    pub fn verify_transaction_proof(&self, tx_proof: TransactionProof) -> Vec<H256>;
+    pub fn get_block_economic_state(&self, block_hash: H256) -> Option<BlockEconomicState>;
+    pub fn get_cellbase_output_capacity_details(&self, block_hash: H256) -> Option<BlockReward>;
 
     pub fn local_node_info(&self) -> LocalNode;
     pub fn get_peers(&self) -> Vec<RemoteNode>;
     pub fn get_banned_addresses(&self) -> Vec<BannedAddr>;
+    pub fn clear_banned_addresses(&self) -> ();
+    pub fn sync_state(&self) -> SyncState;
+    pub fn set_network_active(&self, state: bool) -> ();
+    pub fn ping_peers(&self) -> ();
     pub fn set_ban(
         &self,
         address: String,
@@ -45,6 +58,11 @@ jsonrpc!(pub struct Inner2021 {
     pub fn dry_run_transaction(&self, _tx: Transaction) -> DryRunResult;
     pub fn send_transaction(&self, tx: Transaction, outputs_validator: Option<String>) -> H256;
     pub fn tx_pool_info(&self) -> TxPoolInfo;
+    pub fn clear_tx_pool(&self) -> ();
+    pub fn remove_transaction(&self, tx_hash: H256) -> bool;
+    pub fn test_tx_pool_accept(&self, tx: Transaction, outputs_validator: Option<String>) -> EntryCompleted;
+    pub fn estimate_cycles(&self, tx: Transaction) -> EstimateCycles;
+    pub fn get_fee_rate_statistics(&self, target: Option<Uint64>) -> Option<FeeRateStatistics>;
 
     pub fn send_alert(&self, alert: Alert) -> ();
 
@@ -58,4 +76,20 @@ jsonrpc!(pub struct Inner2021 {
     pub fn get_raw_tx_pool(&self, verbose: Option<bool>) -> RawTxPool;
 
     pub fn calculate_dao_maximum_withdraw(&self, _out_point: OutPoint, _hash: H256) -> Capacity;
+
+    pub fn get_cells(
+        &self,
+        search_key: IndexerSearchKey,
+        order: IndexerOrder,
+        limit: Uint32,
+        after: Option<JsonBytes>
+    ) -> IndexerPagination<IndexerCell>;
+    pub fn get_transactions(
+        &self,
+        search_key: IndexerSearchKey,
+        order: IndexerOrder,
+        limit: Uint32,
+        after: Option<JsonBytes>
+    ) -> IndexerPagination<IndexerTx>;
+    pub fn get_cells_capacity(&self, search_key: IndexerSearchKey) -> Option<IndexerCellsCapacity>;
 });
@@ -0,0 +1,67 @@
+use super::stream_codec::StreamCodec;
+use super::SubscriptionAddress;
+use futures::{SinkExt, StreamExt};
+use std::io;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tokio_util::codec::Framed;
+
+/// A connection to the subscription service of ckb, sending and receiving JSON-RPC messages.
+pub(super) enum Connection {
+    Tcp(Framed<TcpStream, StreamCodec>),
+    Ws(Box<WebSocketStream<MaybeTlsStream<TcpStream>>>),
+}
+
+impl Connection {
+    pub(super) async fn connect(address: &SubscriptionAddress) -> io::Result<Self> {
+        match address {
+            SubscriptionAddress::Tcp(address) => {
+                let stream = TcpStream::connect(address).await?;
+                Ok(Connection::Tcp(Framed::new(
+                    stream,
+                    StreamCodec::stream_incoming(),
+                )))
+            }
+            SubscriptionAddress::Ws(address) => {
+                let (stream, _) = tokio_tungstenite::connect_async(format!("ws://{}", address))
+                    .await
+                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+                Ok(Connection::Ws(Box::new(stream)))
+            }
+        }
+    }
+
+    pub(super) async fn send(&mut self, message: String) -> io::Result<()> {
+        match self {
+            Connection::Tcp(framed) => framed.send(message).await,
+            Connection::Ws(stream) => stream
+                .send(Message::Text(message))
+                .await
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err)),
+        }
+    }
+
+    /// Receive the next message, `None` if the connection is closed.
+    pub(super) async fn recv(&mut self) -> io::Result<Option<serde_json::Value>> {
+        loop {
+            let message = match self {
+                Connection::Tcp(framed) => match framed.next().await {
+                    Some(frame) => frame?.to_vec(),
+                    None => return Ok(None),
+                },
+                Connection::Ws(stream) => match stream.next().await {
+                    Some(Ok(Message::Text(text))) => text.into_bytes(),
+                    Some(Ok(Message::Binary(bytes))) => bytes,
+                    Some(Ok(Message::Close(_))) | None => return Ok(None),
+                    // Ping and pong are answered by tungstenite
+                    Some(Ok(_)) => continue,
+                    Some(Err(err)) => return Err(io::Error::new(io::ErrorKind::Other, err)),
+                },
+            };
+            return serde_json::from_slice(&message)
+                .map(Some)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err));
+        }
+    }
+}
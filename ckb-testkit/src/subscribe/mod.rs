@@ -1,512 +1,479 @@
-/// This file is copied from https://github.com/nervosnetwork/ckb-cli/blob/271261e7bd5e54b15b2468a64505eb5183325915/ckb-sdk/src/pubsub/mod.rs
-///
-/// This module provides a general rpc subscription client,
-/// you can use it with any connection method that implements `AsyncWrite + AsyncRead`.
-/// The simplest TCP connection is as follows:
-///
-/// ```ignore
-/// use ckb_jsonrpc_types::HeaderView;
-/// use ckb_types::core::HeaderView as CoreHeaderView;
-/// use tokio::net::{TcpStream, ToSocketAddrs};
-///
-/// pub async fn new_tcp_client(addr: impl ToSocketAddrs) -> io::Result<Client<TcpStream>> {
-///     let tcp = TcpStream::connect(addr).await?;
-///     Ok(Client::new(tcp))
-/// }
-///
-/// fn main() {
-///     let mut rt = tokio::runtime::Runtime::new().unwrap();
-///     rt.block_on(async {
-///         let c = new_tcp_client("127.0.0.1:18114").await.unwrap();
-///         let mut h = c
-///             .subscribe::<HeaderView>("new_tip_header")
-///             .await
-///             .unwrap();
-///         while let Some(Ok(r)) = h.next().await {
-///             let core: CoreHeaderView = r.into();
-///             println!(
-///                 "number: {}, difficulty: {}, epoch: {}, timestamp: {}",
-///                 core.number(),
-///                 core.difficulty(),
-///                 core.epoch(),
-///                 core.timestamp()
-///             )
-///         }
-///     });   
-/// }
-/// ```
-///
-use std::{
-    collections::{HashMap, VecDeque},
-    io,
-    marker::PhantomData,
-    pin::Pin,
-    task::{Context, Poll},
-};
-
-use futures::{
-    sink::SinkExt,
-    stream::{Stream, StreamExt},
-};
-use serde::{Deserialize, Serialize};
-use tokio_util::codec::Framed;
-
-use stream_codec::StreamCodec;
-use tokio::net::{TcpStream, ToSocketAddrs};
-
-mod stream_codec {
-    /// copy from jsonrpc [service-util](https://github.com/paritytech/jsonrpc/blob/master/server-utils/src/stream_codec.rs)
-    ///
-    /// I changed the return value of decode to BytesMut. It is not a good idea to parse it into a string in the codec.
-    /// It will cause one more copy, but it is necessary to check whether it is utf8 encoding
-    use bytes::BytesMut;
-    use std::{io, str};
-
-    /// Separator for enveloping messages in streaming codecs
-    #[derive(Debug, Clone)]
-    pub enum Separator {
-        /// No envelope is expected between messages. Decoder will try to figure out
-        /// message boundaries by accumulating incoming bytes until valid JSON is formed.
-        /// Encoder will send messages without any boundaries between requests.
-        Empty,
-        /// Byte is used as an sentitel between messages
-        Byte(u8),
-    }
+//! The client of ckb's subscription RPC, over the raw TCP service or the WebSocket service, i.e.
+//! `rpc.tcp_listen_address` or `rpc.ws_listen_address` of ckb.toml.
+//!
+//! ```ignore
+//! node.update_config(vec![("rpc.tcp_listen_address", format!("127.0.0.1:{}", port))]);
+//! let subscriptions = node.subscriptions();
+//! node.mine(1);
+//! let header = subscriptions.wait_for_tip_header(1, Duration::from_secs(10));
+//! ```
+
+mod connection;
+mod stream_codec;
+#[cfg(test)]
+mod tests;
+
+use ckb_jsonrpc_types::{BlockView, HeaderView, PoolTransactionEntry, PoolTransactionReject};
+use ckb_types::{core::BlockNumber, packed::Byte32, prelude::*, H256};
+use connection::Connection;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The default maximal number of buffered events, the oldest events are dropped beyond it.
+pub const SUBSCRIPTION_QUEUE_CAPACITY: usize = 10_000;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const RECONNECT_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubscriptionTopic {
+    NewTipHeader,
+    NewTipBlock,
+    NewTransaction,
+    ProposedTransaction,
+    RejectedTransaction,
+}
 
-    impl Default for Separator {
-        fn default() -> Self {
-            Separator::Byte(b'\n')
+impl SubscriptionTopic {
+    pub const ALL: [SubscriptionTopic; 5] = [
+        SubscriptionTopic::NewTipHeader,
+        SubscriptionTopic::NewTipBlock,
+        SubscriptionTopic::NewTransaction,
+        SubscriptionTopic::ProposedTransaction,
+        SubscriptionTopic::RejectedTransaction,
+    ];
+
+    /// The topic name of the RPC `subscribe`
+    pub fn name(&self) -> &'static str {
+        match self {
+            SubscriptionTopic::NewTipHeader => "new_tip_header",
+            SubscriptionTopic::NewTipBlock => "new_tip_block",
+            SubscriptionTopic::NewTransaction => "new_transaction",
+            SubscriptionTopic::ProposedTransaction => "proposed_transaction",
+            SubscriptionTopic::RejectedTransaction => "rejected_transaction",
         }
     }
+}
 
-    /// Stream codec for streaming protocols (ipc, tcp)
-    #[derive(Debug, Default)]
-    pub struct StreamCodec {
-        incoming_separator: Separator,
-        outgoing_separator: Separator,
-    }
+#[derive(Debug, Clone)]
+pub enum SubscriptionEvent {
+    NewTipHeader(HeaderView),
+    NewTipBlock(BlockView),
+    NewTransaction(PoolTransactionEntry),
+    ProposedTransaction(PoolTransactionEntry),
+    RejectedTransaction(PoolTransactionEntry, PoolTransactionReject),
+}
 
-    impl StreamCodec {
-        /// Default codec with streaming input data. Input can be both enveloped and not.
-        pub fn stream_incoming() -> Self {
-            StreamCodec::new(Separator::Empty, Default::default())
+impl SubscriptionEvent {
+    pub fn topic(&self) -> SubscriptionTopic {
+        match self {
+            SubscriptionEvent::NewTipHeader(_) => SubscriptionTopic::NewTipHeader,
+            SubscriptionEvent::NewTipBlock(_) => SubscriptionTopic::NewTipBlock,
+            SubscriptionEvent::NewTransaction(_) => SubscriptionTopic::NewTransaction,
+            SubscriptionEvent::ProposedTransaction(_) => SubscriptionTopic::ProposedTransaction,
+            SubscriptionEvent::RejectedTransaction(_, _) => SubscriptionTopic::RejectedTransaction,
         }
+    }
 
-        /// New custom stream codec
-        pub fn new(incoming_separator: Separator, outgoing_separator: Separator) -> Self {
-            StreamCodec {
-                incoming_separator,
-                outgoing_separator,
+    // The notification result is the JSON string of the event
+    fn parse(topic: SubscriptionTopic, result: &str) -> serde_json::Result<Self> {
+        let event = match topic {
+            SubscriptionTopic::NewTipHeader => {
+                SubscriptionEvent::NewTipHeader(serde_json::from_str(result)?)
             }
-        }
+            SubscriptionTopic::NewTipBlock => {
+                SubscriptionEvent::NewTipBlock(serde_json::from_str(result)?)
+            }
+            SubscriptionTopic::NewTransaction => {
+                SubscriptionEvent::NewTransaction(serde_json::from_str(result)?)
+            }
+            SubscriptionTopic::ProposedTransaction => {
+                SubscriptionEvent::ProposedTransaction(serde_json::from_str(result)?)
+            }
+            SubscriptionTopic::RejectedTransaction => {
+                let (entry, reject) = serde_json::from_str(result)?;
+                SubscriptionEvent::RejectedTransaction(entry, reject)
+            }
+        };
+        Ok(event)
     }
+}
+
+/// The address of ckb's subscription service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubscriptionAddress {
+    /// `rpc.tcp_listen_address`, e.g. "127.0.0.1:18114"
+    Tcp(String),
+    /// `rpc.ws_listen_address`, e.g. "127.0.0.1:28114"
+    Ws(String),
+}
 
-    fn is_whitespace(byte: u8) -> bool {
-        matches!(byte, 0x0D | 0x0A | 0x20 | 0x09)
+impl SubscriptionAddress {
+    /// Read the subscription address from ckb.toml, the TCP service is preferred if both are
+    /// configured. Return `None` if neither is configured.
+    pub fn from_app_config(app_config: &Path) -> Option<Self> {
+        let content = fs::read_to_string(app_config).unwrap_or_else(|err| {
+            panic!("failed to read {}, error: {}", app_config.display(), err)
+        });
+        let document = content
+            .parse::<toml_edit::Document>()
+            .unwrap_or_else(|err| {
+                panic!("failed to parse {}, error: {}", app_config.display(), err)
+            });
+        let rpc = document.get("rpc")?;
+        let listen_address = |key: &str| {
+            rpc.get(key)
+                .and_then(|address| address.as_str())
+                .map(|address| address.replace("0.0.0.0", "127.0.0.1"))
+        };
+        listen_address("tcp_listen_address")
+            .map(SubscriptionAddress::Tcp)
+            .or_else(|| listen_address("ws_listen_address").map(SubscriptionAddress::Ws))
     }
+}
 
-    impl tokio_util::codec::Decoder for StreamCodec {
-        type Item = BytesMut;
-        type Error = io::Error;
+/// Subscribe the topics of a ckb node in background, buffering the events in a bounded queue.
+///
+/// The subscriptions are resumed when the connection drops, e.g. the node is restarted, but the
+/// events in between are lost. The background thread stops when `Subscriptions` is dropped.
+pub struct Subscriptions {
+    address: SubscriptionAddress,
+    shared: Arc<Shared>,
+    stopped_signal_sender: Option<tokio::sync::oneshot::Sender<()>>,
+    worker: Option<thread::JoinHandle<()>>,
+}
 
-        fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<Self::Item>> {
-            if let Separator::Byte(separator) = self.incoming_separator {
-                if let Some(i) = buf.as_ref().iter().position(|&b| b == separator) {
-                    let line = buf.split_to(i);
-                    let _ = buf.split_to(1);
+struct Shared {
+    capacity: usize,
+    state: Mutex<State>,
+    condvar: Condvar,
+}
 
-                    match str::from_utf8(&line.as_ref()) {
-                        Ok(_) => Ok(Some(line)),
-                        Err(_) => Err(io::Error::new(io::ErrorKind::Other, "invalid UTF-8")),
-                    }
-                } else {
-                    Ok(None)
-                }
-            } else {
-                let mut depth = 0;
-                let mut in_str = false;
-                let mut is_escaped = false;
-                let mut start_idx = 0;
-                let mut whitespaces = 0;
-
-                for idx in 0..buf.as_ref().len() {
-                    let byte = buf.as_ref()[idx];
-
-                    if (byte == b'{' || byte == b'[') && !in_str {
-                        if depth == 0 {
-                            start_idx = idx;
-                        }
-                        depth += 1;
-                    } else if (byte == b'}' || byte == b']') && !in_str {
-                        depth -= 1;
-                    } else if byte == b'"' && !is_escaped {
-                        in_str = !in_str;
-                    } else if is_whitespace(byte) {
-                        whitespaces += 1;
-                    }
-                    if byte == b'\\' && !is_escaped && in_str {
-                        is_escaped = true;
-                    } else {
-                        is_escaped = false;
-                    }
+#[derive(Default)]
+struct State {
+    events: VecDeque<SubscriptionEvent>,
+    dropped: usize,
+    connected: bool,
+    connections: usize,
+}
 
-                    if depth == 0 && idx != start_idx && idx - start_idx + 1 > whitespaces {
-                        let bts = buf.split_to(idx + 1);
-                        match str::from_utf8(bts.as_ref()) {
-                            Ok(_) => return Ok(Some(bts)),
-                            Err(_) => {
-                                return Ok(None);
-                            } // skip non-utf requests (TODO: log error?)
-                        };
+impl Subscriptions {
+    /// Start subscribing `topics` in background, buffering at most `capacity` events.
+    pub fn start(
+        address: SubscriptionAddress,
+        topics: &[SubscriptionTopic],
+        capacity: usize,
+    ) -> Self {
+        let shared = Arc::new(Shared {
+            capacity,
+            state: Default::default(),
+            condvar: Condvar::new(),
+        });
+        let (stopped_signal_sender, stopped_signal_receiver) = tokio::sync::oneshot::channel();
+        let log_target = crate::LOG_TARGET.with(|c| c.borrow().clone());
+        let worker = {
+            let address = address.clone();
+            let topics = topics.to_vec();
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || {
+                crate::LOG_TARGET.with(|c| {
+                    *c.borrow_mut() = log_target;
+                });
+                let rt = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap();
+                rt.block_on(async move {
+                    tokio::select! {
+                        _ = keep_subscribing(&address, &topics, &shared) => {},
+                        _ = stopped_signal_receiver => {},
                     }
-                }
-                Ok(None)
-            }
+                });
+            })
+        };
+        Self {
+            address,
+            shared,
+            stopped_signal_sender: Some(stopped_signal_sender),
+            worker: Some(worker),
         }
     }
 
-    impl tokio_util::codec::Encoder<String> for StreamCodec {
-        type Error = io::Error;
-
-        fn encode(&mut self, msg: String, buf: &mut BytesMut) -> io::Result<()> {
-            let mut payload = msg.into_bytes();
-            if let Separator::Byte(separator) = self.outgoing_separator {
-                payload.push(separator);
-            }
-            buf.extend_from_slice(&payload);
-            Ok(())
-        }
+    pub fn address(&self) -> &SubscriptionAddress {
+        &self.address
     }
-}
 
-/// General rpc subscription client
-pub struct Client<T> {
-    inner: Framed<T, StreamCodec>,
-    id: usize,
-}
+    /// Whether all the topics are subscribed on the current connection
+    pub fn is_connected(&self) -> bool {
+        self.shared.state.lock().unwrap().connected
+    }
 
-impl<T> Client<T>
-where
-    T: tokio::io::AsyncWrite + tokio::io::AsyncRead + Unpin,
-{
-    /// New a pubsub rpc client
-    pub fn new(io: T) -> Client<T> {
-        let inner = Framed::new(io, StreamCodec::stream_incoming());
-        Client { inner, id: 0 }
+    /// The number of successful connections so far, more than 1 means reconnected
+    pub fn connections(&self) -> usize {
+        self.shared.state.lock().unwrap().connections
     }
 
-    /// Subscription a topic
-    pub async fn subscribe<F: for<'de> serde::de::Deserialize<'de>>(
-        mut self,
-        name: &str,
-    ) -> io::Result<Handle<T, F>> {
-        let mut topic_list = HashMap::default();
-        let mut pending_recv = VecDeque::new();
-
-        subscribe(
-            &mut self.inner,
-            self.id,
-            name,
-            &mut topic_list,
-            &mut pending_recv,
-        )
-        .await?;
-        self.id = self.id.wrapping_add(1);
-
-        Ok(Handle {
-            inner: self.inner,
-            topic_list,
-            output: PhantomData::default(),
-            rpc_id: self.id,
-            pending_recv,
-        })
+    /// The number of the events dropped as the queue was full
+    pub fn dropped(&self) -> usize {
+        self.shared.state.lock().unwrap().dropped
     }
 
-    /// Subscription topics
-    pub async fn subscribe_list<
-        F: for<'de> serde::de::Deserialize<'de>,
-        I: Iterator<Item = H>,
-        H: AsRef<str>,
-    >(
-        mut self,
-        name_list: I,
-    ) -> io::Result<Handle<T, F>> {
-        let mut topic_list = HashMap::default();
-        let mut pending_recv = VecDeque::new();
-
-        for topic in name_list {
-            subscribe(
-                &mut self.inner,
-                self.id,
-                topic,
-                &mut topic_list,
-                &mut pending_recv,
-            )
-            .await?;
-            self.id = self.id.wrapping_add(1);
+    /// Wait until all the topics are subscribed, return false on timeout.
+    pub fn wait_for_connected(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.state.lock().unwrap();
+        while !state.connected {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            state = self
+                .shared
+                .condvar
+                .wait_timeout(state, deadline - now)
+                .unwrap()
+                .0;
         }
-
-        Ok(Handle {
-            inner: self.inner,
-            topic_list,
-            output: PhantomData::default(),
-            rpc_id: self.id,
-            pending_recv,
-        })
+        true
     }
-}
 
-/// General rpc subscription topic handle
-pub struct Handle<T, F> {
-    inner: Framed<T, StreamCodec>,
-    topic_list: HashMap<String, String>,
-    output: PhantomData<F>,
-    rpc_id: usize,
-    pending_recv: VecDeque<bytes::BytesMut>,
-}
-
-impl<T, F> Handle<T, F>
-where
-    T: tokio::io::AsyncWrite + tokio::io::AsyncRead + Unpin,
-{
-    /// Sub ids
-    pub fn ids(&self) -> impl Iterator<Item = &String> {
-        self.topic_list.keys()
+    /// Take the buffered events
+    pub fn drain(&self) -> Vec<SubscriptionEvent> {
+        self.shared.state.lock().unwrap().events.drain(..).collect()
     }
 
-    /// Topic names
-    pub fn topics(&self) -> impl Iterator<Item = &String> {
-        self.topic_list.values()
+    /// Wait for an event matching `predicate`. The events before it are consumed, like
+    /// `LogWatcher::wait_for_log`.
+    pub fn wait_for<P>(&self, mut predicate: P, timeout: Duration) -> Option<SubscriptionEvent>
+    where
+        P: FnMut(&SubscriptionEvent) -> bool,
+    {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.state.lock().unwrap();
+        loop {
+            while let Some(event) = state.events.pop_front() {
+                if predicate(&event) {
+                    return Some(event);
+                }
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return None;
+            }
+            state = self
+                .shared
+                .condvar
+                .wait_timeout(state, deadline - now)
+                .unwrap()
+                .0;
+        }
     }
 
-    /// if topic is empty, return Ok, else Err
-    pub fn try_into(self) -> Result<Client<T>, Self> {
-        if self.topic_list.is_empty() {
-            Ok(Client {
-                inner: self.inner,
-                id: self.rpc_id,
-            })
-        } else {
-            Err(self)
+    /// Wait for the new tip header whose number is `number` or higher
+    pub fn wait_for_tip_header(
+        &self,
+        number: BlockNumber,
+        timeout: Duration,
+    ) -> Option<HeaderView> {
+        let event = self.wait_for(
+            |event| match event {
+                SubscriptionEvent::NewTipHeader(header) => header.inner.number.value() >= number,
+                _ => false,
+            },
+            timeout,
+        );
+        match event {
+            Some(SubscriptionEvent::NewTipHeader(header)) => Some(header),
+            _ => None,
         }
     }
 
-    pub async fn subscribe(mut self, topic: &str) -> io::Result<Self> {
-        if self.topic_list.iter().any(|(_, v)| *v == topic) {
-            return Ok(self);
+    /// Wait for the transaction entering the tx-pool
+    pub fn wait_for_new_transaction(
+        &self,
+        tx_hash: &Byte32,
+        timeout: Duration,
+    ) -> Option<PoolTransactionEntry> {
+        let tx_hash: H256 = tx_hash.unpack();
+        let event = self.wait_for(
+            |event| match event {
+                SubscriptionEvent::NewTransaction(entry) => entry.transaction.hash == tx_hash,
+                _ => false,
+            },
+            timeout,
+        );
+        match event {
+            Some(SubscriptionEvent::NewTransaction(entry)) => Some(entry),
+            _ => None,
         }
-
-        subscribe(
-            &mut self.inner,
-            self.rpc_id,
-            topic,
-            &mut self.topic_list,
-            &mut self.pending_recv,
-        )
-        .await?;
-        self.rpc_id = self.rpc_id.wrapping_add(1);
-
-        Ok(self)
     }
 
-    /// Unsubscribe one topic
-    pub async fn unsubscribe(&mut self, topic: &str) -> io::Result<()> {
-        let id = {
-            let id = self
-                .topic_list
-                .iter()
-                .find_map(|(k, v)| if v == topic { Some(k) } else { None })
-                .cloned();
-            if id.is_none() {
-                return Ok(());
-            }
-            id.unwrap()
-        };
-        let req_json = format!(
-            r#"{{"id": {}, "jsonrpc": "2.0", "method": "unsubscribe", "params": ["{}"]}}"#,
-            self.rpc_id, id
+    /// Wait for the transaction rejected by the tx-pool, return the reject reason
+    pub fn wait_for_rejected_transaction(
+        &self,
+        tx_hash: &Byte32,
+        timeout: Duration,
+    ) -> Option<PoolTransactionReject> {
+        let tx_hash: H256 = tx_hash.unpack();
+        let event = self.wait_for(
+            |event| match event {
+                SubscriptionEvent::RejectedTransaction(entry, _) => {
+                    entry.transaction.hash == tx_hash
+                }
+                _ => false,
+            },
+            timeout,
         );
-        self.rpc_id = self.rpc_id.wrapping_add(1);
-
-        self.inner.send(req_json).await?;
-
-        let output = loop {
-            let resp = self.inner.next().await;
-
-            let resp = resp.ok_or_else::<io::Error, _>(|| io::ErrorKind::BrokenPipe.into())??;
-
-            // Since the current subscription state, the return value may be a notification,
-            // we need to ensure that the unsubscribed message returns before jumping out
-            match serde_json::from_slice::<jsonrpc_core::response::Output>(&resp) {
-                Ok(output) => break output,
-                Err(_) => self.pending_recv.push_back(resp),
-            }
-        };
-
-        match output {
-            jsonrpc_core::response::Output::Success(_) => {
-                self.topic_list.remove(&id);
-                Ok(())
-            }
-            jsonrpc_core::response::Output::Failure(e) => {
-                Err(io::Error::new(io::ErrorKind::InvalidData, e.error))
-            }
+        match event {
+            Some(SubscriptionEvent::RejectedTransaction(_, reject)) => Some(reject),
+            _ => None,
         }
     }
+}
 
-    /// Unsubscribe and return this Client
-    pub async fn unsubscribe_all(mut self) -> io::Result<Client<T>> {
-        for topic in self.topic_list.clone().values() {
-            self.unsubscribe(topic).await?
+impl Drop for Subscriptions {
+    fn drop(&mut self) {
+        if let Some(sender) = self.stopped_signal_sender.take() {
+            let _ = sender.send(());
+        }
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
         }
-        Ok(Client {
-            inner: self.inner,
-            id: self.rpc_id,
-        })
     }
 }
 
-impl<T, F> Stream for Handle<T, F>
-where
-    F: for<'de> serde::de::Deserialize<'de> + Unpin,
-    T: tokio::io::AsyncWrite + tokio::io::AsyncRead + Unpin,
-{
-    type Item = io::Result<(String, F)>;
-
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let parse = |data: bytes::BytesMut,
-                     topic_list: &HashMap<String, String>|
-         -> io::Result<(String, F)> {
-            let output = serde_json::from_slice::<jsonrpc_core::request::Notification>(&data)
-                .expect("must parse to notification");
-            let message = output
-                .params
-                .parse::<Message>()
-                .expect("must parse to message");
-            serde_json::from_str::<F>(&message.result)
-                .map(|r| (topic_list.get(&message.subscription).cloned().unwrap(), r))
-                .map_err(|_| io::ErrorKind::InvalidData.into())
-        };
+impl Shared {
+    fn set_connected(&self, connected: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.connected = connected;
+        if connected {
+            state.connections += 1;
+        }
+        self.condvar.notify_all();
+    }
 
-        if let Some(data) = self.pending_recv.pop_front() {
-            return Poll::Ready(Some(parse(data, &self.topic_list)));
+    fn push(&self, event: SubscriptionEvent) {
+        let mut state = self.state.lock().unwrap();
+        if state.events.len() >= self.capacity {
+            state.events.pop_front();
+            state.dropped += 1;
         }
-        match self.inner.poll_next_unpin(cx) {
-            Poll::Ready(Some(Ok(frame))) => Poll::Ready(Some(parse(frame, &self.topic_list))),
-            Poll::Ready(None) => Poll::Ready(None),
-            Poll::Pending => Poll::Pending,
-            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err))),
+        state.events.push_back(event);
+        self.condvar.notify_all();
+    }
+
+    // Push the event of the notification message, ignore the others
+    fn notify(
+        &self,
+        subscription_topics: &HashMap<String, SubscriptionTopic>,
+        message: &serde_json::Value,
+    ) {
+        let params = match message.get("params") {
+            Some(params) => params,
+            None => return,
+        };
+        let topic = params
+            .get("subscription")
+            .and_then(|id| subscription_topics.get(&id.to_string()));
+        let result = params.get("result").and_then(|result| result.as_str());
+        if let (Some(topic), Some(result)) = (topic, result) {
+            match SubscriptionEvent::parse(*topic, result) {
+                Ok(event) => self.push(event),
+                Err(err) => crate::error!(
+                    "failed to parse the {} notification, error: {}",
+                    topic.name(),
+                    err
+                ),
+            }
         }
     }
 }
 
-#[derive(Deserialize, Serialize, Debug)]
-struct Message {
-    result: String,
-    subscription: String,
+// Subscribe the topics, and subscribe again once the connection drops
+async fn keep_subscribing(
+    address: &SubscriptionAddress,
+    topics: &[SubscriptionTopic],
+    shared: &Shared,
+) {
+    loop {
+        match tokio::time::timeout(CONNECT_TIMEOUT, Connection::connect(address)).await {
+            Ok(Ok(mut connection)) => {
+                if let Err(err) = serve(&mut connection, topics, shared).await {
+                    crate::debug!("subscription to {:?} dropped, error: {}", address, err);
+                }
+                shared.set_connected(false);
+            }
+            Ok(Err(err)) => {
+                crate::debug!("failed to connect {:?}, error: {}", address, err);
+            }
+            Err(_) => {
+                crate::debug!("timeout to connect {:?}", address);
+            }
+        }
+        tokio::time::sleep(RECONNECT_INTERVAL).await;
+    }
 }
 
-async fn subscribe<T: tokio::io::AsyncWrite + tokio::io::AsyncRead + Unpin>(
-    io: &mut Framed<T, StreamCodec>,
-    id: usize,
-    topic: impl AsRef<str>,
-    topic_list: &mut HashMap<String, String>,
-    pending_recv: &mut VecDeque<bytes::BytesMut>,
+// Subscribe the topics on the connection and receive the notifications until it drops
+async fn serve(
+    connection: &mut Connection,
+    topics: &[SubscriptionTopic],
+    shared: &Shared,
 ) -> io::Result<()> {
-    // telnet localhost 18114
-    // > {"id": 2, "jsonrpc": "2.0", "method": "subscribe", "params": ["new_tip_header"]}
-    // < {"jsonrpc":"2.0","result":0,"id":2}
-    // < {"jsonrpc":"2.0","method":"subscribe","params":{"result":"...block header json...",
-    // "subscription":0}}
-    // < {"jsonrpc":"2.0","method":"subscribe","params":{"result":"...block header json...",
-    // "subscription":0}}
-    // < ...
-    // > {"id": 2, "jsonrpc": "2.0", "method": "unsubscribe", "params": [0]}
-    // < {"jsonrpc":"2.0","result":true,"id":2}
-    let req_json = format!(
-        r#"{{"id": {}, "jsonrpc": "2.0", "method": "subscribe", "params": ["{}"]}}"#,
-        id,
-        topic.as_ref()
-    );
-
-    io.send(req_json).await?;
-
-    // loop util this subscribe success
-    loop {
-        let resp = io.next().await;
-        let resp = resp.ok_or_else::<io::Error, _>(|| io::ErrorKind::BrokenPipe.into())??;
-        match serde_json::from_slice::<jsonrpc_core::response::Output>(&resp) {
-            Ok(output) => match output {
-                jsonrpc_core::response::Output::Success(success) => {
-                    let res = serde_json::from_value::<String>(success.result).unwrap();
-                    topic_list.insert(res, topic.as_ref().to_owned());
-                    break Ok(());
+    // subscription id => topic
+    let mut subscription_topics = HashMap::new();
+    for (id, topic) in topics.iter().enumerate() {
+        // > {"id": 0, "jsonrpc": "2.0", "method": "subscribe", "params": ["new_tip_header"]}
+        // < {"jsonrpc": "2.0", "result": "0x0", "id": 0}
+        let request = serde_json::json!({
+            "id": id,
+            "jsonrpc": "2.0",
+            "method": "subscribe",
+            "params": [topic.name()],
+        });
+        connection.send(request.to_string()).await?;
+        loop {
+            let message = recv(connection).await?;
+            if message.get("id") != Some(&serde_json::json!(id)) {
+                // The notification of the topics subscribed before
+                shared.notify(&subscription_topics, &message);
+                continue;
+            }
+            match message.get("result") {
+                Some(subscription_id) => {
+                    subscription_topics.insert(subscription_id.to_string(), *topic);
+                    break;
                 }
-                jsonrpc_core::response::Output::Failure(e) => {
-                    return Err(io::Error::new(io::ErrorKind::InvalidData, e.error))
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "failed to subscribe {}, response: {}",
+                            topic.name(),
+                            message
+                        ),
+                    ))
                 }
-            },
-            // must be Notification message
-            Err(_) => pending_recv.push_back(resp),
+            }
         }
     }
-}
-
-pub async fn subscribe_new_tip_block<A: ToSocketAddrs>(
-    addr: A,
-) -> Result<Handle<TcpStream, ckb_jsonrpc_types::BlockView>, io::Error> {
-    let c = Client::new(TcpStream::connect(addr).await?);
-    c
-        .subscribe_list::<ckb_jsonrpc_types::BlockView, _, _>(vec!["new_tip_block"].iter())
-        .await
-        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "not a subscribe port, please set ckb `tcp_listen_address` to use subscribe rpc feature"))
-}
+    shared.set_connected(true);
 
-pub async fn subscribe_new_tip_header<A: ToSocketAddrs>(
-    addr: A,
-) -> Result<Handle<TcpStream, ckb_jsonrpc_types::HeaderView>, io::Error> {
-    let c = Client::new(TcpStream::connect(addr).await?);
-    c
-        .subscribe_list::<ckb_jsonrpc_types::HeaderView, _, _>(vec!["new_tip_header"].iter())
-        .await
-        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "not a subscribe port, please set ckb `tcp_listen_address` to use subscribe rpc feature"))
-}
-
-pub async fn subscribe_new_transaction<A: ToSocketAddrs>(
-    addr: A,
-) -> Result<Handle<TcpStream, ckb_jsonrpc_types::PoolTransactionEntry>, io::Error> {
-    let c = Client::new(TcpStream::connect(addr).await?);
-    c
-        .subscribe_list::<ckb_jsonrpc_types::PoolTransactionEntry, _, _>(vec!["new_transaction"].iter())
-        .await
-        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "not a subscribe port, please set ckb `tcp_listen_address` to use subscribe rpc feature"))
-}
-
-pub async fn subscribe_proposed_transaction<A: ToSocketAddrs>(
-    addr: A,
-) -> Result<Handle<TcpStream, ckb_jsonrpc_types::PoolTransactionEntry>, io::Error> {
-    let c = Client::new(TcpStream::connect(addr).await?);
-    c
-        .subscribe_list::<ckb_jsonrpc_types::PoolTransactionEntry, _, _>(vec!["proposed_transaction"].iter())
-        .await
-        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "not a subscribe port, please set ckb `tcp_listen_address` to use subscribe rpc feature"))
+    // < {"jsonrpc": "2.0", "method": "subscribe", "params": {"result": "...", "subscription": "0x0"}}
+    loop {
+        let message = recv(connection).await?;
+        shared.notify(&subscription_topics, &message);
+    }
 }
 
-pub async fn subscribe_rejected_transaction<A: ToSocketAddrs>(
-    addr: A,
-) -> Result<
-    Handle<
-        TcpStream,
-        (
-            ckb_jsonrpc_types::PoolTransactionEntry,
-            ckb_jsonrpc_types::PoolTransactionReject,
-        ),
-    >,
-    io::Error,
-> {
-    let c = Client::new(TcpStream::connect(addr).await?);
-    c
-        .subscribe_list::<(ckb_jsonrpc_types::PoolTransactionEntry,ckb_jsonrpc_types::PoolTransactionReject), _, _>(vec!["rejected_transaction"].iter())
-        .await
-        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "not a subscribe port, please set ckb `tcp_listen_address` to use subscribe rpc feature"))
+async fn recv(connection: &mut Connection) -> io::Result<serde_json::Value> {
+    connection
+        .recv()
+        .await?
+        .ok_or_else(|| io::ErrorKind::BrokenPipe.into())
 }
@@ -0,0 +1,144 @@
+use super::{SubscriptionAddress, SubscriptionEvent, SubscriptionTopic, Subscriptions};
+use ckb_types::{core::HeaderBuilder, prelude::*};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// Answer the subscribe requests of a connection like ckb does, then notify the new tip headers
+fn serve_subscriptions(stream: &TcpStream, tip_numbers: &[u64]) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream.try_clone().unwrap();
+    let mut new_tip_header_id = None;
+    for _ in 0..SubscriptionTopic::ALL.len() {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let request: serde_json::Value = serde_json::from_str(&line).unwrap();
+        let subscription_id = format!("{:#x}", request["id"].as_u64().unwrap());
+        if request["params"][0] == "new_tip_header" {
+            new_tip_header_id = Some(subscription_id.clone());
+        }
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "result": subscription_id,
+            "id": request["id"],
+        });
+        writeln!(writer, "{}", response).unwrap();
+    }
+    for number in tip_numbers {
+        let header = HeaderBuilder::default().number(number.pack()).build();
+        let result = serde_json::to_string(&ckb_jsonrpc_types::HeaderView::from(header)).unwrap();
+        let notification = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "subscribe",
+            "params": {
+                "result": result,
+                "subscription": new_tip_header_id,
+            },
+        });
+        writeln!(writer, "{}", notification).unwrap();
+    }
+}
+
+#[test]
+fn test_from_app_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let app_config = dir.path().join("ckb.toml");
+    std::fs::write(&app_config, "[rpc]\nlisten_address = \"127.0.0.1:8114\"\n").unwrap();
+    assert_eq!(SubscriptionAddress::from_app_config(&app_config), None);
+
+    std::fs::write(
+        &app_config,
+        "[rpc]\nws_listen_address = \"0.0.0.0:28114\"\n",
+    )
+    .unwrap();
+    assert_eq!(
+        SubscriptionAddress::from_app_config(&app_config),
+        Some(SubscriptionAddress::Ws("127.0.0.1:28114".to_string()))
+    );
+
+    std::fs::write(
+        &app_config,
+        "[rpc]\ntcp_listen_address = \"127.0.0.1:18114\"\nws_listen_address = \"127.0.0.1:28114\"\n",
+    )
+    .unwrap();
+    assert_eq!(
+        SubscriptionAddress::from_app_config(&app_config),
+        Some(SubscriptionAddress::Tcp("127.0.0.1:18114".to_string()))
+    );
+}
+
+#[test]
+fn test_reconnect() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || {
+        // The first connection drops after notifying tip 1
+        let (stream, _) = listener.accept().unwrap();
+        serve_subscriptions(&stream, &[1]);
+        drop(stream);
+
+        let (stream, _) = listener.accept().unwrap();
+        serve_subscriptions(&stream, &[2]);
+        // Keep the connection until the client drops it
+        let mut line = String::new();
+        let _ = BufReader::new(stream).read_line(&mut line);
+    });
+
+    let subscriptions = Subscriptions::start(
+        SubscriptionAddress::Tcp(address),
+        &SubscriptionTopic::ALL,
+        10,
+    );
+    assert!(subscriptions.wait_for_connected(TIMEOUT));
+    let header = subscriptions.wait_for_tip_header(1, TIMEOUT).unwrap();
+    assert_eq!(header.inner.number.value(), 1);
+    let header = subscriptions.wait_for_tip_header(2, TIMEOUT).unwrap();
+    assert_eq!(header.inner.number.value(), 2);
+    assert_eq!(subscriptions.connections(), 2);
+    assert!(subscriptions.is_connected());
+}
+
+#[test]
+fn test_bounded_queue() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        serve_subscriptions(&stream, &[1, 2, 3, 4, 5]);
+        let mut line = String::new();
+        let _ = BufReader::new(stream).read_line(&mut line);
+    });
+
+    let subscriptions = Subscriptions::start(
+        SubscriptionAddress::Tcp(address),
+        &SubscriptionTopic::ALL,
+        2,
+    );
+    let start_time = Instant::now();
+    while subscriptions.dropped() < 3 {
+        assert!(
+            start_time.elapsed() < TIMEOUT,
+            "timeout to receive the notifications"
+        );
+        thread::sleep(Duration::from_millis(100));
+    }
+    // Only the latest events are kept
+    let numbers = subscriptions
+        .drain()
+        .into_iter()
+        .filter_map(|event| match event {
+            SubscriptionEvent::NewTipHeader(header) => Some(header.inner.number.value()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(numbers, vec![4, 5]);
+    assert_eq!(
+        subscriptions
+            .wait_for(|_| true, Duration::from_millis(100))
+            .map(|event| event.topic()),
+        None
+    );
+}
@@ -21,11 +21,25 @@ pub const FLAG_SINCE_TIMESTAMP: u64 =
 
 lazy_static! {
     pub static ref PORT_COUNTER: AtomicU16 = AtomicU16::new(9000);
+    // Exclusive
+    pub static ref PORT_RANGE_END: AtomicU16 = AtomicU16::new(u16::MAX);
+}
+
+/// Restrict `find_available_port` to `[start, end)`. Processes running concurrently should use
+/// disjoint ranges, otherwise a port found available by one may be taken by another before the
+/// ckb process binds it.
+pub fn set_port_range(start: u16, end: u16) {
+    assert!(start < end, "invalid port range [{}, {})", start, end);
+    PORT_COUNTER.store(start, SeqCst);
+    PORT_RANGE_END.store(end, SeqCst);
 }
 
 pub fn find_available_port() -> u16 {
     for _ in 0..2000 {
         let port = PORT_COUNTER.fetch_add(1, SeqCst);
+        if port >= PORT_RANGE_END.load(SeqCst) {
+            break;
+        }
         let address = SocketAddrV4::new(Ipv4Addr::LOCALHOST, port);
         if TcpListener::bind(address).is_ok() {
             return port;